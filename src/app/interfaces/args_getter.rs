@@ -1,10 +1,76 @@
+use crate::domain::aggregates::common::InputFormat;
+use crate::domain::aggregates::v1::cue::{CdTextLanguage, GapLayout, Header};
+use crate::domain::services::common::{LintLevel, LintOptions};
+use crate::share::{DiagnosticFormat, TextEncoding, TextStyle};
 use std::path::PathBuf;
 
 pub trait ArgsGetter {
-    fn load(&self) -> Result<Args, String>;
+    fn load(&self) -> Result<Command, String>;
+    // エラーや警告の出力形式
+    fn diagnostic_format(&self) -> DiagnosticFormat {
+        DiagnosticFormat::Text
+    }
 }
 
-pub struct Args {
+pub enum Command {
+    Build(BuildArgs),
+    Diff(DiffArgs),
+    Inspect(InspectArgs),
+    Lint(LintArgs),
+    Migrate(MigrateArgs),
+}
+
+pub enum OutputFormat {
+    Cue,
+    Toc(CdTextLanguage),
+    AudacityLabels,
+    FfMetadata,
+    MatroskaChapters,
+    Csv,
+    Tsv,
+    Markdown,
+    M3u8,
+    Tags,
+}
+
+pub struct BuildArgs {
+    pub input_file_path: PathBuf,
+    pub input_format: Option<InputFormat>,
+    pub target_file_path: PathBuf,
+    pub output_format: OutputFormat,
+    pub encoding: TextEncoding,
+    pub transliterate: bool,
+    pub style: TextStyle,
+    pub header: Header,
+    pub replaygain: bool,
+    pub disc_id: bool,
+    pub write_inherited_info: bool,
+    pub gap_layout: Option<GapLayout>,
+}
+
+pub struct DiffArgs {
+    pub old_file_path: PathBuf,
+    pub new_file_path: PathBuf,
+    // JSONなどの形式(拡張子がcueのファイルはcue sheetとして読む)
+    pub input_format: Option<InputFormat>,
+    pub cue_encoding: TextEncoding,
+}
+
+pub struct InspectArgs {
+    pub input_file_path: PathBuf,
+    pub input_format: Option<InputFormat>,
+}
+
+pub struct LintArgs {
+    pub input_file_path: PathBuf,
+    pub input_format: Option<InputFormat>,
+    pub options: LintOptions,
+    // ルールIDごとの重大度(指定のないルールは既定の重大度)
+    pub levels: Vec<(String, LintLevel)>,
+}
+
+pub struct MigrateArgs {
     pub input_file_path: PathBuf,
+    pub input_format: Option<InputFormat>,
     pub target_file_path: PathBuf,
 }
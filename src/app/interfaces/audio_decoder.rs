@@ -0,0 +1,9 @@
+use crate::domain::aggregates::common::AudioFormat;
+use crate::share::Issue;
+use std::path::Path;
+
+pub trait AudioDecoder {
+    fn probe(&self, path: &Path) -> Result<AudioFormat, Issue>;
+    // on_samplesにはチャンネル順にインターリーブされた-1.0〜1.0の値を渡す
+    fn decode(&self, path: &Path, on_samples: &mut dyn FnMut(&[f32])) -> Result<(), Issue>;
+}
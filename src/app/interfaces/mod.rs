@@ -1,2 +1,4 @@
 pub mod args_getter;
 pub use args_getter::*;
+pub mod audio_decoder;
+pub use audio_decoder::*;
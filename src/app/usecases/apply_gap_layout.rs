@@ -0,0 +1,23 @@
+use super::probe_file_lengths;
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::v1::cue::{Cue, GapLayout};
+use crate::share::Issue;
+
+// 前のファイルの末尾に置くプリギャップの位置は音声ファイルの長さから決める
+pub fn apply_gap_layout<D: AudioDecoder>(
+    cue: &mut Cue,
+    gap_layout: Option<GapLayout>,
+    decoder: &D,
+) -> Result<(), Issue> {
+    let needs_lengths = cue.has_appended_pregaps() || gap_layout == Some(GapLayout::Noncompliant);
+    let file_lengths = if needs_lengths {
+        probe_file_lengths(cue, decoder)?
+    } else {
+        vec![None; cue.files().len()]
+    };
+    cue.resolve_appended_pregaps(&file_lengths)?;
+    if let Some(gap_layout) = gap_layout {
+        cue.convert_gap_layout(gap_layout, &file_lengths)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::v1::cue::{Cue, File as CueFile};
+use crate::domain::services::common::{Loudness, LoudnessMeter, ReplayGain};
+use crate::share::Issue;
+use std::path::Path;
+
+// トラックの範囲はINDEX 01から同じファイル内の次のトラックのINDEX 01まで(最後のトラックはファイル末尾まで)
+pub fn apply_replaygain<D: AudioDecoder>(cue: &mut Cue, decoder: &D) -> Result<(), Issue> {
+    let mut file_loudness: Vec<Vec<Loudness>> = Vec::new();
+    for file in cue.files().iter() {
+        let loudness = match analyze_file(file, decoder) {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(
+                    res.prefixed(format!("failed to analyze replaygain of {}\n", file.path()))
+                );
+            }
+        };
+        file_loudness.push(loudness);
+    }
+
+    let album = Loudness::merge(&file_loudness.concat());
+    for (key, value) in ReplayGain::from_loudness(&album).to_album_rem_entries() {
+        cue.set_rem(key, value);
+    }
+    for (file, loudness) in cue.files_mut().iter_mut().zip(file_loudness.iter()) {
+        for (track, track_loudness) in file.tracks_mut().iter_mut().zip(loudness.iter()) {
+            for (key, value) in ReplayGain::from_loudness(track_loudness).to_track_rem_entries() {
+                track.set_rem(key, value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn analyze_file<D: AudioDecoder>(file: &CueFile, decoder: &D) -> Result<Vec<Loudness>, Issue> {
+    let path = Path::new(file.path());
+    let format = decoder.probe(path)?;
+    let mut starts: Vec<u64> = Vec::new();
+    for (index, track) in file.tracks().iter().enumerate() {
+        let start = track.index_01()?.to_sample(format.sample_rate);
+        if let Some(last) = starts.last()
+            && *last > start
+        {
+            return Err(Issue::from(format!(
+                "INDEX 01 of track index={} is before the previous track",
+                index
+            )));
+        }
+        starts.push(start);
+    }
+
+    let mut splitter = TrackSplitter::new(starts, format.sample_rate, format.channels);
+    decoder.decode(path, &mut |samples| splitter.push(samples))?;
+
+    Ok(splitter.finish())
+}
+
+struct TrackSplitter {
+    starts: Vec<u64>,
+    channels: usize,
+    meters: Vec<LoudnessMeter>,
+    position: u64,
+}
+
+impl TrackSplitter {
+    fn new(starts: Vec<u64>, sample_rate: u32, channels: u16) -> TrackSplitter {
+        let meters = starts
+            .iter()
+            .map(|_| LoudnessMeter::new(sample_rate, channels))
+            .collect();
+        TrackSplitter {
+            starts,
+            channels: channels as usize,
+            meters,
+            position: 0,
+        }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        let mut rest = samples;
+        while !rest.is_empty() {
+            // 現在位置を含むトラック(最初のトラックより前は対象外)
+            let current = self.starts.iter().rposition(|s| *s <= self.position);
+            let next_start = match current {
+                Some(index) => self.starts.get(index + 1).copied(),
+                None => self.starts.first().copied(),
+            };
+            let rest_frames = (rest.len() / self.channels) as u64;
+            if rest_frames == 0 {
+                break;
+            }
+            let frames = match next_start {
+                Some(next_start) => rest_frames.min(next_start - self.position),
+                None => rest_frames,
+            };
+            let (head, tail) = rest.split_at(frames as usize * self.channels);
+            if let Some(index) = current {
+                self.meters[index].push(head);
+            }
+            self.position += frames;
+            rest = tail;
+        }
+    }
+
+    fn finish(self) -> Vec<Loudness> {
+        self.meters.into_iter().map(|m| m.finish()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::common::AudioFormat;
+    use crate::domain::aggregates::v1::cue::{
+        FileType, Header, Pregap, PregapType, Rem, Time, Track,
+    };
+    use crate::share::TextStyle;
+
+    const SAMPLE_RATE: u32 = 7500;
+
+    // 1秒目まで振幅0.5、以降は振幅0.25の矩形波を返す
+    struct MockDecoder {}
+    impl AudioDecoder for MockDecoder {
+        fn probe(&self, _: &Path) -> Result<AudioFormat, Issue> {
+            Ok(AudioFormat {
+                sample_rate: SAMPLE_RATE,
+                channels: 1,
+                length: SAMPLE_RATE as u64 * 4,
+            })
+        }
+
+        fn decode(&self, _: &Path, on_samples: &mut dyn FnMut(&[f32])) -> Result<(), Issue> {
+            let samples: Vec<f32> = (0..SAMPLE_RATE * 4)
+                .map(|i| {
+                    let amplitude = if i < SAMPLE_RATE { 0.5 } else { 0.25 };
+                    if i / 4 % 2 == 0 {
+                        amplitude
+                    } else {
+                        -amplitude
+                    }
+                })
+                .collect();
+            for chunk in samples.chunks(1000) {
+                on_samples(chunk);
+            }
+            Ok(())
+        }
+    }
+
+    fn build_track(start_at: &[u8]) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 0, 0])?,
+            },
+            Time::from_vec(start_at)?,
+        ))
+    }
+
+    #[test]
+    fn test_apply_replaygain() -> Result<(), Box<dyn std::error::Error>> {
+        let tracks = vec![build_track(&[0, 0, 0])?, build_track(&[0, 1, 0])?];
+        let file = CueFile::new(String::from("mock.wav"), tracks, FileType::Wave);
+        let mut cue = Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![file],
+        );
+
+        apply_replaygain(&mut cue, &MockDecoder {})?;
+
+        let result = cue.to_cdtext_string(&Header::new(false, None), &TextStyle::default())?;
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[1].starts_with("REM REPLAYGAIN_ALBUM_GAIN "));
+        assert_eq!(lines[2], "REM REPLAYGAIN_ALBUM_PEAK 0.500000");
+        assert!(lines[6].starts_with("    REM REPLAYGAIN_TRACK_GAIN "));
+        assert_eq!(lines[7], "    REM REPLAYGAIN_TRACK_PEAK 0.500000");
+        assert_eq!(lines[12], "    REM REPLAYGAIN_TRACK_PEAK 0.250000");
+        Ok(())
+    }
+
+    #[test]
+    fn test_track_splitter() {
+        let mut splitter = TrackSplitter::new(vec![2, 5], 10, 2);
+
+        splitter.push(&[0.9, 0.9, 0.9, 0.9, 0.1, 0.1]);
+        splitter.push(&[0.2, 0.2, 0.3, 0.3, 0.4, 0.4, 0.6, 0.6]);
+        let result = splitter.finish();
+
+        assert_eq!(result.len(), 2);
+        assert!((result[0].peak() - 0.3).abs() < 1e-6);
+        assert!((result[1].peak() - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_apply_replaygain_err() -> Result<(), Box<dyn std::error::Error>> {
+        let tracks = vec![build_track(&[0, 2, 0])?, build_track(&[0, 1, 0])?];
+        let file = CueFile::new(String::from("mock.wav"), tracks, FileType::Wave);
+        let mut cue = Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![file],
+        );
+
+        let Err(result) = apply_replaygain(&mut cue, &MockDecoder {}) else {
+            return Err("".into());
+        };
+
+        assert_eq!(
+            result.message,
+            "failed to analyze replaygain of mock.wav\nINDEX 01 of track index=1 is before the previous track"
+        );
+        Ok(())
+    }
+}
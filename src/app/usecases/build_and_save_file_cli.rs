@@ -1,72 +1,282 @@
-use crate::app::interfaces::ArgsGetter;
-use crate::domain::aggregates::common::json::Json as JsonCommon;
-use crate::domain::aggregates::v1::json::Json as JsonV1;
-use crate::domain::services::v1::Json2CueV1;
+use super::{
+    apply_disc_id, apply_gap_layout, apply_replaygain, build_chapters, build_tracklist,
+    convert_document, load_document, probe_layout,
+};
+use crate::app::interfaces::{AudioDecoder, BuildArgs, OutputFormat};
+use crate::domain::aggregates::v1::cue::Cue;
+use crate::domain::aggregates::v1::cue::Delimiter;
+use crate::domain::services::common::{Converted, FileCheck};
+use crate::share::{Issue, IssueCode, TextEncoding};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub fn build_and_save_file<A: ArgsGetter>(args_getter: A) -> Result<(), String> {
-    // ! 書き出し実装をinfra-layerに掃き出し
-    let args = args_getter.load()?;
-    let version = parse_version(&args.input_file_path)?;
-    if Json2CueV1::is_parsable_version(version) {
-        let built_cue_string = build_json_v1(&args.input_file_path)?;
-        return save_file(&args.target_file_path, built_cue_string);
+// 書き出しには影響しない警告を返す
+// * ディスクセットはディスクごとに別のファイルに書き出す
+// * 途中のディスクで失敗しても一部のファイルだけが残らないよう、全てのディスクを組み立ててから書き出す
+pub fn build_and_save_file<D: AudioDecoder>(
+    args: &BuildArgs,
+    decoder: &D,
+) -> Result<Vec<Issue>, Issue> {
+    let document = load_document(&args.input_file_path, args.input_format)?;
+    let discs = convert_document(&args.input_file_path, &document, FileCheck::Required)?;
+    let total = discs.len();
+
+    if document.is_disc_set() {
+        for (index, converted) in discs.iter().enumerate() {
+            validate_disc(&converted.cue, decoder)
+                .map_err(|e| e.prefixed(format!("disc {}: ", index + 1)))?;
+        }
     }
-    Err(format!("unknown version: {}", version))
+
+    let mut warnings: Vec<Issue> = Vec::new();
+    let mut outputs: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    for (index, converted) in discs.into_iter().enumerate() {
+        let Converted {
+            mut cue,
+            warnings: disc_warnings,
+        } = converted;
+        let target_file_path = match document.is_disc_set() {
+            true => build_disc_file_path(&args.target_file_path, index + 1, total),
+            false => args.target_file_path.clone(),
+        };
+        warnings.extend(disc_warnings);
+        let data = build_disc(&mut cue, args, decoder, &mut warnings)?;
+        outputs.push((target_file_path, data));
+    }
+    for (target_file_path, data) in outputs {
+        save_file(&target_file_path, data)?;
+    }
+    Ok(warnings)
 }
 
-fn parse_version(input_file_path: &PathBuf) -> Result<u8, String> {
-    let json_string = match fs::read_to_string(input_file_path) {
-        Ok(res) => res,
-        Err(res) => {
-            return Err(format!(
-                "failed to read json file: {:?}\n{}",
-                input_file_path, res
-            ));
-        }
-    };
-    let json: JsonCommon = match serde_json::from_str(&json_string) {
-        Ok(res) => res,
-        Err(res) => {
-            return Err(format!(
-                "json file({:?}) is not parsable as v1\n{}",
-                input_file_path, res
-            ));
-        }
+// ディスクごとにCDのトラック数と収録時間の上限を確認する
+fn validate_disc<D: AudioDecoder>(cue: &Cue, decoder: &D) -> Result<(), Issue> {
+    probe_layout(cue, decoder)?
+        .validate_limits()
+        .map_err(|e| Issue::new(IssueCode::DiscLimit, e))
+}
+
+// hoge.cue -> hoge-disc1.cue(番号の桁はディスク数に合わせる)
+fn build_disc_file_path(target_file_path: &Path, number: usize, total: usize) -> PathBuf {
+    let width = total.to_string().len();
+    let stem = target_file_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match target_file_path.extension() {
+        Some(extension) => format!(
+            "{}-disc{:0>width$}.{}",
+            stem,
+            number,
+            extension.to_string_lossy()
+        ),
+        None => format!("{}-disc{:0>width$}", stem, number),
     };
-    Ok(json.version)
+    target_file_path.with_file_name(file_name)
 }
 
-fn build_json_v1(input_file_path: &PathBuf) -> Result<String, String> {
-    let json_string = match fs::read_to_string(input_file_path) {
-        Ok(res) => res,
-        Err(res) => {
-            return Err(format!(
-                "failed to read json file: {:?}\n{}",
-                input_file_path, res
-            ));
+fn build_disc<D: AudioDecoder>(
+    cue: &mut Cue,
+    args: &BuildArgs,
+    decoder: &D,
+    warnings: &mut Vec<Issue>,
+) -> Result<Vec<u8>, Issue> {
+    // ! 書き出し実装をinfra-layerに掃き出し
+    if args.write_inherited_info {
+        cue.write_inherited_info();
+    }
+    if args.replaygain {
+        apply_replaygain(cue, decoder)?;
+    }
+    if args.disc_id {
+        apply_disc_id(cue, decoder)?;
+    }
+    let built_cue_string = match args.output_format {
+        // * プリギャップの置き方を変えるとファイルの長さが変わるため、他の計算の後に行う
+        OutputFormat::Cue => {
+            apply_gap_layout(cue, args.gap_layout, decoder)?;
+            cue.to_cdtext_string(&args.header, &args.style)?
         }
-    };
-    let json: JsonV1 = match serde_json::from_str(&json_string) {
-        Ok(res) => res,
-        Err(res) => {
-            return Err(format!(
-                "json file({:?}) is not parsable as v1\n{}",
-                input_file_path, res
-            ));
+        OutputFormat::Toc(language) => cue.to_toc_string(&args.header, language, &args.style)?,
+        OutputFormat::AudacityLabels => {
+            build_chapters(cue, decoder)?.to_audacity_labels_string(&args.style)
+        }
+        OutputFormat::FfMetadata => {
+            build_chapters(cue, decoder)?.to_ffmetadata_string(&args.header, &args.style)
+        }
+        OutputFormat::MatroskaChapters => {
+            build_chapters(cue, decoder)?.to_matroska_xml_string(&args.header, &args.style)
+        }
+        OutputFormat::Csv => build_tracklist(cue, decoder, warnings)?
+            .to_tracklist_string(Delimiter::Comma, &args.style),
+        OutputFormat::Tsv => build_tracklist(cue, decoder, warnings)?
+            .to_tracklist_string(Delimiter::Tab, &args.style),
+        OutputFormat::Markdown => {
+            build_tracklist(cue, decoder, warnings)?.to_markdown_string(&args.style)
+        }
+        OutputFormat::M3u8 => {
+            build_tracklist(cue, decoder, warnings)?.to_m3u8_string(&args.header, &args.style)
         }
+        OutputFormat::Tags => cue.tags().to_json_string(&args.style)?,
     };
-    let builder = Json2CueV1::new(json);
-    builder.build()
+    encode(&built_cue_string, args.encoding, args.transliterate)
 }
 
-fn save_file(target_file_path: &PathBuf, data: String) -> Result<(), String> {
+fn encode(data: &str, encoding: TextEncoding, transliterate: bool) -> Result<Vec<u8>, Issue> {
+    match encoding.encode(data, transliterate) {
+        Ok(res) => Ok(res),
+        Err(res) => Err(Issue::new(
+            IssueCode::Encoding,
+            format!(
+                "failed to encode output in {}\n{}\n(use --transliterate to replace them)",
+                encoding.name(),
+                res
+            ),
+        )),
+    }
+}
+
+fn save_file(target_file_path: &PathBuf, data: Vec<u8>) -> Result<(), Issue> {
     match fs::write(target_file_path, data) {
         Ok(res) => Ok(res),
-        Err(res) => Err(format!(
-            "failed to save data to {:?}\n{}",
-            target_file_path, res
+        Err(res) => Err(Issue::new(
+            IssueCode::OutputNotWritable,
+            format!("failed to save data to {:?}\n{}", target_file_path, res),
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::common::AudioFormat;
+    use crate::domain::aggregates::v1::cue::Header;
+    use crate::share::TextStyle;
+    use std::env;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    // README.mdは81分、それ以外は1分の音声として扱う
+    struct MockDecoder {}
+    impl AudioDecoder for MockDecoder {
+        fn probe(&self, path: &Path) -> Result<AudioFormat, Issue> {
+            let minutes = match path.ends_with("README.md") {
+                true => 81,
+                false => 1,
+            };
+            Ok(AudioFormat {
+                sample_rate: SAMPLE_RATE,
+                channels: 2,
+                length: SAMPLE_RATE as u64 * 60 * minutes,
+            })
+        }
+
+        fn decode(&self, _: &Path, _: &mut dyn FnMut(&[f32])) -> Result<(), Issue> {
+            Err(Issue::from(String::from("not supported")))
+        }
+    }
+
+    fn build_args(input_file_path: PathBuf, target_file_path: PathBuf) -> BuildArgs {
+        BuildArgs {
+            input_file_path,
+            input_format: None,
+            target_file_path,
+            output_format: OutputFormat::Cue,
+            encoding: TextEncoding::Utf8,
+            transliterate: false,
+            style: TextStyle::default(),
+            header: Header::default(),
+            replaygain: false,
+            disc_id: false,
+            write_inherited_info: false,
+            gap_layout: None,
+        }
+    }
+
+    // * 実行時のディレクトリに依存しないよう、クレートのファイルを絶対パスで指定する
+    fn build_disc_set(names: &[&str]) -> String {
+        let discs: Vec<String> = names
+            .iter()
+            .map(|n| {
+                let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(n);
+                format!(
+                    "{{\"files\": [{{\"path\": {}, \"type\": \"WAVE\", \"tracks\": [{{\"title\": \"a\"}}]}}]}}",
+                    serde_json::Value::from(path.to_string_lossy())
+                )
+            })
+            .collect();
+        format!(
+            "{{\"version\": 1, \"title\": \"box\", \"discs\": [{}]}}",
+            discs.join(", ")
+        )
+    }
+
+    fn prepare_dir(name: &str) -> Result<PathBuf, String> {
+        let dir = env::temp_dir().join(format!("json2cuesheet-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        }
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(dir)
+    }
+
+    #[test]
+    fn test_build_and_save_file_disc_set_validates_all_discs_first() -> Result<(), String> {
+        let dir = prepare_dir("validate")?;
+        let input = dir.join("box.json");
+        fs::write(&input, build_disc_set(&["Cargo.toml", "README.md"]))
+            .map_err(|e| e.to_string())?;
+
+        let result = build_and_save_file(&build_args(input, dir.join("box.cue")), &MockDecoder {});
+
+        let written = dir.join("box-disc1.cue").exists();
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        let Err(result) = result else {
+            return Err(String::from("disc over 80 minutes is accepted"));
+        };
+        assert!(result.message.starts_with("disc 2: "), "{}", result);
+        assert_eq!(result.code, IssueCode::DiscLimit);
+        assert!(!written, "disc 1 is written before disc 2 is validated");
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_and_save_file_single_disc_set() -> Result<(), String> {
+        let dir = prepare_dir("single")?;
+        let input = dir.join("box.json");
+        fs::write(&input, build_disc_set(&["README.md"])).map_err(|e| e.to_string())?;
+
+        let result = build_and_save_file(&build_args(input, dir.join("box.cue")), &MockDecoder {});
+
+        let written = [
+            dir.join("box.cue").exists(),
+            dir.join("box-disc1.cue").exists(),
+        ];
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        let Err(result) = result else {
+            return Err(String::from("disc over 80 minutes is accepted"));
+        };
+        assert!(result.message.starts_with("disc 1: "), "{}", result);
+        assert_eq!(result.code, IssueCode::DiscLimit);
+        assert_eq!(written, [false, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_disc_file_path() {
+        let path = Path::new("/tmp/box.cue");
+
+        assert_eq!(
+            build_disc_file_path(path, 2, 3),
+            PathBuf::from("/tmp/box-disc2.cue")
+        );
+        assert_eq!(
+            build_disc_file_path(path, 2, 12),
+            PathBuf::from("/tmp/box-disc02.cue")
+        );
+        assert_eq!(
+            build_disc_file_path(Path::new("box"), 1, 2),
+            PathBuf::from("box-disc1")
+        );
+    }
+}
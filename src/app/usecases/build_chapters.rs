@@ -0,0 +1,17 @@
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::v1::cue::{Chapters, Cue};
+use crate::share::Issue;
+use std::path::Path;
+
+// 最後のチャプターの終了位置にファイルの長さが必要なため音声ファイルを調べる
+pub fn build_chapters<D: AudioDecoder>(cue: &Cue, decoder: &D) -> Result<Chapters, Issue> {
+    let mut file_lengths: Vec<u32> = Vec::new();
+    for file in cue.files().iter() {
+        let format = decoder.probe(Path::new(file.path()))?;
+        file_lengths.push(format.length_frames());
+    }
+    match cue.chapters(&file_lengths) {
+        Ok(res) => Ok(res),
+        Err(res) => Err(Issue::from(format!("failed to build chapters\n{}", res))),
+    }
+}
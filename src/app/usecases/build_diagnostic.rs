@@ -0,0 +1,31 @@
+use crate::domain::aggregates::common::InputFormat;
+use crate::share::{Diagnostic, Issue, Severity, jsonc};
+use std::fs;
+use std::path::PathBuf;
+
+pub fn build_diagnostic(severity: Severity, issue: &Issue) -> Diagnostic {
+    let mut diagnostic = Diagnostic::from_issue(severity, issue);
+    locate_diagnostic(&mut diagnostic);
+    diagnostic
+}
+
+pub fn build_diagnostics(severity: Severity, issues: &[Issue]) -> Vec<Diagnostic> {
+    issues
+        .iter()
+        .map(|i| build_diagnostic(severity, i))
+        .collect()
+}
+
+// 入力ファイルがJSONの場合、行と列がなければJSON Pointerの位置で補う
+pub fn locate_diagnostic(diagnostic: &mut Diagnostic) {
+    if diagnostic.line.is_none()
+        && let (Some(file), Some(pointer)) = (&diagnostic.file, &diagnostic.pointer)
+        && InputFormat::from_path(&PathBuf::from(file)).unwrap_or(InputFormat::Json)
+            == InputFormat::Json
+        && let Ok(source) = fs::read_to_string(file)
+        && let Some((line, column)) = jsonc::locate_pointer(&source, pointer)
+    {
+        diagnostic.line = Some(line);
+        diagnostic.column = Some(column);
+    }
+}
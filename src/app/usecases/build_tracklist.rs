@@ -0,0 +1,34 @@
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::v1::cue::{Cue, Tracklist};
+use crate::share::Issue;
+use std::path::Path;
+
+// トラックリストはcueから組み立て、ファイルの最後のトラックの長さのため音声ファイルを調べる
+// * 調べられない音声ファイルはエラーにせず、そのファイルに依存する長さと開始位置を空欄とする
+pub fn build_tracklist<D: AudioDecoder>(
+    cue: &Cue,
+    decoder: &D,
+    warnings: &mut Vec<Issue>,
+) -> Result<Tracklist, Issue> {
+    let mut file_lengths: Vec<Option<u32>> = Vec::new();
+    for file in cue.files().iter() {
+        if file.tracks().is_empty() {
+            file_lengths.push(None);
+            continue;
+        }
+        match decoder.probe(Path::new(file.path())) {
+            Ok(res) => file_lengths.push(Some(res.length_frames())),
+            Err(res) => {
+                warnings.push(res.prefixed(format!(
+                    "length of {} is unknown, the track lengths which need it are left empty\n",
+                    file.path()
+                )));
+                file_lengths.push(None);
+            }
+        }
+    }
+    match cue.tracklist(&file_lengths) {
+        Ok(res) => Ok(res),
+        Err(res) => Err(Issue::from(format!("failed to build track list\n{}", res))),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::v1::cue::{Cue, DiscLayout};
+use crate::domain::services::common::DiscId;
+use crate::share::Issue;
+use std::path::Path;
+
+const KEY_DISC_ID: &str = "DISCID";
+const KEY_MUSICBRAINZ_DISC_ID: &str = "MUSICBRAINZ_DISCID";
+
+pub fn probe_file_lengths<D: AudioDecoder>(
+    cue: &Cue,
+    decoder: &D,
+) -> Result<Vec<Option<u32>>, Issue> {
+    let mut file_lengths: Vec<Option<u32>> = Vec::new();
+    for file in cue.files().iter() {
+        let format = decoder.probe(Path::new(file.path()))?;
+        file_lengths.push(Some(format.length_frames()));
+    }
+    Ok(file_lengths)
+}
+
+// 隠し音声がない場合はNone
+pub fn probe_hidden_length<D: AudioDecoder>(cue: &Cue, decoder: &D) -> Result<Option<u32>, Issue> {
+    match cue.hidden_audio() {
+        Some(hidden_audio) => Ok(Some(
+            decoder
+                .probe(Path::new(&hidden_audio.path))?
+                .length_frames(),
+        )),
+        None => Ok(None),
+    }
+}
+
+pub fn probe_layout<D: AudioDecoder>(cue: &Cue, decoder: &D) -> Result<DiscLayout, Issue> {
+    let file_lengths = probe_file_lengths(cue, decoder)?;
+    Ok(cue.layout(&file_lengths, probe_hidden_length(cue, decoder)?)?)
+}
+
+pub fn compute_disc_id(layout: &DiscLayout) -> Result<DiscId, String> {
+    let Some(first_track) = layout.tracks.first() else {
+        return Err(String::from("disc has no tracks"));
+    };
+    let mut offsets: Vec<u32> = Vec::new();
+    for track in layout.tracks.iter() {
+        let Some(offset) = track.offset else {
+            return Err(format!("offset of track No.{} is unknown", track.number));
+        };
+        offsets.push(offset + DiscId::LEAD_IN_FRAMES);
+    }
+    let Some(lead_out) = layout.lead_out else {
+        return Err(String::from("lead-out is unknown"));
+    };
+    DiscId::new(
+        first_track.number,
+        offsets,
+        lead_out + DiscId::LEAD_IN_FRAMES,
+    )
+}
+
+pub fn apply_disc_id<D: AudioDecoder>(cue: &mut Cue, decoder: &D) -> Result<(), Issue> {
+    let layout = probe_layout(cue, decoder)?;
+    let disc_id = match compute_disc_id(&layout) {
+        Ok(res) => res,
+        Err(res) => return Err(Issue::from(format!("failed to compute disc id\n{}", res))),
+    };
+    cue.set_rem(String::from(KEY_DISC_ID), disc_id.freedb());
+    cue.set_rem(String::from(KEY_MUSICBRAINZ_DISC_ID), disc_id.musicbrainz());
+    Ok(())
+}
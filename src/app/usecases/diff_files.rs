@@ -0,0 +1,103 @@
+use super::{
+    RunOutput, build_diagnostics, is_cue_sheet, load_cue, load_cue_sheet, probe_file_lengths,
+};
+use crate::app::interfaces::{AudioDecoder, DiffArgs};
+use crate::domain::services::common::{Converted, FileCheck};
+use crate::share::{Issue, Severity};
+use std::path::PathBuf;
+
+// 違いはトラックごとに標準出力に表示する(違いがあっても失敗にはしない)
+pub fn diff_files<D: AudioDecoder>(args: &DiffArgs, decoder: &D) -> Result<RunOutput, Issue> {
+    let old = load_for_diff(&args.old_file_path, args, decoder)?;
+    let new = load_for_diff(&args.new_file_path, args, decoder)?;
+
+    let lines = old.cue.diff(&new.cue);
+    let stdout = match lines.is_empty() {
+        true => String::from("no changes"),
+        false => lines.join("\n"),
+    };
+    let mut diagnostics = build_diagnostics(Severity::Warning, &old.warnings);
+    diagnostics.extend(build_diagnostics(Severity::Warning, &new.warnings));
+    Ok(RunOutput {
+        stdout: Some(stdout),
+        diagnostics,
+    })
+}
+
+fn load_for_diff<D: AudioDecoder>(
+    file_path: &PathBuf,
+    args: &DiffArgs,
+    decoder: &D,
+) -> Result<Converted, Issue> {
+    if is_cue_sheet(file_path) {
+        return load_cue_sheet(file_path, args.cue_encoding);
+    }
+    // * メタデータとINDEXの比較には音声ファイルは不要
+    let mut converted = load_cue(file_path, args.input_format, FileCheck::Skipped)?;
+    // * cue sheetは前のファイルの末尾のプリギャップをINDEX 00の位置で表すため、
+    //   音声ファイルが読める場合は位置を求めて比べる
+    if converted.cue.has_appended_pregaps() {
+        match probe_file_lengths(&converted.cue, decoder) {
+            Ok(file_lengths) => converted.cue.resolve_appended_pregaps(&file_lengths)?,
+            Err(res) => converted.warnings.push(
+                Issue::from(format!(
+                    "INDEX 00 in the previous file is not compared: {}",
+                    res
+                ))
+                .in_file(file_path),
+            ),
+        }
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::common::AudioFormat;
+    use crate::share::TextEncoding;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    // 音声ファイルを読めない場合
+    struct MockDecoder {}
+    impl AudioDecoder for MockDecoder {
+        fn probe(&self, path: &Path) -> Result<AudioFormat, Issue> {
+            Err(Issue::from(format!("{:?} is not readable", path)))
+        }
+
+        fn decode(&self, _: &Path, _: &mut dyn FnMut(&[f32])) -> Result<(), Issue> {
+            Err(Issue::from(String::from("not supported")))
+        }
+    }
+
+    fn build_json(title: &str) -> String {
+        format!(
+            "{{\"version\": 1, \"title\": \"album\", \"files\": [{{\"path\": \"missing.wav\", \"type\": \"WAVE\", \"tracks\": [{{\"title\": \"{}\"}}]}}]}}",
+            title
+        )
+    }
+
+    #[test]
+    fn test_diff_files_without_audio_files() -> Result<(), String> {
+        let dir = env::temp_dir().join(format!("json2cuesheet-diff-{}", std::process::id()));
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let (old, new) = (dir.join("old.json"), dir.join("new.json"));
+        fs::write(&old, build_json("a")).map_err(|e| e.to_string())?;
+        fs::write(&new, build_json("b")).map_err(|e| e.to_string())?;
+        let args = DiffArgs {
+            old_file_path: old,
+            new_file_path: new,
+            input_format: None,
+            cue_encoding: TextEncoding::Utf8,
+        };
+
+        let result = diff_files(&args, &MockDecoder {});
+
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        let stdout = result?.stdout.unwrap_or_default();
+        assert!(stdout.contains("TITLE"), "{}", stdout);
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use super::{
+    RunOutput, build_diagnostics, compute_disc_id, load_cues, probe_file_lengths,
+    probe_hidden_length,
+};
+use crate::app::interfaces::{AudioDecoder, InspectArgs};
+use crate::domain::services::common::FileCheck;
+use crate::share::{Diagnostic, Issue, Severity};
+
+pub fn inspect_file<D: AudioDecoder>(args: &InspectArgs, decoder: &D) -> Result<RunOutput, Issue> {
+    let discs = load_cues(
+        &args.input_file_path,
+        args.input_format,
+        FileCheck::Required,
+    )?;
+    let total = discs.len();
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for (index, mut converted) in discs.into_iter().enumerate() {
+        let file_lengths = probe_file_lengths(&converted.cue, decoder)?;
+        let hidden_length = probe_hidden_length(&converted.cue, decoder)?;
+        // * 前のファイルの末尾のプリギャップはINDEX 00の位置を表示する
+        if converted.cue.has_appended_pregaps() {
+            converted.cue.resolve_appended_pregaps(&file_lengths)?;
+        }
+        let layout = converted.cue.layout(&file_lengths, hidden_length)?;
+
+        if total > 1 {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            lines.push(format!(
+                "Disc {}/{}: {}",
+                index + 1,
+                total,
+                converted.cue.title()
+            ));
+        }
+        lines.extend(converted.cue.to_inspection_strings(&layout, &file_lengths));
+        lines.push(String::new());
+        // * トラックの位置がおかしい場合もレイアウトは表示する
+        match compute_disc_id(&layout) {
+            Ok(disc_id) => {
+                lines.push(format!("FreeDB disc ID: {}", disc_id.freedb()));
+                lines.push(format!("MusicBrainz disc ID: {}", disc_id.musicbrainz()));
+            }
+            Err(res) => lines.push(format!("disc ID is not computable: {}", res)),
+        }
+        diagnostics.extend(build_diagnostics(Severity::Warning, &converted.warnings));
+    }
+    Ok(RunOutput {
+        stdout: Some(lines.join("\n")),
+        diagnostics,
+    })
+}
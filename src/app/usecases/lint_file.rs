@@ -0,0 +1,97 @@
+use super::{RunOutput, build_diagnostics, convert_document, load_document, locate_diagnostic};
+use crate::app::interfaces::LintArgs;
+use crate::domain::aggregates::v1::cue::Cue;
+use crate::domain::services::common::{
+    FileCheck, FirstIndexNotAtFileStart, LintLevel, Linter, NonLatin1Characters,
+    PregapOnFirstTrack, TitleTooLong,
+};
+use crate::share::{Diagnostic, Issue, Severity};
+
+// * 新しいルールはここで登録する
+pub fn build_linter() -> Linter {
+    let mut linter = Linter::new();
+    linter.register(Box::new(PregapOnFirstTrack));
+    linter.register(Box::new(FirstIndexNotAtFileStart));
+    linter.register(Box::new(TitleTooLong));
+    linter.register(Box::new(NonLatin1Characters));
+    linter
+}
+
+// 問題はルールIDをコードとした診断として返し、標準出力にはディスクごとの件数を表示する
+pub fn lint_file(args: &LintArgs) -> Result<RunOutput, Issue> {
+    let mut linter = build_linter();
+    for (id, level) in args.levels.iter() {
+        linter.set_level(id, *level)?;
+    }
+    // * lintは音声ファイルを読まないため、存在しなくてもよい
+    let document = load_document(&args.input_file_path, args.input_format)?;
+    let discs = convert_document(&args.input_file_path, &document, FileCheck::Skipped)?;
+    let total = discs.len();
+    let file = args.input_file_path.to_string_lossy();
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for (index, converted) in discs.iter().enumerate() {
+        let disc = match total {
+            1 => String::new(),
+            _ => format!("disc {} ", index + 1),
+        };
+        // * 位置はトラック(ディスク全体の場合は文書かディスク)を指す
+        let disc_pointer = match document.is_disc_set() {
+            true => format!("/discs/{}", index),
+            false => String::new(),
+        };
+        let report = linter.lint(&converted.cue, &args.options);
+        diagnostics.extend(build_diagnostics(Severity::Warning, &converted.warnings));
+        diagnostics.extend(report.findings.iter().map(|finding| {
+            let severity = match finding.level {
+                LintLevel::Error => Severity::Error,
+                _ => Severity::Warning,
+            };
+            let track_pointer = finding
+                .issue
+                .track
+                .and_then(|n| find_track_pointer(&converted.cue, n))
+                .unwrap_or_default();
+            let mut diagnostic = Diagnostic {
+                location: Some(format!("{}{}", disc, finding.location())),
+                file: Some(String::from(file.as_ref())),
+                pointer: Some(format!("{}{}", disc_pointer, track_pointer)),
+                ..Diagnostic::new(
+                    severity,
+                    String::from(finding.rule_id),
+                    finding.issue.message.clone(),
+                )
+            };
+            locate_diagnostic(&mut diagnostic);
+            diagnostic
+        }));
+        if total > 1 {
+            lines.push(format!(
+                "Disc {}/{}: {}: {}",
+                index + 1,
+                total,
+                converted.cue.title(),
+                report.to_summary_string()
+            ));
+        } else {
+            lines.push(report.to_summary_string());
+        }
+    }
+    Ok(RunOutput {
+        stdout: Some(lines.join("\n")),
+        diagnostics,
+    })
+}
+
+// トラック番号からファイルとトラックの位置を求める
+fn find_track_pointer(cue: &Cue, number: usize) -> Option<String> {
+    let mut index = number.checked_sub(cue.first_track_number())?;
+    for (file_index, file) in cue.files().iter().enumerate() {
+        if index < file.tracks().len() {
+            return Some(format!("/files/{}/tracks/{}", file_index, index));
+        }
+        index -= file.tracks().len();
+    }
+    None
+}
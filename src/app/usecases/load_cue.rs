@@ -0,0 +1,126 @@
+use crate::domain::aggregates::common::{Document, InputFormat};
+use crate::domain::services::common::{Converted, ConverterRegistry, FileCheck};
+use crate::domain::services::v1::{ConverterV1, CueSheet2Cue};
+use crate::domain::services::v2::ConverterV2;
+use crate::share::{Issue, IssueCode, TextEncoding};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// * 新しいバージョンはここで登録する
+pub fn build_registry() -> ConverterRegistry {
+    let mut registry = ConverterRegistry::new();
+    registry.register(Box::new(ConverterV1 {}));
+    registry.register(Box::new(ConverterV2 {}));
+    registry
+}
+
+// * file_checkがSkippedの場合は音声ファイルがなくても読み込める
+pub fn load_cue(
+    input_file_path: &PathBuf,
+    input_format: Option<InputFormat>,
+    file_check: FileCheck,
+) -> Result<Converted, Issue> {
+    let document = load_document(input_file_path, input_format)?;
+    if document.is_disc_set() {
+        return Err(
+            Issue::from(String::from("disc sets are not supported by this command"))
+                .in_file(input_file_path),
+        );
+    }
+    match build_registry().convert(&document, file_check) {
+        Ok(res) => Ok(with_path(input_file_path, res)),
+        Err(res) => Err(res.in_file(input_file_path)),
+    }
+}
+
+// ディスクセットの場合はディスクごとに返す
+// * file_checkがSkippedの場合は音声ファイルがなくても読み込める
+pub fn load_cues(
+    input_file_path: &PathBuf,
+    input_format: Option<InputFormat>,
+    file_check: FileCheck,
+) -> Result<Vec<Converted>, Issue> {
+    let document = load_document(input_file_path, input_format)?;
+    convert_document(input_file_path, &document, file_check)
+}
+
+// 読み込んだ文書をディスクごとに変換する
+pub fn convert_document(
+    input_file_path: &Path,
+    document: &Document,
+    file_check: FileCheck,
+) -> Result<Vec<Converted>, Issue> {
+    match build_registry().convert_all(document, file_check) {
+        Ok(res) => Ok(res
+            .into_iter()
+            .map(|c| with_path(input_file_path, c))
+            .collect()),
+        Err(res) => Err(res.in_file(input_file_path)),
+    }
+}
+
+// cue sheetを読み込む(比較などのため)
+pub fn load_cue_sheet(
+    input_file_path: &PathBuf,
+    encoding: TextEncoding,
+) -> Result<Converted, Issue> {
+    let bytes = match fs::read(input_file_path) {
+        Ok(res) => res,
+        Err(res) => {
+            return Err(Issue::new(
+                IssueCode::InputNotReadable,
+                format!("failed to read input file: {:?}\n{}", input_file_path, res),
+            ));
+        }
+    };
+    let source = match encoding.decode(&bytes) {
+        Ok(res) => res,
+        Err(res) => {
+            return Err(Issue::new(IssueCode::Encoding, res).in_file(input_file_path));
+        }
+    };
+    match CueSheet2Cue::parse(&source) {
+        Ok(res) => Ok(with_path(input_file_path, res)),
+        Err(res) => Err(res.in_file(input_file_path)),
+    }
+}
+
+pub fn is_cue_sheet(input_file_path: &Path) -> bool {
+    input_file_path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("cue"))
+}
+
+fn with_path(input_file_path: &Path, converted: Converted) -> Converted {
+    Converted {
+        cue: converted.cue,
+        warnings: converted
+            .warnings
+            .into_iter()
+            .map(|w| w.in_file(input_file_path))
+            .collect(),
+    }
+}
+
+// 形式の指定がない場合は拡張子から判定し、判定できない場合はJSONとして扱う
+pub fn load_document(
+    input_file_path: &PathBuf,
+    input_format: Option<InputFormat>,
+) -> Result<Document, Issue> {
+    let format = input_format
+        .or(InputFormat::from_path(input_file_path))
+        .unwrap_or(InputFormat::Json);
+    let source = match fs::read_to_string(input_file_path) {
+        Ok(res) => res,
+        Err(res) => {
+            return Err(Issue::new(
+                IssueCode::InputNotReadable,
+                format!("failed to read input file: {:?}\n{}", input_file_path, res),
+            ));
+        }
+    };
+    match Document::parse(source, format) {
+        Ok(res) => Ok(res),
+        Err(res) => Err(res.in_file(input_file_path)),
+    }
+}
@@ -0,0 +1,65 @@
+use super::load_document;
+use crate::app::interfaces::MigrateArgs;
+use crate::domain::aggregates::v1::json::Json as JsonV1;
+use crate::domain::services::v1::Json2CueV1;
+use crate::domain::services::v2::MigrateV1ToV2;
+use crate::share::{Issue, IssueCode};
+use std::fs;
+
+// 書き出しには影響しない警告を返す
+pub fn migrate_file(args: &MigrateArgs) -> Result<Vec<Issue>, Issue> {
+    let document = load_document(&args.input_file_path, args.input_format)?;
+    if !Json2CueV1::is_parsable_version(document.version()) {
+        return Err(Issue::new(
+            IssueCode::Version,
+            format!(
+                "migration from version {} is not supported",
+                document.version()
+            ),
+        )
+        .within("/version")
+        .in_file(&args.input_file_path));
+    }
+    if document.is_disc_set() {
+        return Err(
+            Issue::from(String::from("migration of disc sets is not supported"))
+                .within("/discs")
+                .in_file(&args.input_file_path),
+        );
+    }
+    let json: JsonV1 = match document.deserialize() {
+        Ok(res) => res,
+        Err(res) => {
+            return Err(res.prefixed(format!(
+                "input file({:?}) is not parsable as v1\n",
+                args.input_file_path
+            )));
+        }
+    };
+    let (migrated, warnings) = match MigrateV1ToV2::new(json).migrate_with_warnings() {
+        Ok(res) => res,
+        Err(res) => {
+            return Err(res
+                .prefixed(String::from("failed to migrate to v2\n"))
+                .in_file(&args.input_file_path));
+        }
+    };
+    let mut data = match serde_json::to_string_pretty(&migrated) {
+        Ok(res) => res,
+        Err(res) => return Err(Issue::from(format!("failed to serialize v2 json\n{}", res))),
+    };
+    data.push('\n');
+    match fs::write(&args.target_file_path, data) {
+        Ok(_) => Ok(warnings
+            .into_iter()
+            .map(|w| w.in_file(&args.input_file_path))
+            .collect()),
+        Err(res) => Err(Issue::new(
+            IssueCode::OutputNotWritable,
+            format!(
+                "failed to save data to {:?}\n{}",
+                args.target_file_path, res
+            ),
+        )),
+    }
+}
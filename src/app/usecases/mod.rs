@@ -1,2 +1,26 @@
+pub mod apply_gap_layout;
+pub use apply_gap_layout::*;
+pub mod apply_replaygain;
+pub use apply_replaygain::*;
 pub mod build_and_save_file_cli;
 pub use build_and_save_file_cli::*;
+pub mod build_chapters;
+pub use build_chapters::*;
+pub mod build_diagnostic;
+pub use build_diagnostic::*;
+pub mod build_tracklist;
+pub use build_tracklist::*;
+pub mod compute_disc_id;
+pub use compute_disc_id::*;
+pub mod diff_files;
+pub use diff_files::*;
+pub mod inspect_file;
+pub use inspect_file::*;
+pub mod lint_file;
+pub use lint_file::*;
+pub mod load_cue;
+pub use load_cue::*;
+pub mod migrate_file;
+pub use migrate_file::*;
+pub mod run_cli;
+pub use run_cli::*;
@@ -0,0 +1,40 @@
+use super::{
+    build_and_save_file, build_diagnostics, diff_files, inspect_file, lint_file, migrate_file,
+};
+use crate::app::interfaces::{ArgsGetter, AudioDecoder, Command};
+use crate::share::{Diagnostic, Issue, Severity};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RunOutput {
+    // 標準出力に表示する内容
+    pub stdout: Option<String>,
+    // 処理は続けたが確認が必要な内容など(標準エラー出力に表示する)
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl RunOutput {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+}
+
+pub fn run<A: ArgsGetter, D: AudioDecoder>(args_getter: A, decoder: D) -> Result<RunOutput, Issue> {
+    match args_getter.load()? {
+        Command::Build(args) => Ok(RunOutput {
+            stdout: None,
+            diagnostics: build_diagnostics(
+                Severity::Warning,
+                &build_and_save_file(&args, &decoder)?,
+            ),
+        }),
+        Command::Diff(args) => diff_files(&args, &decoder),
+        Command::Inspect(args) => inspect_file(&args, &decoder),
+        Command::Lint(args) => lint_file(&args),
+        Command::Migrate(args) => Ok(RunOutput {
+            stdout: None,
+            diagnostics: build_diagnostics(Severity::Warning, &migrate_file(&args)?),
+        }),
+    }
+}
@@ -0,0 +1,38 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    // チャンネル当たりのサンプル数
+    pub length: u64,
+}
+
+impl AudioFormat {
+    const FRAMES_PER_SECOND: u64 = 75;
+
+    // CDのフレーム数(端数は切り上げ)
+    pub fn length_frames(&self) -> u32 {
+        (self.length * Self::FRAMES_PER_SECOND).div_ceil(self.sample_rate as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_frames() {
+        let aligned = AudioFormat {
+            sample_rate: 44100,
+            channels: 2,
+            length: 588 * 100,
+        };
+        let not_aligned = AudioFormat {
+            sample_rate: 44100,
+            channels: 2,
+            length: 588 * 100 + 1,
+        };
+
+        assert_eq!(aligned.length_frames(), 100);
+        assert_eq!(not_aligned.length_frames(), 101);
+    }
+}
@@ -0,0 +1,261 @@
+use super::json::Json as JsonCommon;
+use crate::share::{Issue, IssueCode, jsonc, line_and_column};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl InputFormat {
+    // 拡張子から判定する(不明な場合はNone)
+    pub fn from_path(path: &Path) -> Option<InputFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" | "jsonc" => Some(InputFormat::Json),
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            "toml" => Some(InputFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::Yaml => "YAML",
+            InputFormat::Toml => "TOML",
+        }
+    }
+}
+
+// 入力ファイルの内容とバージョン
+// * 形式に関わらず一度だけ読み込んでJSONの値とし、バージョンの判定と変換は同じ値から行う
+#[derive(Debug, Clone)]
+pub struct Document {
+    version: u8,
+    is_disc_set: bool,
+    format: InputFormat,
+    source: String,
+    value: Value,
+}
+
+impl Document {
+    pub fn parse(source: String, format: InputFormat) -> Result<Document, Issue> {
+        let value: Value = match Self::deserialize_source(&source, format, IssueCode::Syntax) {
+            Ok(res) => res,
+            Err(res) => return Err(res.prefixed(String::from("version is not readable\n"))),
+        };
+        let common = match JsonCommon::deserialize(&value) {
+            Ok(res) => res,
+            Err(res) => {
+                let res = Self::locate_error::<JsonCommon>(&source, format, IssueCode::Syntax, res);
+                return Err(res.prefixed(String::from("version is not readable\n")));
+            }
+        };
+        Ok(Document {
+            version: common.version,
+            is_disc_set: common.discs.is_some(),
+            format,
+            source,
+            value,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    // 複数のディスクをまとめた文書か
+    pub fn is_disc_set(&self) -> bool {
+        self.is_disc_set
+    }
+
+    pub fn format(&self) -> InputFormat {
+        self.format
+    }
+
+    // エラーには元の形式での行と列が含まれる
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, Issue> {
+        match T::deserialize(&self.value) {
+            Ok(res) => Ok(res),
+            Err(res) => Err(Self::locate_error::<T>(
+                &self.source,
+                self.format,
+                IssueCode::Schema,
+                res,
+            )),
+        }
+    }
+
+    // JSONの値からの変換エラーには位置がないため、エラーの場合のみ元の文書から読み直して位置を求める
+    fn locate_error<T: DeserializeOwned>(
+        source: &str,
+        format: InputFormat,
+        code: IssueCode,
+        error: serde_json::Error,
+    ) -> Issue {
+        match Self::deserialize_source::<T>(source, format, code) {
+            Err(res) => res,
+            Ok(_) => Issue::new(code, format!("{} ({})", error, format.name())),
+        }
+    }
+
+    fn deserialize_source<T: DeserializeOwned>(
+        source: &str,
+        format: InputFormat,
+        code: IssueCode,
+    ) -> Result<T, Issue> {
+        let (message, position) = match format {
+            // * JSONはコメントと末尾のカンマを許容する
+            InputFormat::Json => match serde_json::from_str(&jsonc::strip_jsonc(source)) {
+                Ok(res) => return Ok(res),
+                Err(res) => (res.to_string(), Some((res.line(), res.column()))),
+            },
+            InputFormat::Yaml => match serde_yaml_ng::from_str(source) {
+                Ok(res) => return Ok(res),
+                Err(res) => (
+                    res.to_string(),
+                    res.location().map(|l| (l.line(), l.column())),
+                ),
+            },
+            InputFormat::Toml => match toml::from_str(source) {
+                Ok(res) => return Ok(res),
+                Err(res) => (
+                    res.to_string(),
+                    res.span().map(|s| line_and_column(source, s.start)),
+                ),
+            },
+        };
+        // * 位置のないエラーは行が0になる
+        let position = position.filter(|(line, _)| *line > 0);
+        Err(Issue {
+            line: position.map(|p| p.0),
+            column: position.map(|p| p.1),
+            ..Issue::new(code, format!("{} ({})", message.trim_end(), format.name()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Sample {
+        #[allow(dead_code)]
+        title: String,
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let json = Document::parse(
+            String::from("{\"version\": 2, \"title\": \"a\"}"),
+            InputFormat::Json,
+        )?;
+        let yaml = Document::parse(
+            String::from("# comment\nversion: 1\ntitle: a\n"),
+            InputFormat::Yaml,
+        )?;
+        let toml = Document::parse(
+            String::from("# comment\nversion = 2\ntitle = \"a\"\n"),
+            InputFormat::Toml,
+        )?;
+
+        let jsonc = Document::parse(
+            String::from("{\n  // note\n  \"version\": 1, /* v1 */\n}"),
+            InputFormat::Json,
+        )?;
+
+        let disc_set = Document::parse(
+            String::from("{\"version\": 1, \"discs\": []}"),
+            InputFormat::Json,
+        )?;
+
+        assert_eq!(json.version(), 2);
+        assert_eq!(jsonc.version(), 1);
+        assert!(!json.is_disc_set());
+        assert!(disc_set.is_disc_set());
+        assert_eq!(yaml.version(), 1);
+        assert_eq!(toml.version(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err() {
+        let Err(result) = Document::parse(String::from("{\"title\": \"a\"}"), InputFormat::Json)
+        else {
+            panic!("document without version is accepted");
+        };
+
+        assert_eq!(
+            result.message,
+            "version is not readable\nmissing field `version` at line 1 column 14 (JSON)"
+        );
+        assert_eq!(result.code, IssueCode::Syntax);
+        assert_eq!((result.line, result.column), (Some(1), Some(14)));
+    }
+
+    #[test]
+    fn test_deserialize_err_line() -> Result<(), String> {
+        let yaml = Document::parse(
+            String::from("version: 1\n\ntitle:\n  - a\n"),
+            InputFormat::Yaml,
+        )?;
+        let toml = Document::parse(
+            String::from("version = 1\n\ntitle = 3\n"),
+            InputFormat::Toml,
+        )?;
+
+        let (Err(yaml), Err(toml)) = (yaml.deserialize::<Sample>(), toml.deserialize::<Sample>())
+        else {
+            return Err(String::from("invalid document is accepted"));
+        };
+
+        assert!(yaml.message.contains("line 4"), "{}", yaml);
+        assert!(toml.message.contains("line 3"), "{}", toml);
+        assert_eq!((yaml.code, yaml.line), (IssueCode::Schema, Some(4)));
+        assert_eq!((toml.line, toml.column), (Some(3), Some(9)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_keeps_key_order() -> Result<(), String> {
+        let sources = [
+            ("{\"version\": 1, \"b\": 1, \"a\": 2}", InputFormat::Json),
+            ("version: 1\nb: 1\na: 2\n", InputFormat::Yaml),
+            ("version = 1\nb = 1\na = 2\n", InputFormat::Toml),
+        ];
+
+        for (source, format) in sources {
+            let result: indexmap::IndexMap<String, u8> =
+                Document::parse(String::from(source), format)?.deserialize()?;
+
+            assert_eq!(
+                result.keys().collect::<Vec<&String>>(),
+                vec!["version", "b", "a"],
+                "{}",
+                format.name()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            InputFormat::from_path(Path::new("a.YML")),
+            Some(InputFormat::Yaml)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("a.toml")),
+            Some(InputFormat::Toml)
+        );
+        assert_eq!(InputFormat::from_path(Path::new("a.json5")), None);
+        assert_eq!(InputFormat::from_path(Path::new("a")), None);
+    }
+}
@@ -1,6 +1,10 @@
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
 
-#[derive(Serialize, Deserialize, Debug)]
+// バージョンに関わらず共通の項目
+#[derive(Deserialize, Debug)]
 pub struct Json {
     pub version: u8,
+    // ディスクセットの場合のみ存在する(内容はバージョンごとに読み込む)
+    pub discs: Option<IgnoredAny>,
 }
@@ -1,2 +1,6 @@
+pub mod audio;
+pub use audio::*;
+pub mod document;
+pub use document::*;
 pub mod json;
 pub use json::*;
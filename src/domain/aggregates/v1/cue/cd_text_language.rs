@@ -0,0 +1,25 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CdTextLanguage {
+    English,
+    Japanese,
+    German,
+    French,
+    Italian,
+    Dutch,
+    Spanish,
+}
+
+impl CdTextLanguage {
+    // CD-TEXTの言語コード(EBU Tech 3264)
+    pub fn code(&self) -> u8 {
+        match self {
+            CdTextLanguage::English => 0x09,
+            CdTextLanguage::Japanese => 0x69,
+            CdTextLanguage::German => 0x08,
+            CdTextLanguage::French => 0x0F,
+            CdTextLanguage::Italian => 0x15,
+            CdTextLanguage::Dutch => 0x1D,
+            CdTextLanguage::Spanish => 0x0A,
+        }
+    }
+}
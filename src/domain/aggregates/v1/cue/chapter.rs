@@ -0,0 +1,407 @@
+use super::cue::Cue;
+use super::header::Header;
+use crate::share::util::{self, TextStyle};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub number: usize,
+    pub title: String,
+    pub performer: Option<String>,
+    pub composer: Option<String>,
+    pub path: String,
+    // ファイル先頭からのフレーム数
+    pub file_start: u32,
+    // 全ファイルを連結したストリーム先頭からのフレーム数
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapters {
+    pub title: String,
+    pub performer: Option<String>,
+    pub genre: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Cue {
+    // チャプターはINDEX 01から次のトラックのINDEX 01(最後のトラックはストリーム末尾)まで
+    // * PREGAPによる無音はストリームに含まれないため考慮しない
+    pub fn chapters(&self, file_lengths: &[u32]) -> Result<Chapters, String> {
+        let mut chapters: Vec<Chapter> = Vec::new();
+        let mut file_start: u32 = 0;
+        // * チャプター番号はトラック番号と同じ
+        let mut number = self.first_track_number();
+
+        for (file_index, file) in self.files().iter().enumerate() {
+            let Some(file_length) = file_lengths.get(file_index) else {
+                return Err(format!("length of {} is unknown", file.path()));
+            };
+            for track in file.tracks().iter() {
+                let index_01 = match track.index_01() {
+                    Ok(res) => res,
+                    Err(res) => return Err(format!("error at track No.{}:\n{}", number, res)),
+                };
+                if index_01.to_frame() > *file_length {
+                    return Err(format!(
+                        "INDEX 01 of track No.{} exceeds the length of {}",
+                        number,
+                        file.path()
+                    ));
+                }
+                chapters.push(Chapter {
+                    number,
+                    title: String::from(track.title()),
+                    performer: Self::non_empty(track.info().and_then(|i| i.performer())),
+                    composer: Self::non_empty(track.info().and_then(|i| i.composer())),
+                    path: String::from(file.path()),
+                    file_start: index_01.to_frame(),
+                    start: file_start + index_01.to_frame(),
+                    end: 0,
+                });
+                number += 1;
+            }
+            file_start += file_length;
+        }
+
+        let next_starts: Vec<u32> = chapters
+            .iter()
+            .skip(1)
+            .map(|c| c.start)
+            .chain([file_start])
+            .collect();
+        for (chapter, next_start) in chapters.iter_mut().zip(next_starts) {
+            if next_start < chapter.start {
+                return Err(format!(
+                    "INDEX 01 of track No.{} is before the previous track",
+                    chapter.number + 1
+                ));
+            }
+            chapter.end = next_start;
+        }
+
+        Ok(Chapters {
+            title: String::from(self.title()),
+            performer: self.info().and_then(|i| i.performer()).cloned(),
+            genre: self.genre().cloned(),
+            chapters,
+        })
+    }
+
+    // 空文字列は消去された項目として扱う
+    pub(super) fn non_empty(value: Option<&String>) -> Option<String> {
+        value.filter(|v| !v.is_empty()).cloned()
+    }
+}
+
+impl Chapter {
+    pub fn length(&self) -> u32 {
+        self.end - self.start
+    }
+}
+
+impl Chapters {
+    pub(super) const FRAMES_PER_SECOND: u64 = 75;
+    const NANOS_PER_SECOND: u64 = 1_000_000_000;
+    const FFMETADATA_HEADER: &str = ";FFMETADATA1";
+    const FFMETADATA_CHAPTER: &str = "[CHAPTER]";
+    const FFMETADATA_TIMEBASE: &str = "TIMEBASE=1/75";
+    const MATROSKA_LANGUAGE: &str = "und";
+
+    // Audacityのラベル(開始秒、終了秒、ラベルをタブ区切り)
+    pub fn to_audacity_labels_string(&self, style: &TextStyle) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for chapter in self.chapters.iter() {
+            lines.push(format!(
+                "{:.6}\t{:.6}\t{}",
+                Self::to_seconds(chapter.start),
+                Self::to_seconds(chapter.end),
+                chapter.title.replace(['\t', '\r', '\n'], " ")
+            ));
+        }
+        // * 最終行にも改行が必要
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    // FFmpegのメタデータファイル(タイムベースはCDのフレーム単位)
+    pub fn to_ffmetadata_string(&self, header: &Header, style: &TextStyle) -> String {
+        let mut lines: Vec<String> = vec![String::from(Self::FFMETADATA_HEADER)];
+
+        lines.push(format!("title={}", Self::escape_ffmetadata(&self.title)));
+        if let Some(performer) = &self.performer {
+            lines.push(format!("artist={}", Self::escape_ffmetadata(performer)));
+        }
+        if let Some(genre) = &self.genre {
+            lines.push(format!("genre={}", Self::escape_ffmetadata(genre)));
+        }
+        if let Some(comment) = &header.comment {
+            lines.push(format!("comment={}", Self::escape_ffmetadata(comment)));
+        }
+        for chapter in self.chapters.iter() {
+            lines.push(String::new());
+            lines.push(String::from(Self::FFMETADATA_CHAPTER));
+            lines.push(String::from(Self::FFMETADATA_TIMEBASE));
+            lines.push(format!("START={}", chapter.start));
+            lines.push(format!("END={}", chapter.end));
+            lines.push(format!("title={}", Self::escape_ffmetadata(&chapter.title)));
+            if let Some(performer) = &chapter.performer {
+                lines.push(format!("artist={}", Self::escape_ffmetadata(performer)));
+            }
+        }
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    // mkvmergeで読み込めるMatroskaのチャプターXML
+    pub fn to_matroska_xml_string(&self, header: &Header, style: &TextStyle) -> String {
+        let indent = |s: String, level: usize| {
+            (0..level).fold(s, |acc, _| util::build_indent_string(&acc, style))
+        };
+        let mut lines: Vec<String> = vec![
+            String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"),
+            String::from("<!DOCTYPE Chapters SYSTEM \"matroskachapters.dtd\">"),
+        ];
+
+        if let Some(comment) = &header.comment {
+            lines.push(format!("<!-- {} -->", comment.replace("--", "- -")));
+        }
+        lines.push(String::from("<Chapters>"));
+        lines.push(indent(String::from("<EditionEntry>"), 1));
+        for chapter in self.chapters.iter() {
+            lines.push(indent(String::from("<ChapterAtom>"), 2));
+            lines.push(indent(
+                format!(
+                    "<ChapterTimeStart>{}</ChapterTimeStart>",
+                    Self::to_timestamp(chapter.start)
+                ),
+                3,
+            ));
+            lines.push(indent(
+                format!(
+                    "<ChapterTimeEnd>{}</ChapterTimeEnd>",
+                    Self::to_timestamp(chapter.end)
+                ),
+                3,
+            ));
+            lines.push(indent(String::from("<ChapterDisplay>"), 3));
+            lines.push(indent(
+                format!(
+                    "<ChapterString>{}</ChapterString>",
+                    Self::escape_xml(&chapter.title)
+                ),
+                4,
+            ));
+            lines.push(indent(
+                format!(
+                    "<ChapterLanguage>{}</ChapterLanguage>",
+                    Self::MATROSKA_LANGUAGE
+                ),
+                4,
+            ));
+            lines.push(indent(String::from("</ChapterDisplay>"), 3));
+            lines.push(indent(String::from("</ChapterAtom>"), 2));
+        }
+        lines.push(indent(String::from("</EditionEntry>"), 1));
+        lines.push(String::from("</Chapters>"));
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    pub(super) fn to_seconds(frame: u32) -> f64 {
+        frame as f64 / Self::FRAMES_PER_SECOND as f64
+    }
+
+    // HH:MM:SS.nnnnnnnnn
+    fn to_timestamp(frame: u32) -> String {
+        let nanos = (frame as u64 * Self::NANOS_PER_SECOND * 2 + Self::FRAMES_PER_SECOND)
+            / (Self::FRAMES_PER_SECOND * 2);
+        let seconds = nanos / Self::NANOS_PER_SECOND;
+        format!(
+            "{:0>2}:{:0>2}:{:0>2}.{:0>9}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            nanos % Self::NANOS_PER_SECOND
+        )
+    }
+
+    fn escape_ffmetadata(value: &str) -> String {
+        let mut result = String::new();
+        for c in value.chars() {
+            if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+                result.push('\\');
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    fn escape_xml(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Info, Pregap, PregapType, Rem, Time, Track};
+    use super::*;
+
+    fn build_track(title: &str, start_at: &[u8]) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from(title),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            Time::from_vec(start_at)?,
+        ))
+    }
+
+    fn build_cue() -> Result<Cue, String> {
+        let file_1 = File::new(
+            String::from("1.wav"),
+            vec![
+                build_track("One", &[0, 0, 0])?,
+                build_track("Two=2", &[1, 0, 0])?,
+            ],
+            FileType::Wave,
+        );
+        let file_2 = File::new(
+            String::from("2.wav"),
+            vec![build_track("<Three>", &[0, 0, 0])?],
+            FileType::Wave,
+        );
+        Ok(Cue::new(
+            String::from("album"),
+            Some(String::from("rock")),
+            Some(Info::new(None, None, None, Some(String::from("P")))),
+            Rem::default(),
+            vec![file_1, file_2],
+        ))
+    }
+
+    #[test]
+    fn test_chapters() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = build_cue()?;
+
+        let result = cue.chapters(&[75 * 90, 75 * 30 + 1])?;
+
+        let ranges: Vec<(u32, u32)> = result.chapters.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(
+            ranges,
+            vec![(0, 75 * 60), (75 * 60, 75 * 90), (75 * 90, 75 * 120 + 1)]
+        );
+        assert_eq!(result.performer, Some(String::from("P")));
+        assert_eq!(result.chapters[2].number, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chapters_err() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = build_cue()?;
+
+        let Err(short) = cue.chapters(&[75 * 30, 75 * 30]) else {
+            return Err("".into());
+        };
+        let Err(unknown) = cue.chapters(&[75 * 90]) else {
+            return Err("".into());
+        };
+
+        assert_eq!(short, "INDEX 01 of track No.2 exceeds the length of 1.wav");
+        assert_eq!(unknown, "length of 2.wav is unknown");
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_audacity_labels_string() -> Result<(), Box<dyn std::error::Error>> {
+        let chapters = build_cue()?.chapters(&[75 * 90, 75 * 30 + 1])?;
+
+        let result = chapters.to_audacity_labels_string(&TextStyle::default());
+
+        let expected = [
+            "0.000000\t60.000000\tOne",
+            "60.000000\t90.000000\tTwo=2",
+            "90.000000\t120.013333\t<Three>",
+            "",
+        ];
+        assert_eq!(result, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_ffmetadata_string() -> Result<(), Box<dyn std::error::Error>> {
+        let chapters = build_cue()?.chapters(&[75 * 90, 75 * 30])?;
+
+        let result =
+            chapters.to_ffmetadata_string(&Header::new(false, None), &TextStyle::default());
+
+        let expected = [
+            ";FFMETADATA1",
+            "title=album",
+            "artist=P",
+            "genre=rock",
+            "",
+            "[CHAPTER]",
+            "TIMEBASE=1/75",
+            "START=0",
+            "END=4500",
+            "title=One",
+            "",
+            "[CHAPTER]",
+            "TIMEBASE=1/75",
+            "START=4500",
+            "END=6750",
+            "title=Two\\=2",
+            "",
+            "[CHAPTER]",
+            "TIMEBASE=1/75",
+            "START=6750",
+            "END=9000",
+            "title=<Three>",
+            "",
+        ];
+        assert_eq!(result, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_matroska_xml_string() -> Result<(), Box<dyn std::error::Error>> {
+        let mut chapters = build_cue()?.chapters(&[75 * 90, 75 * 30 + 1])?;
+        chapters.chapters.drain(..2);
+
+        let result = chapters.to_matroska_xml_string(&Header::default(), &TextStyle::default());
+
+        let expected = [
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+            "<!DOCTYPE Chapters SYSTEM \"matroskachapters.dtd\">",
+            "<!-- Generated by Json2Cue -->",
+            "<Chapters>",
+            "  <EditionEntry>",
+            "    <ChapterAtom>",
+            "      <ChapterTimeStart>00:01:30.000000000</ChapterTimeStart>",
+            "      <ChapterTimeEnd>00:02:00.013333333</ChapterTimeEnd>",
+            "      <ChapterDisplay>",
+            "        <ChapterString>&lt;Three&gt;</ChapterString>",
+            "        <ChapterLanguage>und</ChapterLanguage>",
+            "      </ChapterDisplay>",
+            "    </ChapterAtom>",
+            "  </EditionEntry>",
+            "</Chapters>",
+            "",
+        ];
+        assert_eq!(result, expected.join("\n"));
+        Ok(())
+    }
+}
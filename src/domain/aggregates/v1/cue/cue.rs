@@ -1,47 +1,150 @@
+use super::cd_text_language::CdTextLanguage;
 use super::file::{File, FileFormatter};
+use super::header::Header;
 use super::info::{Info, InfoFormatter};
-use crate::share::util;
+use super::rem::{Rem, RemFormatter};
+use crate::share::util::{self, TextStyle};
 
 pub struct Cue<I: InfoFormatter = Info, F: FileFormatter = File> {
     title: String,
     genre: Option<String>,
     info: Option<I>,
+    rem: Rem,
+    catalog: Option<String>,
+    first_track_number: usize,
     files: Vec<F>,
 }
 
 impl<I: InfoFormatter, F: FileFormatter> Cue<I, F> {
     const KEY_TITLE: &str = "TITLE";
     const KEY_GENRE: &str = "REM GENRE";
-    const STATIC_COMMENTS: [&str; 2] = ["REM CDRTOOLS", "REM COMMENT Generated by Json2Cue"];
+    const KEY_CDRTOOLS: &str = "REM CDRTOOLS";
+    const KEY_COMMENT: &str = "COMMENT";
+    const KEY_CATALOG: &str = "CATALOG";
+    const CATALOG_LENGTH: usize = 13;
+    pub const DEFAULT_FIRST_TRACK_NUMBER: usize = 1;
+    pub const MAX_TRACK_NUMBER: usize = 99;
+    const KEY_TOC_CD_DA: &str = "CD_DA";
+    const KEY_TOC_CD_TEXT: &str = "CD_TEXT";
+    const KEY_TOC_LANGUAGE_MAP: &str = "LANGUAGE_MAP";
+    const KEY_TOC_LANGUAGE: &str = "LANGUAGE";
 
-    pub fn new(title: String, genre: Option<String>, info: Option<I>, files: Vec<F>) -> Cue<I, F> {
+    pub fn new(
+        title: String,
+        genre: Option<String>,
+        info: Option<I>,
+        rem: Rem,
+        files: Vec<F>,
+    ) -> Cue<I, F> {
         Cue {
             title,
             genre,
             info,
+            rem,
+            catalog: None,
+            first_track_number: Self::DEFAULT_FIRST_TRACK_NUMBER,
             files,
         }
     }
 
-    pub fn to_cdtext_string(&self) -> Result<String, String> {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn genre(&self) -> Option<&String> {
+        self.genre.as_ref()
+    }
+
+    pub fn info(&self) -> Option<&I> {
+        self.info.as_ref()
+    }
+
+    pub fn rem(&self) -> &Rem {
+        &self.rem
+    }
+
+    pub fn set_rem(&mut self, key: String, value: String) {
+        self.rem.set(key, value);
+    }
+
+    pub fn catalog(&self) -> Option<&String> {
+        self.catalog.as_ref()
+    }
+
+    pub fn set_catalog(&mut self, catalog: Option<String>) {
+        self.catalog = catalog;
+    }
+
+    pub fn first_track_number(&self) -> usize {
+        self.first_track_number
+    }
+
+    // 続きのディスクなど1以外から番号を振る場合に指定する
+    pub fn set_first_track_number(&mut self, number: usize) -> Result<(), String> {
+        if !(Self::DEFAULT_FIRST_TRACK_NUMBER..=Self::MAX_TRACK_NUMBER).contains(&number) {
+            return Err(format!(
+                "first track number must be >={} and <={}",
+                Self::DEFAULT_FIRST_TRACK_NUMBER,
+                Self::MAX_TRACK_NUMBER
+            ));
+        }
+        self.first_track_number = number;
+        Ok(())
+    }
+
+    // カタログ番号(UPC/EAN)は13桁の数字
+    pub fn validate_catalog(catalog: &str) -> Result<(), String> {
+        if catalog.len() != Self::CATALOG_LENGTH || !catalog.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!(
+                "\"{}\" is not a valid catalog number ({} digits)",
+                catalog,
+                Self::CATALOG_LENGTH
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn files(&self) -> &Vec<F> {
+        &self.files
+    }
+
+    pub fn files_mut(&mut self) -> &mut Vec<F> {
+        &mut self.files
+    }
+
+    pub fn to_cdtext_string(&self, header: &Header, style: &TextStyle) -> Result<String, String> {
         let mut lines: Vec<String> = Vec::new();
 
-        // static comments
-        lines.extend(Self::STATIC_COMMENTS.map(|s| String::from(s)));
+        // header comments
+        if header.cdrtools {
+            lines.push(String::from(Self::KEY_CDRTOOLS));
+        }
+        // * remでCOMMENTが指定されている場合はそちらを優先
+        if let Some(comment) = &header.comment
+            && !self.rem.contains_key(Self::KEY_COMMENT)
+        {
+            lines.push(format!("REM {} {}", Self::KEY_COMMENT, comment));
+        }
+        // catalog
+        if let Some(catalog) = &self.catalog {
+            lines.push(format!("{} {}", Self::KEY_CATALOG, catalog));
+        }
         // title
         lines.push(format!("{} \"{}\"", Self::KEY_TITLE, self.title));
         // genre
         if let Some(genre_string) = &self.genre {
             lines.push(format!("{} \"{}\"", Self::KEY_GENRE, genre_string));
         };
+        // rem
+        lines.extend(self.rem.to_cdtext_strings(false, style));
         // info
         if let Some(info) = &self.info {
-            lines.extend(info.to_cdtext_strings(false));
+            lines.extend(info.to_cdtext_strings(false, style));
         }
         // files
-        let mut current_track_number: usize = 1;
+        let mut current_track_number = self.first_track_number;
         for file in self.files.iter() {
-            let file_result = match file.to_cdtext_strings(current_track_number) {
+            let file_result = match file.to_cdtext_strings(current_track_number, style) {
                 Ok(res) => res,
                 Err(res) => {
                     return Err(res);
@@ -51,7 +154,72 @@ impl<I: InfoFormatter, F: FileFormatter> Cue<I, F> {
             current_track_number += file_result.track_count;
         }
 
-        Ok(util::join_strings_with_lf(&lines))
+        Ok(util::join_strings_with_line_ending(&lines, style))
+    }
+
+    // cdrdaoのTOC形式(CD-TEXTはブロック0のみ)
+    pub fn to_toc_string(
+        &self,
+        header: &Header,
+        language: CdTextLanguage,
+        style: &TextStyle,
+    ) -> Result<String, String> {
+        let mut lines: Vec<String> = Vec::new();
+
+        // header comments
+        if let Some(comment) = &header.comment {
+            lines.push(format!("// {}", comment));
+        }
+        lines.push(String::from(Self::KEY_TOC_CD_DA));
+        if let Some(catalog) = &self.catalog {
+            lines.push(format!("{} \"{}\"", Self::KEY_CATALOG, catalog));
+        }
+        lines.push(String::new());
+        // cd-text
+        let mut cd_text = vec![format!("{} \"{}\"", Self::KEY_TITLE, self.title)];
+        if let Some(info) = &self.info {
+            cd_text.extend(info.to_toc_strings());
+        }
+        let indent = |s: &str, level: usize| {
+            (0..level).fold(String::from(s), |acc, _| {
+                util::build_indent_string(&acc, style)
+            })
+        };
+        lines.push(format!("{} {{", Self::KEY_TOC_CD_TEXT));
+        lines.push(indent(&format!("{} {{", Self::KEY_TOC_LANGUAGE_MAP), 1));
+        lines.push(indent(&format!("0 : {}", language.code()), 2));
+        lines.push(indent("}", 1));
+        lines.push(indent(&format!("{} 0 {{", Self::KEY_TOC_LANGUAGE), 1));
+        lines.extend(cd_text.iter().map(|s| indent(s, 2)));
+        lines.push(indent("}", 1));
+        lines.push(String::from("}"));
+        // files
+        let mut current_track_number = self.first_track_number;
+        for file in self.files.iter() {
+            let file_result = file.to_toc_strings(current_track_number, style)?;
+            lines.extend(file_result.texts);
+            current_track_number += file_result.track_count;
+        }
+
+        Ok(util::join_strings_with_line_ending(&lines, style))
+    }
+}
+
+impl Cue {
+    // アルバムの値にフォールバックしないプレイヤー向けに、引き継いだ値を各トラックに書き出す
+    pub fn write_inherited_info(&mut self) {
+        let Some(album) = &self.info else {
+            return;
+        };
+        for file in self.files.iter_mut() {
+            for track in file.tracks_mut().iter_mut() {
+                let info = match track.info() {
+                    Some(info) => info.inherit(album),
+                    None => album.clone(),
+                };
+                track.set_info(Some(info));
+            }
+        }
     }
 }
 
@@ -59,31 +227,47 @@ impl<I: InfoFormatter, F: FileFormatter> Cue<I, F> {
 mod tests {
     use super::super::file::FileCdtextResult;
     use super::*;
+    use crate::share::util::{Indent, LineEnding};
 
     const TEST_INFO: [&str; 2] = ["info1", "info2"];
     const TEST_FILE: [&str; 3] = ["file", "  strings", "    are here"];
 
     struct MockInfo {}
     impl InfoFormatter for MockInfo {
-        fn to_cdtext_strings(&self, indent: bool) -> Vec<String> {
+        fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String> {
             let mut result: Vec<String> = Vec::new();
             if indent {
-                result.extend(TEST_INFO.map(|s| format!("  {s}")));
+                result.extend(TEST_INFO.map(|s| util::build_indent_string(s, style)));
                 return result;
             }
             result.extend(TEST_INFO.iter().map(|s| String::from(*s)));
             result
         }
+
+        fn to_toc_strings(&self) -> Vec<String> {
+            TEST_INFO.iter().map(|s| String::from(*s)).collect()
+        }
     }
 
     struct MockFile {}
     impl FileFormatter for MockFile {
-        fn to_cdtext_strings(&self, _: usize) -> Result<FileCdtextResult, String> {
-            let strings = TEST_FILE.map(|s| String::from(s));
-            return Ok(FileCdtextResult {
+        fn to_cdtext_strings(&self, _: usize, _: &TextStyle) -> Result<FileCdtextResult, String> {
+            let strings = TEST_FILE.map(String::from);
+            Ok(FileCdtextResult {
                 texts: Vec::from(strings),
                 track_count: 1,
-            });
+            })
+        }
+
+        fn to_toc_strings(
+            &self,
+            initial_number: usize,
+            _: &TextStyle,
+        ) -> Result<FileCdtextResult, String> {
+            Ok(FileCdtextResult {
+                texts: vec![String::new(), format!("track {}", initial_number)],
+                track_count: 1,
+            })
         }
     }
 
@@ -93,9 +277,9 @@ mod tests {
         let genre = Some(String::from("スーパーロック"));
         let info = Some(MockInfo {});
         let files = vec![MockFile {}, MockFile {}];
-        let cue = Cue::new(title, genre, info, files);
+        let cue = Cue::new(title, genre, info, Rem::default(), files);
 
-        let result = cue.to_cdtext_string()?;
+        let result = cue.to_cdtext_string(&Header::default(), &TextStyle::default())?;
 
         let expected = vec![
             "REM CDRTOOLS",
@@ -114,4 +298,247 @@ mod tests {
         assert_eq!(result, expected.join("\n"));
         Ok(())
     }
+
+    #[test]
+    fn test_to_cdtext_string_crlf() -> Result<(), Box<dyn std::error::Error>> {
+        let title = String::from("title");
+        let info: Option<MockInfo> = None;
+        let files = vec![MockFile {}];
+        let cue = Cue::new(title, None, info, Rem::default(), files);
+        let style = TextStyle::new(LineEnding::Crlf, Indent::Tabs(1));
+
+        let result = cue.to_cdtext_string(&Header::default(), &style)?;
+
+        let expected = [
+            "REM CDRTOOLS",
+            "REM COMMENT Generated by Json2Cue",
+            "TITLE \"title\"",
+            "file",
+            "  strings",
+            "    are here",
+        ];
+        assert_eq!(result, expected.join("\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_string_rem() -> Result<(), Box<dyn std::error::Error>> {
+        let title = String::from("title");
+        let info: Option<MockInfo> = None;
+        let rem = Rem::new(vec![
+            (String::from("DATE"), String::from("2024")),
+            (String::from("COMMENT"), String::from("\"our comment\"")),
+        ]);
+        let files = vec![MockFile {}];
+        let cue = Cue::new(title, None, info, rem, files);
+
+        let result = cue.to_cdtext_string(&Header::default(), &TextStyle::default())?;
+
+        let expected = [
+            "REM CDRTOOLS",
+            "TITLE \"title\"",
+            "REM DATE 2024",
+            "REM COMMENT \"our comment\"",
+            "file",
+            "  strings",
+            "    are here",
+        ];
+        assert_eq!(result, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_string_header() -> Result<(), Box<dyn std::error::Error>> {
+        let info: Option<MockInfo> = None;
+        let cue = Cue::new(
+            String::from("title"),
+            None,
+            info,
+            Rem::default(),
+            vec![MockFile {}],
+        );
+        let no_header = Header::new(false, None);
+        let custom_header = Header::new(false, Some(String::from("custom")));
+
+        let result_no_header = cue.to_cdtext_string(&no_header, &TextStyle::default())?;
+        let result_custom = cue.to_cdtext_string(&custom_header, &TextStyle::default())?;
+
+        assert_eq!(
+            result_no_header,
+            ["TITLE \"title\"", "file", "  strings", "    are here"].join("\n")
+        );
+        assert_eq!(
+            result_custom,
+            [
+                "REM COMMENT custom",
+                "TITLE \"title\"",
+                "file",
+                "  strings",
+                "    are here"
+            ]
+            .join("\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_toc_string() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = Cue::new(
+            String::from("title"),
+            Some(String::from("rock")),
+            Some(MockInfo {}),
+            Rem::default(),
+            vec![MockFile {}, MockFile {}],
+        );
+
+        let result = cue.to_toc_string(
+            &Header::default(),
+            CdTextLanguage::Japanese,
+            &TextStyle::default(),
+        )?;
+
+        let expected = [
+            "// Generated by Json2Cue",
+            "CD_DA",
+            "",
+            "CD_TEXT {",
+            "  LANGUAGE_MAP {",
+            "    0 : 105",
+            "  }",
+            "  LANGUAGE 0 {",
+            "    TITLE \"title\"",
+            "    info1",
+            "    info2",
+            "  }",
+            "}",
+            "",
+            "track 1",
+            "",
+            "track 2",
+        ];
+        assert_eq!(result, expected.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_string_catalog() -> Result<(), Box<dyn std::error::Error>> {
+        let info: Option<MockInfo> = None;
+        let mut cue = Cue::new(
+            String::from("title"),
+            None,
+            info,
+            Rem::default(),
+            vec![MockFile {}],
+        );
+        cue.set_catalog(Some(String::from("4988001234567")));
+
+        let result = cue.to_cdtext_string(&Header::new(false, None), &TextStyle::default())?;
+
+        assert_eq!(
+            result.lines().take(2).collect::<Vec<&str>>(),
+            vec!["CATALOG 4988001234567", "TITLE \"title\""]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_string_first_track_number() -> Result<(), Box<dyn std::error::Error>> {
+        let info: Option<MockInfo> = None;
+        let mut cue = Cue::new(
+            String::from("title"),
+            None,
+            info,
+            Rem::default(),
+            vec![MockFile {}, MockFile {}],
+        );
+
+        cue.set_first_track_number(5)?;
+        let result = cue.to_toc_string(
+            &Header::new(false, None),
+            CdTextLanguage::English,
+            &TextStyle::default(),
+        )?;
+
+        let tracks: Vec<&str> = result.lines().filter(|l| l.starts_with("track")).collect();
+        assert_eq!(tracks, vec!["track 5", "track 6"]);
+        assert!(cue.set_first_track_number(0).is_err());
+        assert!(cue.set_first_track_number(100).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_inherited_info() -> Result<(), Box<dyn std::error::Error>> {
+        use super::super::{FileType, Pregap, PregapType, Time, Track};
+
+        let build_track = |info: Option<Info>| -> Result<Track, String> {
+            Ok(Track::new(
+                String::from("title"),
+                info,
+                Rem::default(),
+                None,
+                Pregap {
+                    r#type: PregapType::Silent,
+                    duration: Time::from_frame(0)?,
+                },
+                Time::from_frame(0)?,
+            ))
+        };
+        let files = vec![File::new(
+            String::from("a.wav"),
+            vec![
+                build_track(None)?,
+                build_track(Some(Info::new(None, None, None, Some(String::new()))))?,
+            ],
+            FileType::Wave,
+        )];
+        let mut cue = Cue::new(
+            String::from("album"),
+            None,
+            Some(Info::new(
+                None,
+                Some(String::from("comp")),
+                None,
+                Some(String::from("band")),
+            )),
+            Rem::default(),
+            files,
+        );
+
+        cue.write_inherited_info();
+
+        let tracks = cue.files()[0].tracks();
+        assert_eq!(
+            tracks[0].info(),
+            Some(&Info::new(
+                None,
+                Some(String::from("comp")),
+                None,
+                Some(String::from("band")),
+            ))
+        );
+        assert_eq!(
+            tracks[1].info(),
+            Some(&Info::new(
+                None,
+                Some(String::from("comp")),
+                None,
+                Some(String::new()),
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_catalog() {
+        assert_eq!(
+            Cue::<MockInfo, MockFile>::validate_catalog("4988001234567"),
+            Ok(())
+        );
+        assert_eq!(
+            Cue::<MockInfo, MockFile>::validate_catalog("498800123456"),
+            Err(String::from(
+                "\"498800123456\" is not a valid catalog number (13 digits)"
+            ))
+        );
+    }
 }
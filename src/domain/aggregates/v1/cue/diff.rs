@@ -0,0 +1,484 @@
+use super::cue::Cue;
+use super::info::Info;
+use super::pregap_type::PregapType;
+use super::time::{Time, TimeFormatter};
+use super::track::{Track, TrackFormatter};
+use std::ops::Range;
+use std::path::Path;
+
+// 比較のためにトラックをcue sheetに現れる値へまとめたもの
+struct TrackSnapshot<'a> {
+    number: usize,
+    file_name: String,
+    track: &'a Track,
+    info: Info,
+}
+
+// INDEX 00の位置(appendedは前のファイル、hiddenは隠し音声のファイル)
+#[derive(PartialEq)]
+enum Index00 {
+    None,
+    InFile(u32),
+    Previous(Option<u32>),
+    Hidden(String),
+}
+
+impl Cue {
+    // 2つのCueの違いをトラックごとに並べる(違いがない場合は空)
+    // * FILEはファイル名のみ、CD-TEXTはアルバムから引き継いだ値で比べ、書式やパスの違いは無視する
+    pub fn diff(&self, new: &Cue) -> Vec<String> {
+        let mut lines = self.diff_disc(new);
+        let old_tracks = self.snapshots();
+        let new_tracks = new.snapshots();
+
+        for (old_index, new_index) in align_tracks(&old_tracks, &new_tracks) {
+            match (
+                old_index.map(|i| &old_tracks[i]),
+                new_index.map(|i| &new_tracks[i]),
+            ) {
+                (Some(old), Some(new)) => {
+                    let label = match old.number == new.number {
+                        true => format!("track {:02}", old.number),
+                        false => format!("track {:02} -> {:02}", old.number, new.number),
+                    };
+                    lines.extend(
+                        diff_track(old, new)
+                            .into_iter()
+                            .map(|l| format!("{}: {}", label, l)),
+                    );
+                }
+                (Some(old), None) => lines.push(format!(
+                    "track {:02}: removed (\"{}\")",
+                    old.number,
+                    old.track.title()
+                )),
+                (None, Some(new)) => lines.push(format!(
+                    "track {:02}: added (\"{}\")",
+                    new.number,
+                    new.track.title()
+                )),
+                (None, None) => {}
+            }
+        }
+        lines
+    }
+
+    fn diff_disc(&self, new: &Cue) -> Vec<String> {
+        let empty = Info::new(None, None, None, None);
+        let old_info = self.info().unwrap_or(&empty);
+        let new_info = new.info().unwrap_or(&empty);
+        let mut changes = Vec::new();
+        push_text_change(&mut changes, "TITLE", Some(self.title()), Some(new.title()));
+        push_info_changes(&mut changes, old_info, new_info);
+        push_text_change(
+            &mut changes,
+            "REM GENRE",
+            self.genre().map(String::as_str),
+            new.genre().map(String::as_str),
+        );
+        push_text_change(
+            &mut changes,
+            "CATALOG",
+            self.catalog().map(String::as_str),
+            new.catalog().map(String::as_str),
+        );
+        changes
+            .into_iter()
+            .map(|c| format!("disc: {}", c))
+            .collect()
+    }
+
+    fn snapshots(&self) -> Vec<TrackSnapshot<'_>> {
+        let empty = Info::new(None, None, None, None);
+        let album = self.info().unwrap_or(&empty);
+        self.files()
+            .iter()
+            .flat_map(|f| f.tracks().iter().map(move |t| (f.path(), t)))
+            .enumerate()
+            .map(|(index, (path, track))| TrackSnapshot {
+                number: self.first_track_number() + index,
+                file_name: file_name(path),
+                track,
+                // * トラックで省略した項目はアルバムの値を引き継ぐ
+                info: track.info().map_or(album.clone(), |i| i.inherit(album)),
+            })
+            .collect()
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map_or(String::from(path), |n| n.to_string_lossy().into_owned())
+}
+
+// タイトルの最長共通部分列で対応を取り、間に残ったトラックは位置(ファイル名とINDEX 01)、
+// それでも残ったものは順に組にする
+fn align_tracks(
+    old: &[TrackSnapshot],
+    new: &[TrackSnapshot],
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let titles = |t: &TrackSnapshot| String::from(t.track.title());
+    let positions = |t: &TrackSnapshot| {
+        let index_01 = t.track.index_01().unwrap_or(t.track.start_at());
+        format!("{} {}", t.file_name, index_01.to_frame())
+    };
+    let mut result = Vec::new();
+    for (old_range, new_range, matched) in align_by(old, new, titles) {
+        if matched {
+            result.push((Some(old_range.start), Some(new_range.start)));
+            continue;
+        }
+        let old_gap = &old[old_range.clone()];
+        let new_gap = &new[new_range.clone()];
+        for (old_sub, new_sub, _) in align_by(old_gap, new_gap, positions) {
+            let paired = old_sub.len().min(new_sub.len());
+            for k in 0..paired {
+                result.push((
+                    Some(old_range.start + old_sub.start + k),
+                    Some(new_range.start + new_sub.start + k),
+                ));
+            }
+            result.extend(
+                (old_sub.start + paired..old_sub.end).map(|i| (Some(old_range.start + i), None)),
+            );
+            result.extend(
+                (new_sub.start + paired..new_sub.end).map(|j| (None, Some(new_range.start + j))),
+            );
+        }
+    }
+    result
+}
+
+// キーの最長共通部分列で区切る
+// (旧の範囲, 新の範囲, 一致した組か)の列で、一致した組は1トラックずつ、間は残りの範囲
+fn align_by<K: PartialEq>(
+    old: &[TrackSnapshot],
+    new: &[TrackSnapshot],
+    key: impl Fn(&TrackSnapshot) -> K,
+) -> Vec<(Range<usize>, Range<usize>, bool)> {
+    let old_keys: Vec<K> = old.iter().map(&key).collect();
+    let new_keys: Vec<K> = new.iter().map(&key).collect();
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old_keys[i] == new_keys[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut old_start, mut new_start) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old_keys[i] == new_keys[j] {
+            if (old_start, new_start) != (i, j) {
+                result.push((old_start..i, new_start..j, false));
+            }
+            result.push((i..i + 1, j..j + 1, true));
+            i += 1;
+            j += 1;
+            (old_start, new_start) = (i, j);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if (old_start, new_start) != (old.len(), new.len()) {
+        result.push((old_start..old.len(), new_start..new.len(), false));
+    }
+    result
+}
+
+fn diff_track(old: &TrackSnapshot, new: &TrackSnapshot) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.file_name != new.file_name {
+        changes.push(format!("FILE {} -> {}", old.file_name, new.file_name));
+    }
+    push_text_change(
+        &mut changes,
+        "TITLE",
+        Some(old.track.title()),
+        Some(new.track.title()),
+    );
+    push_info_changes(&mut changes, &old.info, &new.info);
+    push_text_change(
+        &mut changes,
+        "ISRC",
+        old.track.isrc().map(String::as_str),
+        new.track.isrc().map(String::as_str),
+    );
+
+    let (old_pregap, new_pregap) = (silent_pregap(old.track), silent_pregap(new.track));
+    if old_pregap != new_pregap {
+        changes.push(format!(
+            "PREGAP {} -> {}",
+            format_time(old_pregap),
+            format_time(new_pregap)
+        ));
+    }
+    let (old_index_00, new_index_00) = (index_00(old.track), index_00(new.track));
+    // * 前のファイルの長さが分からない場合は位置を比べない
+    let unknown = matches!(
+        (&old_index_00, &new_index_00),
+        (Index00::Previous(None), Index00::Previous(_))
+            | (Index00::Previous(_), Index00::Previous(None))
+    );
+    if old_index_00 != new_index_00 && !unknown {
+        let shift = match (&old_index_00, &new_index_00) {
+            (Index00::InFile(o), Index00::InFile(n))
+            | (Index00::Previous(Some(o)), Index00::Previous(Some(n))) => format_shift(*o, *n),
+            _ => String::new(),
+        };
+        changes.push(format!(
+            "INDEX 00 {} -> {}{}",
+            format_index_00(&old_index_00),
+            format_index_00(&new_index_00),
+            shift
+        ));
+    }
+    // * INDEX 01が計算できない場合はstart_atで比べる
+    let old_index_01 = old.track.index_01().unwrap_or(old.track.start_at());
+    let new_index_01 = new.track.index_01().unwrap_or(new.track.start_at());
+    push_time_change(
+        &mut changes,
+        "INDEX 01",
+        Some(old_index_01),
+        Some(new_index_01),
+    );
+
+    let numbers: Vec<u8> = old
+        .track
+        .extra_indexes()
+        .iter()
+        .chain(new.track.extra_indexes())
+        .map(|(n, _)| *n)
+        .collect();
+    let max_number = numbers.into_iter().max().unwrap_or(0);
+    for number in 2..=max_number {
+        let find = |track: &Track| {
+            track
+                .extra_indexes()
+                .iter()
+                .find(|(n, _)| *n == number)
+                .map(|(_, t)| *t)
+        };
+        push_time_change(
+            &mut changes,
+            &format!("INDEX {:02}", number),
+            find(old.track),
+            find(new.track),
+        );
+    }
+    changes
+}
+
+fn silent_pregap(track: &Track) -> Option<u32> {
+    let pregap = track.pregap();
+    let frame = pregap.duration.to_frame();
+    (pregap.r#type == PregapType::Silent && frame != 0).then_some(frame)
+}
+
+fn index_00(track: &Track) -> Index00 {
+    if let Some(hidden_audio) = track.hidden_audio() {
+        return Index00::Hidden(file_name(&hidden_audio.path));
+    }
+    let pregap = track.pregap();
+    match pregap.r#type {
+        PregapType::Appended if pregap.duration.to_frame() != 0 => {
+            Index00::Previous(track.appended_index_00().map(Time::to_frame))
+        }
+        // * cue sheetから読んだ場合は長さが分からず、INDEX 00の位置のみを持つ
+        PregapType::Appended => match track.appended_index_00() {
+            Some(time) => Index00::Previous(Some(time.to_frame())),
+            None => Index00::None,
+        },
+        _ => match track.index_00() {
+            Some(time) => Index00::InFile(time.to_frame()),
+            None => Index00::None,
+        },
+    }
+}
+
+fn format_index_00(index_00: &Index00) -> String {
+    match index_00 {
+        Index00::None => String::from("(none)"),
+        Index00::InFile(frame) => format_time(Some(*frame)),
+        Index00::Previous(frame) => match frame {
+            Some(frame) => format!("{} (prev)", format_time(Some(*frame))),
+            None => String::from("(prev)"),
+        },
+        Index00::Hidden(name) => format!("(htoa {})", name),
+    }
+}
+
+fn format_time(frame: Option<u32>) -> String {
+    match frame.map(Time::from_frame) {
+        Some(Ok(time)) => time.to_msf_string(),
+        Some(Err(_)) => String::from("?"),
+        None => String::from("(none)"),
+    }
+}
+
+fn format_shift(old: u32, new: u32) -> String {
+    format!(" ({:+} frames)", i64::from(new) - i64::from(old))
+}
+
+fn push_time_change(changes: &mut Vec<String>, key: &str, old: Option<Time>, new: Option<Time>) {
+    let (old, new) = (old.map(Time::to_frame), new.map(Time::to_frame));
+    if old == new {
+        return;
+    }
+    let shift = match (old, new) {
+        (Some(o), Some(n)) => format_shift(o, n),
+        _ => String::new(),
+    };
+    changes.push(format!(
+        "{} {} -> {}{}",
+        key,
+        format_time(old),
+        format_time(new),
+        shift
+    ));
+}
+
+// * 空文字列は値がないものとして扱う
+fn push_text_change(changes: &mut Vec<String>, key: &str, old: Option<&str>, new: Option<&str>) {
+    let (old, new) = (old.filter(|v| !v.is_empty()), new.filter(|v| !v.is_empty()));
+    if old == new {
+        return;
+    }
+    let format =
+        |value: Option<&str>| value.map_or(String::from("(none)"), |v| format!("\"{}\"", v));
+    changes.push(format!("{} {} -> {}", key, format(old), format(new)));
+}
+
+fn push_info_changes(changes: &mut Vec<String>, old: &Info, new: &Info) {
+    let fields = [
+        ("PERFORMER", old.performer(), new.performer()),
+        ("REM COMPOSER", old.composer(), new.composer()),
+        ("REM ARRANGER", old.arranger(), new.arranger()),
+        ("REM LYRICIST", old.lyricist(), new.lyricist()),
+    ];
+    for (key, old, new) in fields {
+        push_text_change(
+            changes,
+            key,
+            old.map(String::as_str),
+            new.map(String::as_str),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Pregap, Rem};
+    use super::*;
+
+    fn build_track(
+        title: &str,
+        pregap_type: PregapType,
+        pregap: u32,
+        start_at: u32,
+    ) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from(title),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: pregap_type,
+                duration: Time::from_frame(pregap)?,
+            },
+            Time::from_frame(start_at)?,
+        ))
+    }
+
+    fn build_cue(path: &str, performer: &str, tracks: Vec<Track>) -> Cue {
+        Cue::new(
+            String::from("album"),
+            None,
+            Some(Info::new(None, None, None, Some(String::from(performer)))),
+            Rem::default(),
+            vec![File::new(String::from(path), tracks, FileType::Wave)],
+        )
+    }
+
+    #[test]
+    fn test_diff() -> Result<(), String> {
+        let mut old_second = build_track("Second", PregapType::Included, 150, 3000)?;
+        old_second.set_info(Some(Info::new(
+            None,
+            None,
+            None,
+            Some(String::from("Band")),
+        )));
+        let old = build_cue(
+            "/music/album.wav",
+            "Band",
+            vec![
+                build_track("First", PregapType::Silent, 0, 0)?,
+                old_second,
+                build_track("Third", PregapType::Silent, 0, 6000)?,
+                build_track("Fourth", PregapType::Silent, 0, 9000)?,
+            ],
+        );
+        let new = build_cue(
+            "album.wav",
+            "Band",
+            vec![
+                build_track("First", PregapType::Silent, 150, 0)?,
+                build_track("Intro", PregapType::Silent, 0, 1500)?,
+                build_track("Second", PregapType::Included, 150, 3010)?,
+                build_track("Fourth!", PregapType::Silent, 0, 9000)?,
+            ],
+        );
+
+        let result = old.diff(&new);
+
+        assert_eq!(
+            result,
+            vec![
+                "track 01: PREGAP (none) -> 00:02:00",
+                "track 02: added (\"Intro\")",
+                "track 02 -> 03: INDEX 00 00:40:00 -> 00:40:10 (+10 frames)",
+                "track 02 -> 03: INDEX 01 00:42:00 -> 00:42:10 (+10 frames)",
+                "track 03: removed (\"Third\")",
+                "track 04: TITLE \"Fourth\" -> \"Fourth!\"",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_disc() -> Result<(), String> {
+        let old = build_cue(
+            "1.wav",
+            "Band",
+            vec![build_track("First", PregapType::Silent, 0, 0)?],
+        );
+        let mut new = build_cue(
+            "2.flac",
+            "",
+            vec![build_track("First", PregapType::Silent, 0, 0)?],
+        );
+        new.set_catalog(Some(String::from("4988001234567")));
+
+        let result = new.diff(&new);
+        let changed = old.diff(&new);
+
+        assert!(result.is_empty());
+        assert_eq!(
+            changed,
+            vec![
+                "disc: PERFORMER \"Band\" -> (none)",
+                "disc: CATALOG (none) -> \"4988001234567\"",
+                "track 01: FILE 1.wav -> 2.flac",
+                "track 01: PERFORMER \"Band\" -> (none)",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,8 +1,19 @@
 use super::file_type::FileType;
+use super::time::Time;
 use super::track::{Track, TrackFormatter};
+use crate::share::util::TextStyle;
 
 pub trait FileFormatter {
-    fn to_cdtext_strings(&self, initial_number: usize) -> Result<FileCdtextResult, String>;
+    fn to_cdtext_strings(
+        &self,
+        initial_number: usize,
+        style: &TextStyle,
+    ) -> Result<FileCdtextResult, String>;
+    fn to_toc_strings(
+        &self,
+        initial_number: usize,
+        style: &TextStyle,
+    ) -> Result<FileCdtextResult, String>;
 }
 
 pub struct File<T: TrackFormatter = Track> {
@@ -18,7 +29,6 @@ pub struct FileCdtextResult {
 
 impl<T: TrackFormatter> File<T> {
     const KEY_FILE: &str = "FILE";
-    const KEY_WAVE: &str = "WAVE";
 
     pub fn new(path: String, tracks: Vec<T>, file_type: FileType) -> File<T> {
         File {
@@ -27,33 +37,128 @@ impl<T: TrackFormatter> File<T> {
             file_type,
         }
     }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn file_type(&self) -> &FileType {
+        &self.file_type
+    }
+
+    pub fn tracks(&self) -> &Vec<T> {
+        &self.tracks
+    }
+
+    pub fn tracks_mut(&mut self) -> &mut Vec<T> {
+        &mut self.tracks
+    }
 }
 
 impl<T: TrackFormatter> FileFormatter for File<T> {
-    fn to_cdtext_strings(&self, initial_number: usize) -> Result<FileCdtextResult, String> {
+    fn to_cdtext_strings(
+        &self,
+        initial_number: usize,
+        style: &TextStyle,
+    ) -> Result<FileCdtextResult, String> {
         let mut lines: Vec<String> = Vec::new();
 
-        // file
-        match self.file_type {
-            FileType::Wave => {}
+        let file_line = |path: &str, file_type: &FileType| {
+            format!("{} \"{}\" {}", Self::KEY_FILE, path, file_type.keyword())
+        };
+        // * 最初のトラックのINDEX 00がこのファイルより前にあれば、TRACKとINDEX 00を書いてからファイルを切り替える
+        // * 隠し音声はそのファイル、前のファイルの末尾のプリギャップは直前のFILEの続きに書く
+        if let Some(hidden_audio) = self.tracks.first().and_then(|t| t.hidden_audio()) {
+            lines.push(file_line(&hidden_audio.path, &hidden_audio.file_type));
+        }
+        match self.tracks.first() {
+            Some(track) if track.starts_before_file() => {
+                let (before_strings, track_strings) =
+                    match track.to_split_cdtext_strings(initial_number, style) {
+                        Ok(res) => res,
+                        Err(res) => {
+                            return Err(format!("error at track No.{}:\n{}", initial_number, res));
+                        }
+                    };
+                lines.extend(before_strings);
+                lines.push(file_line(&self.path, &self.file_type));
+                lines.extend(track_strings);
+            }
+            _ => lines.push(file_line(&self.path, &self.file_type)),
         }
-        lines.push(format!(
-            "{} \"{}\" {}",
-            Self::KEY_FILE,
-            self.path,
-            Self::KEY_WAVE,
-        ));
         // * これ以降はインデント
         // file detail
         let mut track_count = 0;
         for (index, track) in self.tracks.iter().enumerate() {
             let track_number = initial_number + index;
-            let track_strings = match track.to_cdtext_strings(track_number, true) {
+            if track.starts_before_file() {
+                if index != 0 {
+                    return Err(format!(
+                        "error at track No.{}:\nonly the first track of a file can start before the file",
+                        track_number
+                    ));
+                }
+                track_count += 1;
+                continue;
+            }
+            let track_strings = match track.to_cdtext_strings(track_number, true, style) {
+                Ok(res) => res,
+                Err(res) => {
+                    return Err(format!("error at track No.{}:\n{}", track_number, res));
+                }
+            };
+            lines.extend(track_strings);
+            track_count += 1;
+        }
+
+        Ok(FileCdtextResult {
+            texts: lines,
+            track_count,
+        })
+    }
+
+    // cdrdaoにはFILEの概念がないため、トラックごとにファイル内の範囲を指定する
+    fn to_toc_strings(
+        &self,
+        initial_number: usize,
+        style: &TextStyle,
+    ) -> Result<FileCdtextResult, String> {
+        let mut lines: Vec<String> = Vec::new();
+
+        let mut starts: Vec<Time> = Vec::new();
+        for (index, track) in self.tracks.iter().enumerate() {
+            match track.toc_start() {
+                Ok(res) => starts.push(res),
+                Err(res) => {
+                    return Err(format!(
+                        "error at track No.{}:\n{}",
+                        initial_number + index,
+                        res
+                    ));
+                }
+            }
+        }
+        // * 最後のトラックはファイル末尾まで
+        let mut track_count = 0;
+        for (index, track) in self.tracks.iter().enumerate() {
+            let track_number = initial_number + index;
+            let length = match starts.get(index + 1) {
+                Some(next) => match *next - starts[index] {
+                    Ok(res) => Some(res),
+                    Err(res) => {
+                        return Err(format!("error at track No.{}:\n{}", track_number, res));
+                    }
+                },
+                None => None,
+            };
+            let track_strings = match track.to_toc_strings(track_number, &self.path, length, style)
+            {
                 Ok(res) => res,
                 Err(res) => {
                     return Err(format!("error at track No.{}:\n{}", track_number, res));
                 }
             };
+            lines.push(String::new());
             lines.extend(track_strings);
             track_count += 1;
         }
@@ -67,21 +172,76 @@ impl<T: TrackFormatter> FileFormatter for File<T> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::time::TimeFormatter;
+    use super::super::{HiddenAudio, Pregap, PregapType, Rem};
     use super::*;
+    use crate::share::util::{self, Indent, LineEnding};
 
     const TEST_TRACK: [&str; 3] = ["track", "  strings", "  are here"];
 
     struct MockTrack {}
     impl TrackFormatter for MockTrack {
-        fn to_cdtext_strings(&self, number: usize, indent: bool) -> Result<Vec<String>, String> {
+        fn to_cdtext_strings(
+            &self,
+            number: usize,
+            indent: bool,
+            style: &TextStyle,
+        ) -> Result<Vec<String>, String> {
             let mut result: Vec<String> = Vec::from([number.to_string()]);
             if indent {
-                result.extend(TEST_TRACK.map(|s| format!("  {s}")));
+                result.extend(TEST_TRACK.map(|s| util::build_indent_string(s, style)));
                 return Ok(result);
             }
             result.extend(TEST_TRACK.iter().map(|s| String::from(*s)));
             Ok(result)
         }
+
+        fn toc_start(&self) -> Result<Time, String> {
+            Time::from_frame(0)
+        }
+
+        fn to_toc_strings(
+            &self,
+            number: usize,
+            _: &str,
+            _: Option<Time>,
+            _: &TextStyle,
+        ) -> Result<Vec<String>, String> {
+            Ok(vec![number.to_string()])
+        }
+    }
+
+    // toc_startに指定したフレームを返し、to_toc_stringsでは受け取った範囲を返す
+    struct MockTocTrack {
+        start: u32,
+    }
+    impl TrackFormatter for MockTocTrack {
+        fn to_cdtext_strings(
+            &self,
+            _: usize,
+            _: bool,
+            _: &TextStyle,
+        ) -> Result<Vec<String>, String> {
+            Ok(Vec::new())
+        }
+
+        fn toc_start(&self) -> Result<Time, String> {
+            Time::from_frame(self.start)
+        }
+
+        fn to_toc_strings(
+            &self,
+            number: usize,
+            path: &str,
+            length: Option<Time>,
+            _: &TextStyle,
+        ) -> Result<Vec<String>, String> {
+            let length = match length {
+                Some(res) => res.to_msf_string(),
+                None => String::from("end"),
+            };
+            Ok(vec![format!("{} {} {}", number, path, length)])
+        }
     }
 
     #[test]
@@ -91,7 +251,7 @@ mod tests {
         let file_type = FileType::Wave;
         let file: File<MockTrack> = File::new(path, tracks, file_type);
 
-        let result = file.to_cdtext_strings(1)?;
+        let result = file.to_cdtext_strings(1, &TextStyle::default())?;
 
         let expected = vec![
             "FILE \"/path/to/file.wav\" WAVE",
@@ -115,7 +275,7 @@ mod tests {
         let file_type = FileType::Wave;
         let file: File<MockTrack> = File::new(path, tracks, file_type);
 
-        let result = file.to_cdtext_strings(4)?;
+        let result = file.to_cdtext_strings(4, &TextStyle::default())?;
 
         let expected = vec![
             "FILE \"/path/to/file.wav\" WAVE",
@@ -136,4 +296,108 @@ mod tests {
         assert_eq!(result.track_count, 3);
         Ok(())
     }
+
+    #[test]
+    fn test_to_cdtext_strings_tabs() -> Result<(), Box<dyn std::error::Error>> {
+        let path = String::from("/path/to/file.wav");
+        let tracks = vec![MockTrack {}];
+        let file_type = FileType::Wave;
+        let file: File<MockTrack> = File::new(path, tracks, file_type);
+        let style = TextStyle::new(LineEnding::Lf, Indent::Tabs(1));
+
+        let result = file.to_cdtext_strings(1, &style)?;
+
+        let expected = vec![
+            "FILE \"/path/to/file.wav\" WAVE",
+            "1",
+            "\ttrack",
+            "\t  strings",
+            "\t  are here",
+        ];
+        assert_eq!(result.texts, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_toc_strings() -> Result<(), Box<dyn std::error::Error>> {
+        let tracks = vec![
+            MockTocTrack { start: 0 },
+            MockTocTrack { start: 75 * 60 },
+            MockTocTrack { start: 75 * 150 },
+        ];
+        let file: File<MockTocTrack> = File::new(String::from("a.wav"), tracks, FileType::Wave);
+
+        let result = file.to_toc_strings(2, &TextStyle::default())?;
+
+        let expected = vec![
+            "",
+            "2 a.wav 01:00:00",
+            "",
+            "3 a.wav 01:30:00",
+            "",
+            "4 a.wav end",
+        ];
+        assert_eq!(result.texts, expected);
+        assert_eq!(result.track_count, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_toc_strings_err() {
+        let tracks = vec![MockTocTrack { start: 75 }, MockTocTrack { start: 0 }];
+        let file: File<MockTocTrack> = File::new(String::from("a.wav"), tracks, FileType::Wave);
+
+        let Err(result) = file.to_toc_strings(1, &TextStyle::default()) else {
+            panic!("reversed tracks are accepted");
+        };
+
+        assert_eq!(
+            result,
+            "error at track No.1:\n00:00:00 is earlier than 00:01:00"
+        );
+    }
+
+    #[test]
+    fn test_to_cdtext_strings_hidden_audio() -> Result<(), Box<dyn std::error::Error>> {
+        let build_track = || -> Result<Track, String> {
+            Ok(Track::new(
+                String::from("title"),
+                None,
+                Rem::default(),
+                None,
+                Pregap {
+                    r#type: PregapType::Silent,
+                    duration: Time::from_frame(0)?,
+                },
+                Time::from_frame(0)?,
+            ))
+        };
+        let mut hidden_track = build_track()?;
+        hidden_track.set_hidden_audio(Some(HiddenAudio {
+            path: String::from("/path/to/htoa.wav"),
+            file_type: FileType::Wave,
+        }))?;
+        let file: File = File::new(
+            String::from("/path/to/file.wav"),
+            vec![hidden_track, build_track()?],
+            FileType::Wave,
+        );
+
+        let result = file.to_cdtext_strings(1, &TextStyle::default())?;
+
+        let expected = vec![
+            "FILE \"/path/to/htoa.wav\" WAVE",
+            "  TRACK 01 AUDIO",
+            "    TITLE \"title\"",
+            "    INDEX 00 00:00:00",
+            "FILE \"/path/to/file.wav\" WAVE",
+            "    INDEX 01 00:00:00",
+            "  TRACK 02 AUDIO",
+            "    TITLE \"title\"",
+            "    INDEX 01 00:00:00",
+        ];
+        assert_eq!(result.texts, expected);
+        assert_eq!(result.track_count, 2);
+        Ok(())
+    }
 }
@@ -1,4 +1,32 @@
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FileType {
     Wave,
+    Aiff,
+    Mp3,
+    Binary,
+    Motorola,
+}
+
+impl FileType {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            FileType::Wave => "WAVE",
+            FileType::Aiff => "AIFF",
+            FileType::Mp3 => "MP3",
+            FileType::Binary => "BINARY",
+            FileType::Motorola => "MOTOROLA",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<FileType> {
+        [
+            FileType::Wave,
+            FileType::Aiff,
+            FileType::Mp3,
+            FileType::Binary,
+            FileType::Motorola,
+        ]
+        .into_iter()
+        .find(|t| t.keyword() == keyword)
+    }
 }
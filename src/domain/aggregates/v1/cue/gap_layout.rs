@@ -0,0 +1,341 @@
+use super::cue::Cue;
+use super::pregap::Pregap;
+use super::pregap_type::PregapType;
+use super::time::{Time, TimeFormatter};
+use super::track::Track;
+use crate::share::Issue;
+
+// FILEの切り替えと重なるプリギャップの置き方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GapLayout {
+    // トラックのファイルの先頭に含める(EACのcompliant)
+    Compliant,
+    // 前のファイルの末尾に含める(EACのnoncompliant)
+    Noncompliant,
+}
+
+impl Cue {
+    pub fn has_appended_pregaps(&self) -> bool {
+        self.files()
+            .iter()
+            .flat_map(|f| f.tracks().iter())
+            .any(is_appended)
+    }
+
+    // 前のファイルの末尾に含まれるプリギャップは2つ目以降のファイルの最初のトラックのみ
+    // * エラーの位置はJSONの入力と同じ /files/1/tracks/0/pregap の形式
+    // * pregap_keyはプリギャップを指定するトラックの項目(v1は/pregap)
+    pub fn validate_appended_pregaps(&self, pregap_key: &str) -> Result<(), Issue> {
+        let mut number = self.first_track_number();
+        for (file_index, file) in self.files().iter().enumerate() {
+            for (track_index, track) in file.tracks().iter().enumerate() {
+                if is_appended(track) && (file_index == 0 || track_index != 0) {
+                    return Err(Issue::from(format!(
+                        "error at track No.{}:\npregap in the previous file is only allowed on the first track of a file after the first",
+                        number
+                    ))
+                    .within(&format!(
+                        "/files/{}/tracks/{}{}",
+                        file_index, track_index, pregap_key
+                    )));
+                }
+                number += 1;
+            }
+        }
+        Ok(())
+    }
+
+    // 前のファイルの末尾に含まれるプリギャップのINDEX 00を前のファイルの長さから決める
+    // * file_lengthsはファイルごとのフレーム数(不明な場合はNone)
+    pub fn resolve_appended_pregaps(&mut self, file_lengths: &[Option<u32>]) -> Result<(), String> {
+        self.validate_appended_pregaps("")?;
+        let mut number = self.first_track_number();
+        for (file_index, file) in self.files_mut().iter_mut().enumerate() {
+            let previous_length = file_index
+                .checked_sub(1)
+                .and_then(|i| file_lengths.get(i).copied().flatten());
+            for track in file.tracks_mut().iter_mut() {
+                if is_appended(track) {
+                    let index_00 =
+                        resolve_appended_index_00(previous_length, track.pregap().duration)
+                            .map_err(|e| format!("error at track No.{}:\n{}", number, e))?;
+                    track.set_appended_index_00(Some(index_00));
+                }
+                number += 1;
+            }
+        }
+        Ok(())
+    }
+
+    // FILEの切り替えと重なるプリギャップ(ファイルの最初のトラック)を指定した置き方に書き換え、変換した数を返す
+    // * 音声ファイルも変換後の置き方で分割されている必要がある
+    // * 前のファイルの末尾に置く場合、INDEX 00の位置は変換前のファイルの長さから決める
+    pub fn convert_gap_layout(
+        &mut self,
+        layout: GapLayout,
+        file_lengths: &[Option<u32>],
+    ) -> Result<usize, String> {
+        let mut converted = 0;
+        let mut number = self.first_track_number();
+        // 変換でファイルの先頭から前のファイルに移したフレーム数
+        let mut moved: Vec<u32> = vec![0; self.files().len()];
+        for file_index in 0..self.files().len() {
+            let track_number = number;
+            let tracks = self.files_mut()[file_index].tracks_mut();
+            number += tracks.len();
+            let Some(first) = tracks.first() else {
+                continue;
+            };
+            let duration = first.pregap().duration.to_frame();
+            if file_index == 0 || duration == 0 {
+                continue;
+            }
+            let previous_moved = moved[file_index - 1];
+            let result = match (layout, first.pregap().r#type) {
+                (GapLayout::Noncompliant, PregapType::Included)
+                    if first.start_at().to_frame() == 0 =>
+                {
+                    let Some(previous_length) = file_lengths.get(file_index - 1).copied().flatten()
+                    else {
+                        return Err(format!(
+                            "error at track No.{}:\nlength of the previous file is unknown",
+                            track_number
+                        ));
+                    };
+                    moved[file_index] = duration;
+                    converted += 1;
+                    move_to_previous_file(tracks, previous_length.saturating_sub(previous_moved))
+                }
+                // * 前のファイルの先頭を移した分だけ前にずれる
+                (GapLayout::Noncompliant, PregapType::Appended) => {
+                    match tracks[0].appended_index_00() {
+                        Some(index_00) => shift_time(index_00, -i64::from(previous_moved))
+                            .map(|t| tracks[0].set_appended_index_00(Some(t))),
+                        None => Ok(()),
+                    }
+                }
+                (GapLayout::Compliant, PregapType::Appended) => {
+                    converted += 1;
+                    move_to_track_file(tracks)
+                }
+                _ => Ok(()),
+            };
+            if let Err(res) = result {
+                return Err(format!("error at track No.{}:\n{}", track_number, res));
+            }
+        }
+        Ok(converted)
+    }
+}
+
+fn is_appended(track: &Track) -> bool {
+    matches!(track.pregap().r#type, PregapType::Appended) && track.pregap().duration.to_frame() != 0
+}
+
+fn resolve_appended_index_00(previous_length: Option<u32>, duration: Time) -> Result<Time, String> {
+    let Some(previous_length) = previous_length else {
+        return Err(String::from("length of the previous file is unknown"));
+    };
+    let Some(index_00) = previous_length.checked_sub(duration.to_frame()) else {
+        return Err(format!(
+            "pregap ({}) is longer than the previous file",
+            duration.to_msf_string()
+        ));
+    };
+    Time::from_frame(index_00)
+}
+
+// ファイル先頭のプリギャップを前のファイルの末尾(previous_end)に移し、ファイル内の位置を前にずらす
+fn move_to_previous_file(tracks: &mut [Track], previous_end: u32) -> Result<(), String> {
+    let duration = tracks[0].pregap().duration;
+    tracks[0].set_pregap(
+        Pregap {
+            r#type: PregapType::Appended,
+            duration,
+        },
+        Time::from_frame(0)?,
+    )?;
+    tracks[0].set_appended_index_00(Some(Time::from_frame(previous_end)?));
+    shift_tracks(tracks, -i64::from(duration.to_frame()))
+}
+
+// 前のファイルの末尾のプリギャップをファイルの先頭に移し、ファイル内の位置を後ろにずらす
+// * INDEX 01がファイルの先頭にない場合、その手前もプリギャップに含める
+fn move_to_track_file(tracks: &mut [Track]) -> Result<(), String> {
+    let duration = tracks[0].pregap().duration;
+    tracks[0].set_pregap(
+        Pregap {
+            r#type: PregapType::Included,
+            duration: (duration + tracks[0].start_at())?,
+        },
+        Time::from_frame(0)?,
+    )?;
+    shift_tracks(tracks, i64::from(duration.to_frame()))
+}
+
+// 最初のトラックはINDEX 02以降のみ(プリギャップを置き直した後に呼ぶ)、それ以降のトラックはすべての位置をずらす
+fn shift_tracks(tracks: &mut [Track], delta: i64) -> Result<(), String> {
+    for (index, track) in tracks.iter_mut().enumerate() {
+        let extra_indexes = track
+            .extra_indexes()
+            .iter()
+            .map(|(number, time)| shift_time(*time, delta).map(|t| (*number, t)))
+            .collect::<Result<Vec<(u8, Time)>, String>>()?;
+        track.set_extra_indexes(Vec::new())?;
+        if index != 0 {
+            let pregap = Pregap {
+                r#type: track.pregap().r#type,
+                duration: track.pregap().duration,
+            };
+            track.set_pregap(pregap, shift_time(track.start_at(), delta)?)?;
+        }
+        track.set_extra_indexes(extra_indexes)?;
+    }
+    Ok(())
+}
+
+fn shift_time(time: Time, delta: i64) -> Result<Time, String> {
+    let frame = i64::from(time.to_frame()) + delta;
+    let Ok(frame) = u32::try_from(frame) else {
+        return Err(format!("{} frames is out of range of Time object", frame));
+    };
+    Time::from_frame(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Header, Rem};
+    use super::*;
+    use crate::share::util::TextStyle;
+
+    fn build_track(pregap_type: PregapType, pregap: u32, start_at: u32) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: pregap_type,
+                duration: Time::from_frame(pregap)?,
+            },
+            Time::from_frame(start_at)?,
+        ))
+    }
+
+    // 1.wav: トラック1、2.wav: トラック2(2秒のプリギャップ)とトラック3
+    fn build_cue(pregap_type: PregapType, second_start_at: u32) -> Result<Cue, String> {
+        Ok(Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![
+                File::new(
+                    String::from("1.wav"),
+                    vec![build_track(PregapType::Silent, 0, 0)?],
+                    FileType::Wave,
+                ),
+                File::new(
+                    String::from("2.wav"),
+                    vec![
+                        build_track(pregap_type, 150, 0)?,
+                        build_track(PregapType::Silent, 0, second_start_at)?,
+                    ],
+                    FileType::Wave,
+                ),
+            ],
+        ))
+    }
+
+    fn to_lines(cue: &Cue) -> Result<Vec<String>, String> {
+        let header = Header {
+            cdrtools: false,
+            comment: None,
+        };
+        let text = cue.to_cdtext_string(&header, &TextStyle::default())?;
+        Ok(text.lines().skip(1).map(String::from).collect())
+    }
+
+    #[test]
+    fn test_convert_gap_layout_noncompliant() -> Result<(), String> {
+        let mut cue = build_cue(PregapType::Included, 1500)?;
+
+        let converted = cue.convert_gap_layout(GapLayout::Noncompliant, &[Some(3000), None])?;
+
+        assert_eq!(converted, 1);
+        assert_eq!(
+            to_lines(&cue)?,
+            vec![
+                "FILE \"1.wav\" WAVE",
+                "  TRACK 01 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 01 00:00:00",
+                "  TRACK 02 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 00 00:40:00",
+                "FILE \"2.wav\" WAVE",
+                "    INDEX 01 00:00:00",
+                "  TRACK 03 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 01 00:18:00",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_gap_layout_compliant() -> Result<(), String> {
+        let mut cue = build_cue(PregapType::Appended, 1350)?;
+        cue.resolve_appended_pregaps(&[Some(3150), None])?;
+        let before = to_lines(&cue)?;
+
+        let converted = cue.convert_gap_layout(GapLayout::Compliant, &[])?;
+
+        assert_eq!(before[6], "    INDEX 00 00:40:00");
+        assert_eq!(converted, 1);
+        assert_eq!(
+            to_lines(&cue)?[4..],
+            [
+                "FILE \"2.wav\" WAVE",
+                "  TRACK 02 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 00 00:00:00",
+                "    INDEX 01 00:02:00",
+                "  TRACK 03 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 01 00:20:00",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_appended_pregaps_err() -> Result<(), String> {
+        let mut unknown = build_cue(PregapType::Appended, 1350)?;
+        let mut too_long = build_cue(PregapType::Appended, 1350)?;
+        let mut first_file = build_cue(PregapType::Silent, 1350)?;
+        first_file.files_mut()[0].tracks_mut()[0] = build_track(PregapType::Appended, 150, 0)?;
+
+        let results = [
+            unknown.resolve_appended_pregaps(&[None, None]),
+            too_long.resolve_appended_pregaps(&[Some(100), None]),
+            first_file.resolve_appended_pregaps(&[Some(3000), Some(3000)]),
+        ];
+
+        assert_eq!(
+            results.map(|r| r.err()),
+            [
+                Some(String::from(
+                    "error at track No.2:\nlength of the previous file is unknown"
+                )),
+                Some(String::from(
+                    "error at track No.2:\npregap (00:02:00) is longer than the previous file"
+                )),
+                Some(String::from(
+                    "error at track No.1:\npregap in the previous file is only allowed on the first track of a file after the first"
+                )),
+            ]
+        );
+        Ok(())
+    }
+}
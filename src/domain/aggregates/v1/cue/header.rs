@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub cdrtools: bool,
+    pub comment: Option<String>,
+}
+
+impl Header {
+    const DEFAULT_COMMENT: &str = "Generated by Json2Cue";
+
+    pub fn new(cdrtools: bool, comment: Option<String>) -> Header {
+        Header { cdrtools, comment }
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Header::new(true, Some(String::from(Self::DEFAULT_COMMENT)))
+    }
+}
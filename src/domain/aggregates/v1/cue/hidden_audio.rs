@@ -0,0 +1,111 @@
+use super::cue::Cue;
+use super::file_type::FileType;
+use super::track::TrackFormatter;
+use crate::share::Issue;
+
+// トラック1のプリギャップに隠した音声(HTOA)を収めた別のファイル
+// * 前のFILEでINDEX 00 00:00:00、次のFILEの位置でINDEX 01とする
+#[derive(Debug, Clone, PartialEq)]
+pub struct HiddenAudio {
+    pub path: String,
+    pub file_type: FileType,
+}
+
+impl Cue {
+    pub fn hidden_audio(&self) -> Option<&HiddenAudio> {
+        self.files()
+            .first()
+            .and_then(|f| f.tracks().first())
+            .and_then(|t| t.hidden_audio())
+    }
+
+    // 隠し音声を置けるのはディスクの最初のトラックのみ
+    // * エラーの位置はJSONの入力と同じ /files/0/tracks/1 の形式
+    pub fn validate_hidden_audio(&self) -> Result<(), Issue> {
+        let mut number = self.first_track_number();
+        for (file_index, file) in self.files().iter().enumerate() {
+            for (track_index, track) in file.tracks().iter().enumerate() {
+                if track.hidden_audio().is_some() && (file_index, track_index) != (0, 0) {
+                    return Err(Issue::from(format!(
+                        "hidden audio is only allowed on the first track (track No.{})",
+                        number
+                    ))
+                    .within(&format!("/files/{}/tracks/{}", file_index, track_index)));
+                }
+                number += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, Pregap, PregapType, Rem, Time, Track};
+    use super::*;
+
+    fn build_track(hidden_audio: bool) -> Result<Track, String> {
+        let mut track = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_frame(0)?,
+            },
+            Time::from_frame(0)?,
+        );
+        if hidden_audio {
+            track.set_hidden_audio(Some(HiddenAudio {
+                path: String::from("htoa.wav"),
+                file_type: FileType::Wave,
+            }))?;
+        }
+        Ok(track)
+    }
+
+    fn build_cue(first: bool, second: bool) -> Result<Cue, String> {
+        Ok(Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![
+                File::new(
+                    String::from("1.wav"),
+                    vec![build_track(first)?],
+                    FileType::Wave,
+                ),
+                File::new(
+                    String::from("2.wav"),
+                    vec![build_track(second)?],
+                    FileType::Wave,
+                ),
+            ],
+        ))
+    }
+
+    #[test]
+    fn test_validate_hidden_audio() -> Result<(), String> {
+        let valid = build_cue(true, false)?;
+        let invalid = build_cue(false, true)?;
+
+        valid.validate_hidden_audio()?;
+        let Err(result) = invalid.validate_hidden_audio() else {
+            return Err(String::from("hidden audio on track 2 is accepted"));
+        };
+
+        assert_eq!(
+            valid.hidden_audio().map(|h| h.path.as_str()),
+            Some("htoa.wav")
+        );
+        assert_eq!(invalid.hidden_audio(), None);
+        assert_eq!(
+            result.message,
+            "hidden audio is only allowed on the first track (track No.2)"
+        );
+        assert_eq!(result.pointer.as_deref(), Some("/files/1/tracks/0"));
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
-use crate::share::util;
+use crate::share::util::{self, TextStyle};
 
 pub trait InfoFormatter {
-    fn to_cdtext_strings(&self, indent: bool) -> Vec<String>;
+    fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String>;
+    fn to_toc_strings(&self) -> Vec<String>;
 }
 
-#[derive(Debug)]
+// * 空文字列はアルバムの値を引き継がないよう明示的に消去された項目を表す
+#[derive(Debug, Clone, PartialEq)]
 pub struct Info {
     arranger: Option<String>,
     composer: Option<String>,
@@ -17,6 +19,9 @@ impl Info {
     const KEY_COMPOSER: &str = "REM COMPOSER";
     const KEY_LYRICIST: &str = "REM LYRICIST";
     const KEY_PERFORMER: &str = "PERFORMER";
+    const KEY_TOC_ARRANGER: &str = "ARRANGER";
+    const KEY_TOC_COMPOSER: &str = "COMPOSER";
+    const KEY_TOC_SONGWRITER: &str = "SONGWRITER";
 
     pub fn new(
         arranger: Option<String>,
@@ -25,40 +30,86 @@ impl Info {
         performer: Option<String>,
     ) -> Info {
         Info {
-            arranger: arranger,
-            composer: composer,
-            lyricist: lyricist,
-            performer: performer,
+            arranger,
+            composer,
+            lyricist,
+            performer,
         }
     }
 
-    fn build_string(base_string: String, indent: bool) -> String {
+    pub fn arranger(&self) -> Option<&String> {
+        self.arranger.as_ref()
+    }
+
+    pub fn composer(&self) -> Option<&String> {
+        self.composer.as_ref()
+    }
+
+    pub fn lyricist(&self) -> Option<&String> {
+        self.lyricist.as_ref()
+    }
+
+    pub fn performer(&self) -> Option<&String> {
+        self.performer.as_ref()
+    }
+
+    // 値のない項目をアルバムの値で補う
+    pub fn inherit(&self, album: &Info) -> Info {
+        Info {
+            arranger: self.arranger.clone().or(album.arranger.clone()),
+            composer: self.composer.clone().or(album.composer.clone()),
+            lyricist: self.lyricist.clone().or(album.lyricist.clone()),
+            performer: self.performer.clone().or(album.performer.clone()),
+        }
+    }
+
+    fn build_string(base_string: String, indent: bool, style: &TextStyle) -> String {
         if indent {
-            return util::build_indent_string(&base_string);
+            return util::build_indent_string(&base_string, style);
         }
         base_string
     }
 }
 
 impl InfoFormatter for Info {
-    fn to_cdtext_strings(&self, indent: bool) -> Vec<String> {
+    fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String> {
         let mut lines = Vec::new();
 
         if let Some(arranger) = &self.arranger {
             let key_value_string = format!("{} \"{}\"", Self::KEY_ARRANGER, arranger);
-            lines.push(Self::build_string(key_value_string, indent));
+            lines.push(Self::build_string(key_value_string, indent, style));
         }
         if let Some(composer) = &self.composer {
             let key_value_string = format!("{} \"{}\"", Self::KEY_COMPOSER, composer);
-            lines.push(Self::build_string(key_value_string, indent));
+            lines.push(Self::build_string(key_value_string, indent, style));
         }
         if let Some(lyricist) = &self.lyricist {
             let key_value_string = format!("{} \"{}\"", Self::KEY_LYRICIST, lyricist);
-            lines.push(Self::build_string(key_value_string, indent));
+            lines.push(Self::build_string(key_value_string, indent, style));
         }
         if let Some(performer) = &self.performer {
             let key_value_string = format!("{} \"{}\"", Self::KEY_PERFORMER, performer);
-            lines.push(Self::build_string(key_value_string, indent));
+            lines.push(Self::build_string(key_value_string, indent, style));
+        }
+
+        lines
+    }
+
+    // cdrdaoのCD_TEXTブロック内の項目(作詞者はSONGWRITERとして書き出す)
+    fn to_toc_strings(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let Some(performer) = &self.performer {
+            lines.push(format!("{} \"{}\"", Self::KEY_PERFORMER, performer));
+        }
+        if let Some(lyricist) = &self.lyricist {
+            lines.push(format!("{} \"{}\"", Self::KEY_TOC_SONGWRITER, lyricist));
+        }
+        if let Some(composer) = &self.composer {
+            lines.push(format!("{} \"{}\"", Self::KEY_TOC_COMPOSER, composer));
+        }
+        if let Some(arranger) = &self.arranger {
+            lines.push(format!("{} \"{}\"", Self::KEY_TOC_ARRANGER, arranger));
         }
 
         lines
@@ -81,11 +132,8 @@ mod tests {
         let Some(arranger) = result.arranger else {
             return Err(String::new());
         };
-        match result.performer {
-            Some(_) => {
-                return Err(String::new());
-            }
-            None => {}
+        if result.performer.is_some() {
+            return Err(String::new());
         }
         assert_eq!(arranger, String::from("arr"));
         Ok(())
@@ -100,7 +148,7 @@ mod tests {
             Some(String::from("perf")),
         );
 
-        let result = test_info.to_cdtext_strings(true);
+        let result = test_info.to_cdtext_strings(true, &TextStyle::default());
 
         assert_eq!(
             result.join("\n"),
@@ -119,7 +167,7 @@ mod tests {
             Some(String::from("わたかん")),
         );
 
-        let result = test_info.to_cdtext_strings(false);
+        let result = test_info.to_cdtext_strings(false, &TextStyle::default());
 
         assert_eq!(
             result.join(","),
@@ -131,8 +179,58 @@ mod tests {
     fn test_to_cdtext_strings_empty() {
         let test_info = Info::new(None, None, None, None);
 
-        let result = test_info.to_cdtext_strings(true);
+        let result = test_info.to_cdtext_strings(true, &TextStyle::default());
 
         assert_eq!(result.join(""), String::new());
     }
+
+    #[test]
+    fn test_inherit() {
+        let album = Info::new(
+            Some(String::from("arr")),
+            Some(String::from("comp")),
+            None,
+            Some(String::from("band")),
+        );
+        let track = Info::new(
+            None,
+            Some(String::new()),
+            Some(String::from("lyric")),
+            Some(String::from("guest")),
+        );
+
+        let result = track.inherit(&album);
+
+        assert_eq!(
+            result,
+            Info::new(
+                Some(String::from("arr")),
+                Some(String::new()),
+                Some(String::from("lyric")),
+                Some(String::from("guest")),
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_toc_strings() {
+        let test_info = Info::new(
+            Some(String::from("arr")),
+            Some(String::from("comp")),
+            Some(String::from("lyric")),
+            Some(String::from("perf")),
+        );
+
+        let result = test_info.to_toc_strings();
+
+        assert_eq!(
+            result,
+            vec![
+                "PERFORMER \"perf\"",
+                "SONGWRITER \"lyric\"",
+                "COMPOSER \"comp\"",
+                "ARRANGER \"arr\""
+            ]
+        );
+    }
 }
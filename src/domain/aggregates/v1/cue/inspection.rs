@@ -0,0 +1,304 @@
+use super::cue::Cue;
+use super::layout::{DiscLayout, TrackLayout};
+use super::pregap_type::PregapType;
+use super::time::{Time, TimeFormatter};
+use super::track::{Track, TrackFormatter};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+// inspectの表の列
+pub const INSPECTION_COLUMNS: [&str; 8] = [
+    "#",
+    "File",
+    "INDEX 00",
+    "INDEX 01",
+    "Pregap",
+    "Length",
+    "Performer",
+    "Title",
+];
+const FRAMES_PER_SECOND: u32 = 75;
+// Red Bookのトラックの最短の長さ(4秒)
+const MIN_TRACK_FRAMES: u32 = 4 * FRAMES_PER_SECOND;
+const UNKNOWN: &str = "?";
+
+impl Cue {
+    // 計算したレイアウトをトラックごとの表にし、ディスクの合計と確認が必要な点を添える
+    // * file_lengthsはファイルごとのフレーム数(不明な場合はNone)
+    pub fn to_inspection_strings(
+        &self,
+        layout: &DiscLayout,
+        file_lengths: &[Option<u32>],
+    ) -> Vec<String> {
+        let tracks: Vec<&Track> = self.files().iter().flat_map(|f| f.tracks()).collect();
+        let rows: Vec<[String; 8]> = layout
+            .tracks
+            .iter()
+            .zip(tracks.iter())
+            .map(|(track_layout, track)| self.build_inspection_row(track_layout, track))
+            .collect();
+
+        let mut lines = build_table(&rows);
+        lines.push(String::new());
+        lines.push(format!(
+            "Total: {} track(s) in {} file(s), {}",
+            layout.tracks.len(),
+            self.files().len(),
+            layout.lead_out.map_or(String::from(UNKNOWN), to_msf)
+        ));
+        let silence: u32 = layout.tracks.iter().map(|t| t.silent_pregap).sum();
+        if silence != 0 {
+            lines.push(format!("Silent pregaps: {}", to_msf(silence)));
+        }
+
+        let notes = build_notes(layout, file_lengths);
+        if !notes.is_empty() {
+            lines.push(String::new());
+            lines.extend(notes.into_iter().map(|n| format!("! {}", n)));
+        }
+        lines
+    }
+
+    fn build_inspection_row(&self, layout: &TrackLayout, track: &Track) -> [String; 8] {
+        let path = self.files()[layout.file_index].path();
+        let file_name = Path::new(path)
+            .file_name()
+            .map_or(String::from(path), |n| n.to_string_lossy().into_owned());
+        let index_00 = match track.pregap().r#type {
+            PregapType::Appended if track.pregap().duration.to_frame() != 0 => {
+                match track.appended_index_00() {
+                    Some(time) => format!("{} (prev)", time.to_msf_string()),
+                    None => String::from("(prev)"),
+                }
+            }
+            _ if track.hidden_audio().is_some() => String::from("(htoa)"),
+            _ => layout
+                .index_00
+                .as_ref()
+                .map_or(String::new(), Time::to_msf_string),
+        };
+        let pregap = match track.pregap().duration.to_frame() {
+            0 => String::new(),
+            _ => format!(
+                "{} {}",
+                match track.pregap().r#type {
+                    PregapType::Silent => "silent",
+                    PregapType::Included => "included",
+                    PregapType::Appended => "appended",
+                },
+                track.pregap().duration.to_msf_string()
+            ),
+        };
+        // * トラックで省略した演奏者はアルバムの値を引き継ぐ
+        let performer = track
+            .info()
+            .and_then(|i| i.performer())
+            .or(self.info().and_then(|i| i.performer()))
+            .cloned()
+            .unwrap_or_default();
+        [
+            layout.number.to_string(),
+            file_name,
+            index_00,
+            layout.index_01.to_msf_string(),
+            pregap,
+            layout.length.map_or(String::from(UNKNOWN), to_msf),
+            performer,
+            String::from(track.title()),
+        ]
+    }
+}
+
+fn build_table(rows: &[[String; 8]]) -> Vec<String> {
+    let header = INSPECTION_COLUMNS.map(String::from);
+    // * 全角文字で列がずれないよう、文字数ではなく表示幅で揃える
+    let mut widths = header.clone().map(|c| c.width());
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.width());
+        }
+    }
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| {
+                    let padding = width - cell.width();
+                    format!("{}{}", cell, " ".repeat(padding))
+                })
+                .collect();
+            String::from(cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+fn build_notes(layout: &DiscLayout, file_lengths: &[Option<u32>]) -> Vec<String> {
+    let mut notes: Vec<String> = Vec::new();
+    for (index, track) in layout.tracks.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| &layout.tracks[i]);
+        let out_of_order = match (previous, track.offset) {
+            (Some(previous), _) if previous.file_index == track.file_index => {
+                track.index_01 <= previous.index_01
+            }
+            (Some(previous), Some(offset)) => previous.offset.is_some_and(|o| offset <= o),
+            _ => false,
+        };
+        if out_of_order {
+            notes.push(format!(
+                "track {:02}: INDEX 01 is not after the previous track",
+                track.number
+            ));
+        }
+        if let Some(file_length) = file_lengths.get(track.file_index).copied().flatten()
+            && track.index_01.to_frame() >= file_length
+        {
+            notes.push(format!(
+                "track {:02}: INDEX 01 is at or after the end of its file ({})",
+                track.number,
+                to_msf(file_length)
+            ));
+        }
+        match track.length {
+            Some(0) => notes.push(format!("track {:02}: length is 0", track.number)),
+            Some(length) if length < MIN_TRACK_FRAMES => notes.push(format!(
+                "track {:02}: shorter than 4 seconds ({})",
+                track.number,
+                to_msf(length)
+            )),
+            None if !out_of_order && track.offset.is_some() => {
+                notes.push(format!("track {:02}: length is unknown", track.number))
+            }
+            _ => {}
+        }
+    }
+    if let Err(res) = layout.validate_limits() {
+        notes.push(res);
+    }
+    notes
+}
+
+// MM:SS:FF(分は99を超えてもよい)
+fn to_msf(frame: u32) -> String {
+    format!(
+        "{:0>2}:{:0>2}:{:0>2}",
+        frame / FRAMES_PER_SECOND / 60,
+        frame / FRAMES_PER_SECOND % 60,
+        frame % FRAMES_PER_SECOND
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Info, Pregap, Rem};
+    use super::*;
+
+    fn build_track(
+        title: &str,
+        pregap_type: PregapType,
+        pregap: u32,
+        start_at: u32,
+    ) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from(title),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: pregap_type,
+                duration: Time::from_frame(pregap)?,
+            },
+            Time::from_frame(start_at)?,
+        ))
+    }
+
+    #[test]
+    fn test_to_inspection_strings() -> Result<(), String> {
+        let mut second = build_track("Second", PregapType::Included, 150, 3000)?;
+        second.set_info(Some(Info::new(
+            None,
+            None,
+            None,
+            Some(String::from("Guest")),
+        )));
+        let cue = Cue::new(
+            String::from("album"),
+            None,
+            Some(Info::new(None, None, None, Some(String::from("Band")))),
+            Rem::default(),
+            vec![
+                File::new(
+                    String::from("/music/1.wav"),
+                    vec![
+                        build_track("First", PregapType::Silent, 150, 0)?,
+                        second,
+                        build_track("Third", PregapType::Silent, 0, 3150)?,
+                    ],
+                    FileType::Wave,
+                ),
+                File::new(
+                    String::from("/music/2.wav"),
+                    vec![build_track("Fourth", PregapType::Silent, 0, 0)?],
+                    FileType::Wave,
+                ),
+            ],
+        );
+        let file_lengths = [Some(3300), Some(6000)];
+        let layout = cue.layout(&file_lengths, None)?;
+
+        let result = cue.to_inspection_strings(&layout, &file_lengths);
+
+        assert_eq!(
+            result,
+            vec![
+                "#  File   INDEX 00  INDEX 01  Pregap             Length    Performer  Title",
+                "1  1.wav            00:00:00  silent 00:02:00    00:42:00  Band       First",
+                "2  1.wav  00:40:00  00:42:00  included 00:02:00  00:00:00  Guest      Second",
+                "3  1.wav            00:42:00                     00:02:00  Band       Third",
+                "4  2.wav            00:00:00                     01:20:00  Band       Fourth",
+                "",
+                "Total: 4 track(s) in 2 file(s), 02:06:00",
+                "Silent pregaps: 00:02:00",
+                "",
+                "! track 02: length is 0",
+                "! track 03: INDEX 01 is not after the previous track",
+                "! track 03: shorter than 4 seconds (00:02:00)",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_table_wide_characters() -> Result<(), String> {
+        let rows = [
+            [
+                "1",
+                "1.wav",
+                "",
+                "00:00:00",
+                "",
+                "00:42:00",
+                "バンド",
+                "最初",
+            ]
+            .map(String::from),
+            [
+                "2", "1.wav", "", "00:42:00", "", "00:02:00", "Band", "Second",
+            ]
+            .map(String::from),
+        ];
+
+        let result = build_table(&rows);
+
+        assert_eq!(
+            result,
+            vec![
+                "#  File   INDEX 00  INDEX 01  Pregap  Length    Performer  Title",
+                "1  1.wav            00:00:00          00:42:00  バンド     最初",
+                "2  1.wav            00:42:00          00:02:00  Band       Second",
+            ]
+        );
+        Ok(())
+    }
+}
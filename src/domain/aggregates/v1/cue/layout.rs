@@ -0,0 +1,236 @@
+use super::cue::Cue;
+use super::pregap_type::PregapType;
+use super::time::Time;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackLayout {
+    pub number: usize,
+    pub file_index: usize,
+    // ファイル先頭からの位置
+    pub index_00: Option<Time>,
+    pub index_01: Time,
+    // PREGAPで挿入される無音のフレーム数
+    pub silent_pregap: u32,
+    // ディスク上のINDEX 01の位置(リードインを含まないフレーム数)、前のファイルの長さが不明な場合はNone
+    pub offset: Option<u32>,
+    // 次のトラックのINDEX 01(最後のトラックはリードアウト)までのフレーム数
+    pub length: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscLayout {
+    pub tracks: Vec<TrackLayout>,
+    pub lead_out: Option<u32>,
+}
+
+impl Cue {
+    // file_lengthsはファイルごとのフレーム数、hidden_lengthは隠し音声のフレーム数(不明な場合はNone)
+    pub fn layout(
+        &self,
+        file_lengths: &[Option<u32>],
+        hidden_length: Option<u32>,
+    ) -> Result<DiscLayout, String> {
+        let mut tracks: Vec<TrackLayout> = Vec::new();
+        // * 隠し音声はディスクの先頭(トラック1のプリギャップ)に置かれる
+        let mut file_start: Option<u32> = match self.hidden_audio() {
+            Some(_) => hidden_length,
+            None => Some(0),
+        };
+        let mut silence: u32 = 0;
+        let mut number = self.first_track_number();
+
+        for (file_index, file) in self.files().iter().enumerate() {
+            for track in file.tracks().iter() {
+                let index_01 = match track.index_01() {
+                    Ok(res) => res,
+                    Err(res) => return Err(format!("error at track No.{}:\n{}", number, res)),
+                };
+                let silent_pregap = match track.pregap().r#type {
+                    PregapType::Silent => track.pregap().duration.to_frame(),
+                    PregapType::Included | PregapType::Appended => 0,
+                };
+                silence += silent_pregap;
+                tracks.push(TrackLayout {
+                    number,
+                    file_index,
+                    index_00: track.index_00(),
+                    index_01,
+                    silent_pregap,
+                    offset: file_start.map(|s| s + silence + index_01.to_frame()),
+                    length: None,
+                });
+                number += 1;
+            }
+            file_start = match (file_start, file_lengths.get(file_index).copied().flatten()) {
+                (Some(start), Some(length)) => Some(start + length),
+                _ => None,
+            };
+        }
+        let lead_out = file_start.map(|s| s + silence);
+
+        let next_offsets: Vec<Option<u32>> = tracks
+            .iter()
+            .skip(1)
+            .map(|t| t.offset)
+            .chain([lead_out])
+            .collect();
+        for (track, next_offset) in tracks.iter_mut().zip(next_offsets) {
+            track.length = match (track.offset, next_offset) {
+                (Some(offset), Some(next_offset)) => next_offset.checked_sub(offset),
+                _ => None,
+            };
+        }
+
+        Ok(DiscLayout { tracks, lead_out })
+    }
+}
+
+impl DiscLayout {
+    const MAX_TRACKS: usize = 99;
+    // 80分(リードアウトまで)
+    const MAX_FRAMES: u32 = 80 * 60 * 75;
+
+    // CDのトラック数と収録時間の上限を確認する
+    pub fn validate_limits(&self) -> Result<(), String> {
+        if self.tracks.len() > Self::MAX_TRACKS {
+            return Err(format!(
+                "disc has {} tracks (at most {})",
+                self.tracks.len(),
+                Self::MAX_TRACKS
+            ));
+        }
+        if let Some(lead_out) = self.lead_out
+            && lead_out > Self::MAX_FRAMES
+        {
+            return Err(format!(
+                "disc is {} frames long (at most {} frames = 80 minutes)",
+                lead_out,
+                Self::MAX_FRAMES
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, HiddenAudio, Pregap, Rem, Track};
+    use super::*;
+
+    fn build_track(
+        pregap_type: PregapType,
+        pregap: &[u8],
+        start_at: &[u8],
+    ) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: pregap_type,
+                duration: Time::from_vec(pregap)?,
+            },
+            Time::from_vec(start_at)?,
+        ))
+    }
+
+    fn build_cue() -> Result<Cue, String> {
+        let file_1 = File::new(
+            String::from("1.wav"),
+            vec![
+                build_track(PregapType::Silent, &[0, 2, 0], &[0, 0, 0])?,
+                build_track(PregapType::Included, &[0, 1, 0], &[1, 0, 0])?,
+            ],
+            FileType::Wave,
+        );
+        let file_2 = File::new(
+            String::from("2.wav"),
+            vec![build_track(PregapType::Silent, &[0, 0, 0], &[0, 0, 0])?],
+            FileType::Wave,
+        );
+        Ok(Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![file_1, file_2],
+        ))
+    }
+
+    #[test]
+    fn test_layout() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = build_cue()?;
+
+        let result = cue.layout(&[Some(3 * 60 * 75), Some(2 * 60 * 75)], None)?;
+
+        let offsets: Vec<Option<u32>> = result.tracks.iter().map(|t| t.offset).collect();
+        let lengths: Vec<Option<u32>> = result.tracks.iter().map(|t| t.length).collect();
+        assert_eq!(
+            offsets,
+            vec![Some(150), Some(150 + 61 * 75), Some(150 + 180 * 75)]
+        );
+        assert_eq!(lengths, vec![Some(61 * 75), Some(119 * 75), Some(120 * 75)]);
+        assert_eq!(result.lead_out, Some(150 + 300 * 75));
+        assert_eq!(result.tracks[1].index_00, Some(Time::from_vec(&[1, 0, 0])?));
+        assert_eq!(result.tracks[1].index_01, Time::from_vec(&[1, 1, 0])?);
+        assert_eq!(result.tracks[2].number, 3);
+        assert_eq!(result.tracks[2].file_index, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_unknown_length() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = build_cue()?;
+
+        let result = cue.layout(&[None, Some(2 * 60 * 75)], None)?;
+
+        let offsets: Vec<Option<u32>> = result.tracks.iter().map(|t| t.offset).collect();
+        let lengths: Vec<Option<u32>> = result.tracks.iter().map(|t| t.length).collect();
+        assert_eq!(offsets, vec![Some(150), Some(150 + 61 * 75), None]);
+        assert_eq!(lengths, vec![Some(61 * 75), None, None]);
+        assert_eq!(result.lead_out, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_hidden_audio() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cue = build_cue()?;
+        let mut track = build_track(PregapType::Silent, &[0, 0, 0], &[0, 0, 0])?;
+        track.set_hidden_audio(Some(HiddenAudio {
+            path: String::from("0.wav"),
+            file_type: FileType::Wave,
+        }))?;
+        cue.files_mut()[0].tracks_mut()[0] = track;
+
+        let result = cue.layout(&[Some(3 * 60 * 75), Some(2 * 60 * 75)], Some(10 * 75))?;
+        let unknown = cue.layout(&[Some(3 * 60 * 75), Some(2 * 60 * 75)], None)?;
+
+        let offsets: Vec<Option<u32>> = result.tracks.iter().map(|t| t.offset).collect();
+        assert_eq!(
+            offsets,
+            vec![Some(750), Some(750 + 61 * 75), Some(750 + 180 * 75)]
+        );
+        assert_eq!(result.lead_out, Some(750 + 300 * 75));
+        assert_eq!(unknown.tracks[0].offset, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let cue = build_cue()?;
+        let valid = cue.layout(&[Some(40 * 60 * 75), Some(39 * 60 * 75)], None)?;
+        let too_long = cue.layout(&[Some(40 * 60 * 75), Some(40 * 60 * 75)], None)?;
+
+        valid.validate_limits()?;
+        let Err(result) = too_long.validate_limits() else {
+            return Err("disc longer than 80 minutes is accepted".into());
+        };
+
+        assert_eq!(
+            result,
+            "disc is 360150 frames long (at most 360000 frames = 80 minutes)"
+        );
+        Ok(())
+    }
+}
@@ -1,17 +1,41 @@
+#[allow(clippy::module_inception)]
 pub mod cue;
 pub use cue::*;
+pub mod cd_text_language;
+pub use cd_text_language::*;
+pub mod chapter;
+pub use chapter::*;
 pub mod file;
 pub use file::*;
+pub mod header;
+pub use header::*;
+pub mod gap_layout;
+pub use gap_layout::*;
+pub mod hidden_audio;
+pub use hidden_audio::*;
+pub mod diff;
 pub mod info;
 pub use info::*;
+pub mod inspection;
+pub use inspection::*;
+pub mod layout;
+pub use layout::*;
 pub mod time;
 pub use time::*;
 pub mod track;
 pub use track::*;
+pub mod tracklist;
+pub use tracklist::*;
 pub mod pregap;
 pub use pregap::*;
+pub mod rem;
+pub use rem::*;
+pub mod tags;
+pub use tags::*;
 
 pub mod file_type;
 pub use file_type::*;
 pub mod pregap_type;
 pub use pregap_type::*;
+pub mod track_mode;
+pub use track_mode::*;
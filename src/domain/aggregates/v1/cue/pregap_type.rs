@@ -1,4 +1,7 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PregapType {
     Silent,
     Included,
+    // 前のファイルの末尾に含まれるプリギャップ(EACのnoncompliantな分割)
+    Appended,
 }
@@ -0,0 +1,127 @@
+use crate::share::util::{self, TextStyle};
+
+pub trait RemFormatter {
+    fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String>;
+}
+
+#[derive(Debug, Default)]
+pub struct Rem {
+    entries: Vec<(String, String)>,
+}
+
+impl Rem {
+    const KEY_REM: &str = "REM";
+
+    pub fn new(entries: Vec<(String, String)>) -> Rem {
+        Rem { entries }
+    }
+
+    pub fn push(&mut self, key: String, value: String) {
+        self.entries.push((key, value));
+    }
+
+    // 同じキーが既にある場合は値を置き換える
+    pub fn set(&mut self, key: String, value: String) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    pub fn entries(&self) -> &Vec<(String, String)> {
+        &self.entries
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    // REMのキーは空白や引用符を含まない1語である必要がある
+    pub fn validate_key(key: &str) -> Result<(), String> {
+        if key.is_empty() {
+            return Err(String::from("REM key must not be empty"));
+        }
+        if key.chars().any(|c| c.is_whitespace() || c == '"') {
+            return Err(format!(
+                "REM key must not contain whitespace or '\"': {}",
+                key
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl RemFormatter for Rem {
+    fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for (key, value) in self.entries.iter() {
+            let rem_string = format!("{} {} {}", Self::KEY_REM, key, value);
+            if indent {
+                lines.push(util::build_indent_string(&rem_string, style));
+            } else {
+                lines.push(rem_string);
+            }
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_cdtext_strings() {
+        let mut rem = Rem::new(vec![(String::from("DATE"), String::from("2024"))]);
+        rem.push(String::from("DISCID"), String::from("860B640B"));
+
+        let result = rem.to_cdtext_strings(false, &TextStyle::default());
+        let result_indent = rem.to_cdtext_strings(true, &TextStyle::default());
+
+        assert_eq!(result, vec!["REM DATE 2024", "REM DISCID 860B640B"]);
+        assert_eq!(
+            result_indent,
+            vec!["  REM DATE 2024", "  REM DISCID 860B640B"]
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let mut rem = Rem::new(vec![(String::from("DATE"), String::from("2024"))]);
+        rem.set(String::from("DISCID"), String::from("860B640B"));
+        rem.set(String::from("DATE"), String::from("2025"));
+
+        assert_eq!(
+            rem.entries(),
+            &vec![
+                (String::from("DATE"), String::from("2025")),
+                (String::from("DISCID"), String::from("860B640B")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contains_key() {
+        let rem = Rem::new(vec![(String::from("COMMENT"), String::from("hoge"))]);
+
+        assert!(rem.contains_key("COMMENT"));
+        assert!(!rem.contains_key("DATE"));
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert_eq!(Rem::validate_key("REPLAYGAIN_ALBUM_GAIN"), Ok(()));
+        assert_eq!(
+            Rem::validate_key(""),
+            Err(String::from("REM key must not be empty"))
+        );
+        assert_eq!(
+            Rem::validate_key("DISC ID"),
+            Err(String::from(
+                "REM key must not contain whitespace or '\"': DISC ID"
+            ))
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use super::cue::Cue;
+use crate::share::util::{self, TextStyle};
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TrackTags {
+    pub number: usize,
+    pub total: usize,
+    pub path: String,
+    pub vorbis: BTreeMap<String, String>,
+    pub id3v2: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Tags {
+    pub tracks: Vec<TrackTags>,
+}
+
+// タグの種類ごとのキー(Vorbisコメント、ID3v2.4のフレームID)
+struct TagKey {
+    vorbis: &'static str,
+    id3v2: &'static str,
+}
+
+impl Cue {
+    const TAG_TITLE: TagKey = TagKey {
+        vorbis: "TITLE",
+        id3v2: "TIT2",
+    };
+    const TAG_ALBUM: TagKey = TagKey {
+        vorbis: "ALBUM",
+        id3v2: "TALB",
+    };
+    const TAG_ARTIST: TagKey = TagKey {
+        vorbis: "ARTIST",
+        id3v2: "TPE1",
+    };
+    const TAG_ALBUM_ARTIST: TagKey = TagKey {
+        vorbis: "ALBUMARTIST",
+        id3v2: "TPE2",
+    };
+    const TAG_COMPOSER: TagKey = TagKey {
+        vorbis: "COMPOSER",
+        id3v2: "TCOM",
+    };
+    const TAG_LYRICIST: TagKey = TagKey {
+        vorbis: "LYRICIST",
+        id3v2: "TEXT",
+    };
+    // * ID3v2には編曲者専用のフレームがないためユーザー定義フレームを使う
+    const TAG_ARRANGER: TagKey = TagKey {
+        vorbis: "ARRANGER",
+        id3v2: "TXXX:ARRANGER",
+    };
+    const TAG_GENRE: TagKey = TagKey {
+        vorbis: "GENRE",
+        id3v2: "TCON",
+    };
+    const TAG_ISRC: TagKey = TagKey {
+        vorbis: "ISRC",
+        id3v2: "TSRC",
+    };
+    const TAG_VORBIS_TRACK_NUMBER: &str = "TRACKNUMBER";
+    const TAG_VORBIS_TRACK_TOTAL: &str = "TRACKTOTAL";
+    const TAG_ID3V2_TRACK: &str = "TRCK";
+
+    // トラックのinfoがない項目はアルバムのinfoを使う(空文字列で消去された項目は出力しない)
+    pub fn tags(&self) -> Tags {
+        // * 続きのディスクでは総数を最後のトラック番号とする
+        let count: usize = self.files().iter().map(|f| f.tracks().len()).sum();
+        let total = self.first_track_number() + count - 1;
+        let album_info = self.info();
+        let mut tracks: Vec<TrackTags> = Vec::new();
+
+        for file in self.files().iter() {
+            for track in file.tracks().iter() {
+                let number = self.first_track_number() + tracks.len();
+                let track_info = track.info();
+                let mut entries: Vec<(&TagKey, Option<String>)> = vec![
+                    (&Self::TAG_TITLE, Some(String::from(track.title()))),
+                    (&Self::TAG_ALBUM, Some(String::from(self.title()))),
+                    (&Self::TAG_GENRE, self.genre().cloned()),
+                    (&Self::TAG_ISRC, track.isrc().cloned()),
+                    (
+                        &Self::TAG_ALBUM_ARTIST,
+                        album_info.and_then(|i| i.performer()).cloned(),
+                    ),
+                ];
+                let inherited = [
+                    (&Self::TAG_ARTIST, track_info.and_then(|i| i.performer())),
+                    (&Self::TAG_COMPOSER, track_info.and_then(|i| i.composer())),
+                    (&Self::TAG_LYRICIST, track_info.and_then(|i| i.lyricist())),
+                    (&Self::TAG_ARRANGER, track_info.and_then(|i| i.arranger())),
+                ];
+                let album_values = [
+                    album_info.and_then(|i| i.performer()),
+                    album_info.and_then(|i| i.composer()),
+                    album_info.and_then(|i| i.lyricist()),
+                    album_info.and_then(|i| i.arranger()),
+                ];
+                for ((key, value), album_value) in inherited.into_iter().zip(album_values) {
+                    entries.push((key, value.or(album_value).cloned()));
+                }
+
+                let mut vorbis = BTreeMap::new();
+                let mut id3v2 = BTreeMap::new();
+                for (key, value) in entries.into_iter() {
+                    if let Some(value) = value
+                        && !value.is_empty()
+                    {
+                        vorbis.insert(String::from(key.vorbis), value.clone());
+                        id3v2.insert(String::from(key.id3v2), value);
+                    }
+                }
+                vorbis.insert(
+                    String::from(Self::TAG_VORBIS_TRACK_NUMBER),
+                    number.to_string(),
+                );
+                vorbis.insert(
+                    String::from(Self::TAG_VORBIS_TRACK_TOTAL),
+                    total.to_string(),
+                );
+                id3v2.insert(
+                    String::from(Self::TAG_ID3V2_TRACK),
+                    format!("{}/{}", number, total),
+                );
+
+                tracks.push(TrackTags {
+                    number,
+                    total,
+                    path: String::from(file.path()),
+                    vorbis,
+                    id3v2,
+                });
+            }
+        }
+
+        Tags { tracks }
+    }
+}
+
+impl Tags {
+    pub fn to_json_string(&self, style: &TextStyle) -> Result<String, String> {
+        let indent = util::build_indent_string("", style);
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(
+            &mut buffer,
+            PrettyFormatter::with_indent(indent.as_bytes()),
+        );
+        if let Err(res) = self.serialize(&mut serializer) {
+            return Err(format!("failed to serialize tags\n{}", res));
+        }
+        let Ok(built) = String::from_utf8(buffer) else {
+            return Err(String::from("failed to serialize tags"));
+        };
+        let mut lines: Vec<String> = built.lines().map(String::from).collect();
+        lines.push(String::new());
+
+        Ok(util::join_strings_with_line_ending(&lines, style))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Info, Pregap, PregapType, Rem, Time, Track};
+    use super::*;
+    use crate::share::util::{Indent, LineEnding};
+
+    fn build_track(title: &str, info: Option<Info>) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from(title),
+            info,
+            Rem::default(),
+            Some(String::from("JPABC2400001")),
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_frame(0)?,
+            },
+            Time::from_frame(0)?,
+        ))
+    }
+
+    fn build_cue() -> Result<Cue, String> {
+        let file = File::new(
+            String::from("a.wav"),
+            vec![
+                build_track("One", None)?,
+                build_track(
+                    "Two",
+                    Some(Info::new(
+                        Some(String::from("arr")),
+                        Some(String::new()),
+                        Some(String::from("lyr")),
+                        Some(String::from("guest")),
+                    )),
+                )?,
+            ],
+            FileType::Wave,
+        );
+        Ok(Cue::new(
+            String::from("album"),
+            Some(String::from("rock")),
+            Some(Info::new(
+                None,
+                Some(String::from("comp")),
+                None,
+                Some(String::from("band")),
+            )),
+            Rem::default(),
+            vec![file],
+        ))
+    }
+
+    #[test]
+    fn test_tags() -> Result<(), Box<dyn std::error::Error>> {
+        let result = build_cue()?.tags();
+
+        let second = &result.tracks[1];
+        assert_eq!(result.tracks.len(), 2);
+        assert_eq!(second.vorbis["TITLE"], "Two");
+        assert_eq!(second.vorbis["ARTIST"], "guest");
+        assert_eq!(second.vorbis["ALBUMARTIST"], "band");
+        assert!(!second.vorbis.contains_key("COMPOSER"));
+        assert_eq!(result.tracks[0].vorbis["COMPOSER"], "comp");
+        assert_eq!(second.vorbis["LYRICIST"], "lyr");
+        assert_eq!(second.vorbis["ARRANGER"], "arr");
+        assert_eq!(second.vorbis["TRACKNUMBER"], "2");
+        assert_eq!(second.vorbis["TRACKTOTAL"], "2");
+        assert_eq!(second.id3v2["TPE1"], "guest");
+        assert_eq!(second.id3v2["TEXT"], "lyr");
+        assert_eq!(second.id3v2["TXXX:ARRANGER"], "arr");
+        assert_eq!(second.id3v2["TSRC"], "JPABC2400001");
+        assert_eq!(second.id3v2["TRCK"], "2/2");
+        assert_eq!(result.tracks[0].vorbis["ARTIST"], "band");
+        assert!(!result.tracks[0].vorbis.contains_key("ARRANGER"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_json_string() -> Result<(), Box<dyn std::error::Error>> {
+        let mut tags = build_cue()?.tags();
+        tags.tracks.truncate(1);
+        tags.tracks[0].vorbis.retain(|k, _| k == "TITLE");
+        tags.tracks[0].id3v2.retain(|k, _| k == "TIT2");
+
+        let result = tags.to_json_string(&TextStyle::new(LineEnding::Crlf, Indent::Tabs(1)))?;
+
+        let expected = [
+            "{",
+            "\t\"tracks\": [",
+            "\t\t{",
+            "\t\t\t\"number\": 1,",
+            "\t\t\t\"total\": 2,",
+            "\t\t\t\"path\": \"a.wav\",",
+            "\t\t\t\"vorbis\": {",
+            "\t\t\t\t\"TITLE\": \"One\"",
+            "\t\t\t},",
+            "\t\t\t\"id3v2\": {",
+            "\t\t\t\t\"TIT2\": \"One\"",
+            "\t\t\t}",
+            "\t\t}",
+            "\t]",
+            "}",
+            "",
+        ];
+        assert_eq!(result, expected.join("\r\n"));
+        Ok(())
+    }
+}
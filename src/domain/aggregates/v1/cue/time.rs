@@ -1,4 +1,4 @@
-use std::ops::Add;
+use std::ops::{Add, Sub};
 
 pub trait TimeFormatter {
     fn to_msf_string(&self) -> String;
@@ -27,23 +27,46 @@ impl Time {
             return Err(format!("frame must be >=0 and <{}", Self::MAX_FRAME));
         }
         Ok(Time {
-            minute: minute,
-            second: second,
-            frame: frame,
+            minute,
+            second,
+            frame,
         })
     }
 
-    pub fn from_vec(time_vec: &Vec<u8>) -> Result<Time, String> {
+    pub fn from_vec(time_vec: &[u8]) -> Result<Time, String> {
         if time_vec.len() != 3 {
             return Err(String::from("length of time_vec must be 3"));
         }
         Self::new(time_vec[0], time_vec[1], time_vec[2])
     }
 
-    fn to_frame(&self) -> u8 {
-        self.minute * Self::MAX_SECOND * Self::MAX_FRAME
-            + self.second * Self::MAX_FRAME
-            + self.frame
+    pub fn to_vec(self) -> Vec<u8> {
+        vec![self.minute, self.second, self.frame]
+    }
+
+    pub fn from_frame(frame: u32) -> Result<Time, String> {
+        let frames_per_minute = Self::MAX_SECOND as u32 * Self::MAX_FRAME as u32;
+        let minute = frame / frames_per_minute;
+        if minute >= Self::MAX_MINUTE as u32 {
+            return Err(format!("minute must be >=0 and <{}", Self::MAX_MINUTE));
+        }
+        let second = frame % frames_per_minute / Self::MAX_FRAME as u32;
+        Self::new(
+            minute as u8,
+            second as u8,
+            (frame % Self::MAX_FRAME as u32) as u8,
+        )
+    }
+
+    pub fn to_frame(self) -> u32 {
+        self.minute as u32 * Self::MAX_SECOND as u32 * Self::MAX_FRAME as u32
+            + self.second as u32 * Self::MAX_FRAME as u32
+            + self.frame as u32
+    }
+
+    // CDの1フレームは1/75秒
+    pub fn to_sample(self, sample_rate: u32) -> u64 {
+        self.to_frame() as u64 * sample_rate as u64 / Self::MAX_FRAME as u64
     }
 }
 
@@ -81,6 +104,22 @@ impl Add for Time {
     }
 }
 
+// 減算性の定義
+impl Sub for Time {
+    type Output = Result<Time, String>;
+
+    fn sub(self, other: Self) -> Result<Time, String> {
+        let Some(frame) = self.to_frame().checked_sub(other.to_frame()) else {
+            return Err(format!(
+                "{} is earlier than {}",
+                self.to_msf_string(),
+                other.to_msf_string()
+            ));
+        };
+        Time::from_frame(frame)
+    }
+}
+
 impl TimeFormatter for Time {
     fn to_msf_string(&self) -> String {
         format!("{:0>2}:{:0>2}:{:0>2}", self.minute, self.second, self.frame)
@@ -90,6 +129,18 @@ impl TimeFormatter for Time {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    // 80分の上限(フレーム数)
+    const MAX_FRAMES: u32 = 80 * 60 * 75;
+
+    fn arb_time() -> impl Strategy<Value = Time> {
+        (0u8..80, 0u8..60, 0u8..75).prop_map(|(m, s, f)| Time {
+            minute: m,
+            second: s,
+            frame: f,
+        })
+    }
 
     #[test]
     fn test_new_ok() -> Result<(), String> {
@@ -117,7 +168,7 @@ mod tests {
 
     #[test]
     fn test_from_vec_ok() -> Result<(), String> {
-        let Ok(result_ok) = Time::from_vec(&vec![1, 2, 3]) else {
+        let Ok(result_ok) = Time::from_vec(&[1, 2, 3]) else {
             return Err("".to_string());
         };
 
@@ -127,10 +178,10 @@ mod tests {
 
     #[test]
     fn test_from_vec_err() -> Result<(), String> {
-        let Err(result_err_len) = Time::from_vec(&vec![1, 1, 1, 1]) else {
+        let Err(result_err_len) = Time::from_vec(&[1, 1, 1, 1]) else {
             return Err("".to_string());
         };
-        let Err(result_err_range) = Time::from_vec(&vec![0, 90, 0]) else {
+        let Err(result_err_range) = Time::from_vec(&[0, 90, 0]) else {
             return Err("".to_string());
         };
 
@@ -149,6 +200,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_from_frame() -> Result<(), Box<dyn std::error::Error>> {
+        let result = Time::from_frame(14 * 60 * 75 + 16 * 75 + 11)?;
+        let Err(result_err) = Time::from_frame(80 * 60 * 75) else {
+            return Err("".into());
+        };
+
+        assert_eq!(result.to_msf_string(), String::from("14:16:11"));
+        assert_eq!(result_err, String::from("minute must be >=0 and <80"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_frame() -> Result<(), Box<dyn std::error::Error>> {
+        let time = Time::new(79, 59, 74)?;
+
+        assert_eq!(time.to_frame(), 80 * 60 * 75 - 1);
+        assert!(Time::new(10, 0, 0)? > Time::new(3, 59, 74)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_sample() -> Result<(), Box<dyn std::error::Error>> {
+        let time = Time::new(0, 1, 1)?;
+
+        assert_eq!(time.to_sample(44100), 44100 + 588);
+        assert_eq!(time.to_sample(48000), 48000 + 640);
+        Ok(())
+    }
+
     #[test]
     fn test_add() -> Result<(), Box<dyn std::error::Error>> {
         let time_1 = Time::new(10, 35, 36)?;
@@ -159,4 +240,92 @@ mod tests {
         assert_eq!(result.to_msf_string(), String::from("14:16:11"));
         Ok(())
     }
+
+    #[test]
+    fn test_sub() -> Result<(), Box<dyn std::error::Error>> {
+        let time_1 = Time::new(14, 16, 11)?;
+        let time_2 = Time::new(3, 40, 50)?;
+
+        let result = (time_1 - time_2)?;
+        let Err(result_err) = time_2 - time_1 else {
+            return Err("".into());
+        };
+
+        assert_eq!(result.to_msf_string(), String::from("10:35:36"));
+        assert_eq!(
+            result_err,
+            String::from("03:40:50 is earlier than 14:16:11")
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_ordering_matches_frames(a in arb_time(), b in arb_time()) {
+            prop_assert_eq!(a.partial_cmp(&b), a.to_frame().partial_cmp(&b.to_frame()));
+            prop_assert_eq!(a == b, a.to_frame() == b.to_frame());
+        }
+
+        #[test]
+        fn test_frame_round_trip(frame in 0..MAX_FRAMES) {
+            let time = Time::from_frame(frame).map_err(TestCaseError::fail)?;
+
+            prop_assert_eq!(time.to_frame(), frame);
+        }
+
+        #[test]
+        fn test_msf_round_trip(time in arb_time()) {
+            let from_vec = Time::from_vec(&time.to_vec()).map_err(TestCaseError::fail)?;
+            let parts: Vec<u8> = time
+                .to_msf_string()
+                .split(':')
+                .map(|p| p.parse::<u8>())
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert_eq!(from_vec, time);
+            prop_assert_eq!(Time::from_vec(&parts).map_err(TestCaseError::fail)?, time);
+        }
+
+        // 上限を超える場合はパニックせずにエラーを返す
+        #[test]
+        fn test_add_matches_frames(a in arb_time(), b in arb_time()) {
+            let frame = a.to_frame() + b.to_frame();
+
+            match a + b {
+                Ok(res) => prop_assert_eq!(res.to_frame(), frame),
+                Err(_) => prop_assert!(frame >= MAX_FRAMES),
+            }
+        }
+
+        #[test]
+        fn test_add_associative(a in arb_time(), b in arb_time(), c in arb_time()) {
+            let left = (a + b).and_then(|ab| ab + c);
+            let right = (b + c).and_then(|bc| a + bc);
+
+            prop_assert_eq!(left.is_ok(), a.to_frame() + b.to_frame() + c.to_frame() < MAX_FRAMES);
+            if let (Ok(left), Ok(right)) = (left, right) {
+                prop_assert_eq!(left, right);
+            }
+        }
+
+        #[test]
+        fn test_sub_associative(a in arb_time(), b in arb_time(), c in arb_time()) {
+            // (a - b) - c == a - (b + c)
+            let left = (a - b).and_then(|ab| ab - c);
+            let right = (b + c).and_then(|bc| a - bc);
+
+            prop_assert_eq!(left.is_ok(), a.to_frame() >= b.to_frame() + c.to_frame());
+            if let (Ok(left), Ok(right)) = (left, right) {
+                prop_assert_eq!(left, right);
+            }
+        }
+
+        #[test]
+        fn test_sub_inverts_add(a in arb_time(), b in arb_time()) {
+            if let Ok(sum) = a + b {
+                prop_assert_eq!((sum - b).map_err(TestCaseError::fail)?, a);
+            }
+        }
+    }
 }
@@ -1,51 +1,251 @@
+use super::hidden_audio::HiddenAudio;
 use super::info::{Info, InfoFormatter};
 use super::pregap::Pregap;
 use super::pregap_type::PregapType;
+use super::rem::{Rem, RemFormatter};
 use super::time::{Time, TimeFormatter};
-use crate::share::util;
+use super::track_mode::TrackMode;
+use crate::share::util::{self, TextStyle};
 
 pub trait TrackFormatter {
-    fn to_cdtext_strings(&self, number: usize, indent: bool) -> Result<Vec<String>, String>;
+    fn to_cdtext_strings(
+        &self,
+        number: usize,
+        indent: bool,
+        style: &TextStyle,
+    ) -> Result<Vec<String>, String>;
+    fn hidden_audio(&self) -> Option<&HiddenAudio> {
+        None
+    }
+    // INDEX 00がトラックのファイルより前(隠し音声、前のファイルの末尾)にあるか
+    fn starts_before_file(&self) -> bool {
+        false
+    }
+    // FILEの切り替えより前に書く行(TRACKからINDEX 00まで)と後に書く行(INDEX 01以降)
+    fn to_split_cdtext_strings(
+        &self,
+        _number: usize,
+        _style: &TextStyle,
+    ) -> Result<(Vec<String>, Vec<String>), String> {
+        Err(String::from("track does not start before its file"))
+    }
+    // AUDIOFILEの開始位置(INDEX 00があればINDEX 00、なければINDEX 01)
+    fn toc_start(&self) -> Result<Time, String>;
+    fn to_toc_strings(
+        &self,
+        number: usize,
+        path: &str,
+        length: Option<Time>,
+        style: &TextStyle,
+    ) -> Result<Vec<String>, String>;
 }
 
 pub struct Track<I: InfoFormatter = Info> {
     title: String,
     info: Option<I>,
+    rem: Rem,
+    isrc: Option<String>,
+    mode: TrackMode,
     pregap: Pregap,
     start_at: Time,
+    // INDEX 02以降(ファイル先頭からの位置)
+    extra_indexes: Vec<(u8, Time)>,
+    hidden_audio: Option<HiddenAudio>,
+    // 前のファイルの末尾に含まれるプリギャップのINDEX 00(前のファイル先頭からの位置)
+    appended_index_00: Option<Time>,
 }
 
 impl<I: InfoFormatter> Track<I> {
     const KEY_TRACK: &str = "TRACK";
-    const KEY_AUDIO: &str = "AUDIO";
+    const FIRST_EXTRA_INDEX: u8 = 2;
+    const MAX_INDEX: u8 = 99;
     const KEY_TITLE: &str = "TITLE";
     const KEY_PREGAP: &str = "PREGAP";
     const KEY_INDEX: &str = "INDEX";
+    const KEY_ISRC: &str = "ISRC";
+    const KEY_TOC_CD_TEXT: &str = "CD_TEXT";
+    const KEY_TOC_LANGUAGE: &str = "LANGUAGE";
+    const KEY_TOC_AUDIOFILE: &str = "AUDIOFILE";
+    const KEY_TOC_START: &str = "START";
+    const ISRC_LENGTH: usize = 12;
 
-    pub fn new(title: String, info: Option<I>, pregap: Pregap, start_at: Time) -> Track<I> {
+    pub fn new(
+        title: String,
+        info: Option<I>,
+        rem: Rem,
+        isrc: Option<String>,
+        pregap: Pregap,
+        start_at: Time,
+    ) -> Track<I> {
         Track {
             title,
             info,
+            rem,
+            isrc,
+            mode: TrackMode::default(),
             pregap,
             start_at,
+            extra_indexes: Vec::new(),
+            hidden_audio: None,
+            appended_index_00: None,
         }
     }
 
-    fn build_strings(base_strings: Vec<String>, indent: bool) -> Vec<String> {
-        if indent {
-            let result: Vec<String> =
-                Vec::from_iter(base_strings.iter().map(|s| util::build_indent_string(s)));
-            return result;
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn info(&self) -> Option<&I> {
+        self.info.as_ref()
+    }
+
+    pub fn set_info(&mut self, info: Option<I>) {
+        self.info = info;
+    }
+
+    pub fn rem(&self) -> &Rem {
+        &self.rem
+    }
+
+    pub fn set_rem(&mut self, key: String, value: String) {
+        self.rem.set(key, value);
+    }
+
+    pub fn isrc(&self) -> Option<&String> {
+        self.isrc.as_ref()
+    }
+
+    // ISRCは国コード(英字2)、登録者コード(英数字3)、年(数字2)、番号(数字5)の12文字
+    pub fn validate_isrc(isrc: &str) -> Result<(), String> {
+        let chars: Vec<char> = isrc.chars().collect();
+        let valid = chars.len() == Self::ISRC_LENGTH
+            && chars[..2].iter().all(|c| c.is_ascii_uppercase())
+            && chars[2..5]
+                .iter()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            && chars[5..].iter().all(|c| c.is_ascii_digit());
+        if !valid {
+            return Err(format!("\"{}\" is not a valid ISRC", isrc));
         }
-        base_strings
+        Ok(())
     }
-}
 
-impl<I> TrackFormatter for Track<I>
-where
-    I: InfoFormatter,
-{
-    fn to_cdtext_strings(&self, number: usize, indent: bool) -> Result<Vec<String>, String> {
+    pub fn mode(&self) -> TrackMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: TrackMode) {
+        self.mode = mode;
+    }
+
+    pub fn extra_indexes(&self) -> &[(u8, Time)] {
+        &self.extra_indexes
+    }
+
+    // 番号は02〜99の昇順、位置はINDEX 01より後の昇順
+    pub fn set_extra_indexes(&mut self, indexes: Vec<(u8, Time)>) -> Result<(), String> {
+        let mut previous = (1, self.index_01()?);
+        for (number, time) in indexes.iter() {
+            if *number < Self::FIRST_EXTRA_INDEX || *number > Self::MAX_INDEX {
+                return Err(format!(
+                    "INDEX {:0>2} must be >={:0>2} and <={}",
+                    number,
+                    Self::FIRST_EXTRA_INDEX,
+                    Self::MAX_INDEX
+                ));
+            }
+            if *number != previous.0 + 1 {
+                return Err(format!(
+                    "INDEX {:0>2} must follow INDEX {:0>2}",
+                    number, previous.0
+                ));
+            }
+            if *time <= previous.1 {
+                return Err(format!(
+                    "INDEX {:0>2} must be after INDEX {:0>2}",
+                    number, previous.0
+                ));
+            }
+            previous = (*number, *time);
+        }
+        self.extra_indexes = indexes;
+        Ok(())
+    }
+
+    pub fn pregap(&self) -> &Pregap {
+        &self.pregap
+    }
+
+    // INDEX 02以降は変わらないため、必要に応じてset_extra_indexesで置き直す
+    pub fn set_pregap(&mut self, pregap: Pregap, start_at: Time) -> Result<(), String> {
+        if self.hidden_audio.is_some() && pregap.duration.to_frame() != 0 {
+            return Err(String::from(
+                "hidden audio cannot be combined with a pregap",
+            ));
+        }
+        self.pregap = pregap;
+        self.start_at = start_at;
+        self.appended_index_00 = None;
+        Ok(())
+    }
+
+    pub fn appended_index_00(&self) -> Option<Time> {
+        self.appended_index_00
+    }
+
+    // 前のファイルの長さが分かってから決める
+    pub fn set_appended_index_00(&mut self, index_00: Option<Time>) {
+        self.appended_index_00 = index_00;
+    }
+
+    pub fn start_at(&self) -> Time {
+        self.start_at
+    }
+
+    // ファイル内にINDEX 00が存在する場合のみ返す
+    pub fn index_00(&self) -> Option<Time> {
+        if self.pregap.duration.to_frame() == 0 {
+            return None;
+        }
+        match self.pregap.r#type {
+            PregapType::Silent | PregapType::Appended => None,
+            PregapType::Included => Some(self.start_at),
+        }
+    }
+
+    pub fn index_01(&self) -> Result<Time, String> {
+        match self.index_00() {
+            None => Ok(self.start_at),
+            Some(index_00) => match index_00 + self.pregap.duration {
+                Ok(res) => Ok(res),
+                Err(_) => Err(String::from(
+                    "(start_at + pregap) exceeds range of Time object",
+                )),
+            },
+        }
+    }
+
+    // 隠し音声がトラック1のプリギャップになるため、PREGAPとは併用できず、
+    // INDEX 01はトラックのファイルの先頭にする
+    pub fn set_hidden_audio(&mut self, hidden_audio: Option<HiddenAudio>) -> Result<(), String> {
+        if hidden_audio.is_some() {
+            if self.pregap.duration.to_frame() != 0 {
+                return Err(String::from(
+                    "hidden audio cannot be combined with a pregap",
+                ));
+            }
+            if self.start_at.to_frame() != 0 {
+                return Err(String::from(
+                    "hidden audio requires the track to start at 00:00:00 of its file",
+                ));
+            }
+        }
+        self.hidden_audio = hidden_audio;
+        Ok(())
+    }
+
+    // TRACKからISRCまで
+    fn build_header_strings(&self, number: usize, style: &TextStyle) -> Vec<String> {
         let mut lines = Vec::new();
 
         // TRACK
@@ -53,29 +253,62 @@ where
             "{} {:0>2} {}",
             Self::KEY_TRACK,
             number,
-            Self::KEY_AUDIO
+            self.mode.keyword()
         ));
         // * これ以降はインデント
         // TITLE
         let built_title = format!("{} \"{}\"", Self::KEY_TITLE, self.title);
-        lines.push(util::build_indent_string(&built_title));
+        lines.push(util::build_indent_string(&built_title, style));
         // info
-        match &self.info {
-            Some(info) => {
-                lines.extend(info.to_cdtext_strings(true));
-            }
-            None => {}
+        if let Some(info) = &self.info {
+            lines.extend(info.to_cdtext_strings(true, style));
         }
+        // rem
+        lines.extend(self.rem.to_cdtext_strings(true, style));
+        // isrc
+        if let Some(isrc) = &self.isrc {
+            let built_isrc = format!("{} {}", Self::KEY_ISRC, isrc);
+            lines.push(util::build_indent_string(&built_isrc, style));
+        }
+
+        lines
+    }
+
+    // 隠し音声のファイルの先頭、または前のファイルの末尾のプリギャップの位置
+    fn build_before_file_index_string(&self, style: &TextStyle) -> Result<String, String> {
+        let index_00_time = match (&self.hidden_audio, self.appended_index_00) {
+            (Some(_), _) => Time::from_frame(0)?,
+            (None, Some(index_00)) => index_00,
+            (None, None) => {
+                return Err(String::from(
+                    "position of the pregap in the previous file is unknown",
+                ));
+            }
+        };
+        let index_00 = format!("{} 00 {}", Self::KEY_INDEX, index_00_time.to_msf_string());
+        Ok(util::build_indent_string(&index_00, style))
+    }
+
+    // PREGAPとINDEX
+    fn build_index_strings(&self, style: &TextStyle) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+
         // pregap
-        let Ok(time_0) = Time::from_vec(&vec![0, 0, 0]) else {
+        let Ok(time_0) = Time::from_vec(&[0, 0, 0]) else {
             return Err(String::from("Time is not buildable"));
         };
         if self.pregap.duration == time_0 {
             let index_01 = format!("{} 01 {}", Self::KEY_INDEX, self.start_at.to_msf_string());
-            lines.push(util::build_indent_string(&index_01));
-            return Ok(Self::build_strings(lines, indent));
+            lines.push(util::build_indent_string(&index_01, style));
+            lines.extend(self.build_extra_index_strings(style));
+            return Ok(lines);
         }
         match self.pregap.r#type {
+            // * INDEX 00は前のファイルに書く
+            PregapType::Appended => {
+                let index_01 = format!("{} 01 {}", Self::KEY_INDEX, self.start_at.to_msf_string());
+                lines.push(util::build_indent_string(&index_01, style));
+            }
             PregapType::Silent => {
                 // PREGAP pregap
                 let pregap_str = format!(
@@ -83,47 +316,223 @@ where
                     Self::KEY_PREGAP,
                     self.pregap.duration.to_msf_string()
                 );
-                lines.push(util::build_indent_string(&pregap_str));
+                lines.push(util::build_indent_string(&pregap_str, style));
                 // INDEX 01 start_at
                 let index_01 = format!("{} 01 {}", Self::KEY_INDEX, self.start_at.to_msf_string());
-                lines.push(util::build_indent_string(&index_01));
+                lines.push(util::build_indent_string(&index_01, style));
             }
             PregapType::Included => {
                 // INDEX 00 start_at
                 let index_00 = format!("{} 00 {}", Self::KEY_INDEX, self.start_at.to_msf_string());
-                lines.push(util::build_indent_string(&index_00));
+                lines.push(util::build_indent_string(&index_00, style));
                 // INDEX 01 start_at + pregap
-                let Ok(index_01_time) = self.start_at + self.pregap.duration else {
-                    return Err(String::from(
-                        "(start_at + pregap) exceeds range of Time object",
-                    ));
-                };
+                let index_01_time = self.index_01()?;
                 let index_01 = format!("{} 01 {}", Self::KEY_INDEX, index_01_time.to_msf_string());
-                lines.push(util::build_indent_string(&index_01));
+                lines.push(util::build_indent_string(&index_01, style));
             }
         };
+        lines.extend(self.build_extra_index_strings(style));
+
+        Ok(lines)
+    }
+
+    fn build_extra_index_strings(&self, style: &TextStyle) -> Vec<String> {
+        self.extra_indexes
+            .iter()
+            .map(|(number, time)| {
+                let index = format!(
+                    "{} {:0>2} {}",
+                    Self::KEY_INDEX,
+                    number,
+                    time.to_msf_string()
+                );
+                util::build_indent_string(&index, style)
+            })
+            .collect()
+    }
+
+    fn build_strings(base_strings: Vec<String>, indent: bool, style: &TextStyle) -> Vec<String> {
+        if indent {
+            let result: Vec<String> = Vec::from_iter(
+                base_strings
+                    .iter()
+                    .map(|s| util::build_indent_string(s, style)),
+            );
+            return result;
+        }
+        base_strings
+    }
+}
+
+impl<I> TrackFormatter for Track<I>
+where
+    I: InfoFormatter,
+{
+    fn to_cdtext_strings(
+        &self,
+        number: usize,
+        indent: bool,
+        style: &TextStyle,
+    ) -> Result<Vec<String>, String> {
+        let mut lines = self.build_header_strings(number, style);
+        if self.starts_before_file() {
+            lines.push(self.build_before_file_index_string(style)?);
+        }
+        lines.extend(self.build_index_strings(style)?);
+
+        Ok(Self::build_strings(lines, indent, style))
+    }
+
+    fn hidden_audio(&self) -> Option<&HiddenAudio> {
+        self.hidden_audio.as_ref()
+    }
+
+    fn starts_before_file(&self) -> bool {
+        self.hidden_audio.is_some()
+            || (self.pregap.duration.to_frame() != 0
+                && matches!(self.pregap.r#type, PregapType::Appended))
+    }
+
+    fn to_split_cdtext_strings(
+        &self,
+        number: usize,
+        style: &TextStyle,
+    ) -> Result<(Vec<String>, Vec<String>), String> {
+        if !self.starts_before_file() {
+            return Err(String::from("track does not start before its file"));
+        }
+        let mut before_lines = self.build_header_strings(number, style);
+        before_lines.push(self.build_before_file_index_string(style)?);
+        let lines = self.build_index_strings(style)?;
+
+        Ok((
+            Self::build_strings(before_lines, true, style),
+            Self::build_strings(lines, true, style),
+        ))
+    }
+
+    fn toc_start(&self) -> Result<Time, String> {
+        match self.index_00() {
+            Some(index_00) => Ok(index_00),
+            None => self.index_01(),
+        }
+    }
+
+    fn to_toc_strings(
+        &self,
+        number: usize,
+        path: &str,
+        length: Option<Time>,
+        style: &TextStyle,
+    ) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+
+        lines.push(format!("// Track {:0>2}", number));
+        lines.push(format!("{} {}", Self::KEY_TRACK, self.mode.toc_keyword()));
+        // isrc
+        if let Some(isrc) = &self.isrc {
+            lines.push(format!("{} \"{}\"", Self::KEY_ISRC, isrc));
+        }
+        // cd-text
+        let mut cd_text = vec![format!("{} \"{}\"", Self::KEY_TITLE, self.title)];
+        if let Some(info) = &self.info {
+            cd_text.extend(info.to_toc_strings());
+        }
+        lines.push(format!("{} {{", Self::KEY_TOC_CD_TEXT));
+        lines.push(util::build_indent_string(
+            &format!("{} 0 {{", Self::KEY_TOC_LANGUAGE),
+            style,
+        ));
+        for line in cd_text.iter() {
+            let indented = util::build_indent_string(line, style);
+            lines.push(util::build_indent_string(&indented, style));
+        }
+        lines.push(util::build_indent_string("}", style));
+        lines.push(String::from("}"));
+        // pregap
+        let start = self.toc_start()?;
+        let has_pregap = self.pregap.duration.to_frame() != 0;
+        if has_pregap && matches!(self.pregap.r#type, PregapType::Appended) {
+            return Err(String::from(
+                "pregap in the previous file is not supported in TOC output",
+            ));
+        }
+        if has_pregap && matches!(self.pregap.r#type, PregapType::Silent) {
+            lines.push(format!(
+                "{} {}",
+                Self::KEY_PREGAP,
+                self.pregap.duration.to_msf_string()
+            ));
+        }
+        // * 隠し音声はファイル全体をトラックの先頭に置き、STARTでその長さをプリギャップにする
+        if let Some(hidden_audio) = &self.hidden_audio {
+            lines.push(format!(
+                "{} \"{}\" {}",
+                Self::KEY_TOC_AUDIOFILE,
+                hidden_audio.path,
+                Time::from_frame(0)?.to_msf_string()
+            ));
+            lines.push(String::from(Self::KEY_TOC_START));
+        }
+        // audiofile
+        let audiofile = match length {
+            Some(length) => format!(
+                "{} \"{}\" {} {}",
+                Self::KEY_TOC_AUDIOFILE,
+                path,
+                start.to_msf_string(),
+                length.to_msf_string()
+            ),
+            None => format!(
+                "{} \"{}\" {}",
+                Self::KEY_TOC_AUDIOFILE,
+                path,
+                start.to_msf_string()
+            ),
+        };
+        lines.push(audiofile);
+        // * ファイルに含まれるプリギャップはSTARTでトラック先頭からの長さを指定
+        if has_pregap && matches!(self.pregap.r#type, PregapType::Included) {
+            lines.push(format!(
+                "{} {}",
+                Self::KEY_TOC_START,
+                self.pregap.duration.to_msf_string()
+            ));
+        }
+        // * INDEXはINDEX 01からの相対位置
+        let index_01 = self.index_01()?;
+        for (_, time) in self.extra_indexes.iter() {
+            let relative = (*time - index_01)?;
+            lines.push(format!("{} {}", Self::KEY_INDEX, relative.to_msf_string()));
+        }
 
-        Ok(Self::build_strings(lines, indent))
+        Ok(lines)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::share::util::{Indent, LineEnding};
+    use proptest::prelude::*;
 
     const TEST_INFO: [&str; 2] = ["info1", "info2"];
 
     struct MockInfo {}
     impl InfoFormatter for MockInfo {
-        fn to_cdtext_strings(&self, indent: bool) -> Vec<String> {
+        fn to_cdtext_strings(&self, indent: bool, style: &TextStyle) -> Vec<String> {
             let mut result: Vec<String> = Vec::new();
             if indent {
-                result.extend(TEST_INFO.map(|s| format!("  {s}")));
+                result.extend(TEST_INFO.map(|s| util::build_indent_string(s, style)));
                 return result;
             }
             result.extend(TEST_INFO.iter().map(|s| String::from(*s)));
             result
         }
+
+        fn to_toc_strings(&self) -> Vec<String> {
+            TEST_INFO.iter().map(|s| String::from(*s)).collect()
+        }
     }
 
     #[test]
@@ -132,17 +541,23 @@ mod tests {
         let info = Some(MockInfo {});
         let pregap = Pregap {
             r#type: PregapType::Included,
-            duration: Time::from_vec(&vec![0, 2, 15])?,
+            duration: Time::from_vec(&[0, 2, 15])?,
         };
-        let start_at = Time::from_vec(&vec![0, 0, 0])?;
+        let start_at = Time::from_vec(&[0, 0, 0])?;
         let test_track: Track<MockInfo> = Track {
             title,
             info,
+            rem: Rem::default(),
+            isrc: None,
+            mode: TrackMode::Audio,
             pregap,
             start_at,
+            extra_indexes: Vec::new(),
+            hidden_audio: None,
+            appended_index_00: None,
         };
 
-        let result = test_track.to_cdtext_strings(1, false)?;
+        let result = test_track.to_cdtext_strings(1, false, &TextStyle::default())?;
 
         let expected = vec![
             "TRACK 01 AUDIO",
@@ -162,17 +577,23 @@ mod tests {
         let info = Some(MockInfo {});
         let pregap = Pregap {
             r#type: PregapType::Silent,
-            duration: Time::from_vec(&vec![0, 3, 49])?,
+            duration: Time::from_vec(&[0, 3, 49])?,
         };
-        let start_at = Time::from_vec(&vec![0, 0, 0])?;
+        let start_at = Time::from_vec(&[0, 0, 0])?;
         let test_track: Track<MockInfo> = Track {
             title,
             info,
+            rem: Rem::default(),
+            isrc: None,
+            mode: TrackMode::Audio,
             pregap,
             start_at,
+            extra_indexes: Vec::new(),
+            hidden_audio: None,
+            appended_index_00: None,
         };
 
-        let result = test_track.to_cdtext_strings(8, true)?;
+        let result = test_track.to_cdtext_strings(8, true, &TextStyle::default())?;
 
         let expected = vec![
             "  TRACK 08 AUDIO",
@@ -185,4 +606,419 @@ mod tests {
         assert_eq!(result, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_index() -> Result<(), Box<dyn std::error::Error>> {
+        let start_at = Time::from_vec(&[1, 0, 0])?;
+        let included: Track<MockInfo> = Track::new(
+            String::new(),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Included,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            start_at,
+        );
+        let silent: Track<MockInfo> = Track::new(
+            String::new(),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            start_at,
+        );
+        let overflow: Track<MockInfo> = Track::new(
+            String::new(),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Included,
+                duration: Time::from_vec(&[1, 0, 0])?,
+            },
+            Time::from_vec(&[79, 30, 0])?,
+        );
+
+        assert_eq!(included.index_00(), Some(start_at));
+        assert_eq!(included.index_01()?, Time::from_vec(&[1, 2, 0])?);
+        assert_eq!(silent.index_00(), None);
+        assert_eq!(silent.index_01()?, start_at);
+        assert_eq!(
+            overflow.index_01(),
+            Err(String::from(
+                "(start_at + pregap) exceeds range of Time object"
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_strings_tabs() -> Result<(), Box<dyn std::error::Error>> {
+        let title = String::from("title");
+        let pregap = Pregap {
+            r#type: PregapType::Included,
+            duration: Time::from_vec(&[0, 2, 0])?,
+        };
+        let start_at = Time::from_vec(&[1, 0, 0])?;
+        let test_track: Track<MockInfo> = Track {
+            title,
+            info: Some(MockInfo {}),
+            rem: Rem::default(),
+            isrc: None,
+            mode: TrackMode::Audio,
+            pregap,
+            start_at,
+            extra_indexes: Vec::new(),
+            hidden_audio: None,
+            appended_index_00: None,
+        };
+        let style = TextStyle::new(LineEnding::Crlf, Indent::Tabs(1));
+
+        let result = test_track.to_cdtext_strings(2, true, &style)?;
+
+        let expected = vec![
+            "\tTRACK 02 AUDIO",
+            "\t\tTITLE \"title\"",
+            "\t\tinfo1",
+            "\t\tinfo2",
+            "\t\tINDEX 00 01:00:00",
+            "\t\tINDEX 01 01:02:00",
+        ];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_strings_spaces_width() -> Result<(), Box<dyn std::error::Error>> {
+        let title = String::from("title");
+        let pregap = Pregap {
+            r#type: PregapType::Silent,
+            duration: Time::from_vec(&[0, 0, 0])?,
+        };
+        let start_at = Time::from_vec(&[0, 0, 0])?;
+        let test_track: Track<MockInfo> = Track {
+            title,
+            info: None,
+            rem: Rem::new(vec![(String::from("DATE"), String::from("2024"))]),
+            isrc: Some(String::from("JPABC2400001")),
+            mode: TrackMode::Audio,
+            pregap,
+            start_at,
+            extra_indexes: Vec::new(),
+            hidden_audio: None,
+            appended_index_00: None,
+        };
+        let style = TextStyle::new(LineEnding::Lf, Indent::Spaces(4));
+
+        let result = test_track.to_cdtext_strings(1, false, &style)?;
+
+        let expected = vec![
+            "TRACK 01 AUDIO",
+            "    TITLE \"title\"",
+            "    REM DATE 2024",
+            "    ISRC JPABC2400001",
+            "    INDEX 01 00:00:00",
+        ];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_isrc() {
+        assert_eq!(Track::<MockInfo>::validate_isrc("JPABC2400001"), Ok(()));
+        assert_eq!(Track::<MockInfo>::validate_isrc("US1232400001"), Ok(()));
+        assert_eq!(
+            Track::<MockInfo>::validate_isrc("JP-ABC-24-00001"),
+            Err(String::from("\"JP-ABC-24-00001\" is not a valid ISRC"))
+        );
+        assert_eq!(
+            Track::<MockInfo>::validate_isrc("jpabc2400001"),
+            Err(String::from("\"jpabc2400001\" is not a valid ISRC"))
+        );
+    }
+
+    #[test]
+    fn test_to_toc_strings_pregap_included() -> Result<(), Box<dyn std::error::Error>> {
+        let test_track: Track<MockInfo> = Track::new(
+            String::from("title"),
+            Some(MockInfo {}),
+            Rem::default(),
+            Some(String::from("JPABC2400001")),
+            Pregap {
+                r#type: PregapType::Included,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            Time::from_vec(&[1, 0, 0])?,
+        );
+
+        let result = test_track.to_toc_strings(
+            2,
+            "a.wav",
+            Some(Time::from_vec(&[3, 0, 0])?),
+            &TextStyle::default(),
+        )?;
+
+        let expected = vec![
+            "// Track 02",
+            "TRACK AUDIO",
+            "ISRC \"JPABC2400001\"",
+            "CD_TEXT {",
+            "  LANGUAGE 0 {",
+            "    TITLE \"title\"",
+            "    info1",
+            "    info2",
+            "  }",
+            "}",
+            "AUDIOFILE \"a.wav\" 01:00:00 03:00:00",
+            "START 00:02:00",
+        ];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_toc_strings_pregap_silent() -> Result<(), Box<dyn std::error::Error>> {
+        let test_track: Track<MockInfo> = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            Time::from_vec(&[0, 0, 0])?,
+        );
+
+        let result = test_track.to_toc_strings(1, "a.wav", None, &TextStyle::default())?;
+
+        let expected = vec![
+            "// Track 01",
+            "TRACK AUDIO",
+            "CD_TEXT {",
+            "  LANGUAGE 0 {",
+            "    TITLE \"title\"",
+            "  }",
+            "}",
+            "PREGAP 00:02:00",
+            "AUDIOFILE \"a.wav\" 00:00:00",
+        ];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_cdtext_strings_extra_indexes() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_track: Track<MockInfo> = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Included,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            Time::from_vec(&[1, 0, 0])?,
+        );
+        test_track.set_mode(TrackMode::Mode1_2352);
+        test_track.set_extra_indexes(vec![
+            (2, Time::from_vec(&[1, 30, 0])?),
+            (3, Time::from_vec(&[2, 0, 0])?),
+        ])?;
+
+        let cue = test_track.to_cdtext_strings(1, false, &TextStyle::default())?;
+        let toc = test_track.to_toc_strings(1, "a.bin", None, &TextStyle::default())?;
+
+        assert_eq!(
+            cue,
+            vec![
+                "TRACK 01 MODE1/2352",
+                "  TITLE \"title\"",
+                "  INDEX 00 01:00:00",
+                "  INDEX 01 01:02:00",
+                "  INDEX 02 01:30:00",
+                "  INDEX 03 02:00:00",
+            ]
+        );
+        assert_eq!(toc[1], "TRACK MODE1_RAW");
+        assert_eq!(toc[toc.len() - 2..], ["INDEX 00:28:00", "INDEX 00:58:00"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_extra_indexes_err() -> Result<(), Box<dyn std::error::Error>> {
+        let mut test_track: Track<MockInfo> = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 0, 0])?,
+            },
+            Time::from_vec(&[1, 0, 0])?,
+        );
+
+        let skipped = test_track.set_extra_indexes(vec![(3, Time::from_vec(&[2, 0, 0])?)]);
+        let before = test_track.set_extra_indexes(vec![(2, Time::from_vec(&[0, 30, 0])?)]);
+        let out_of_range = test_track.set_extra_indexes(vec![(1, Time::from_vec(&[2, 0, 0])?)]);
+
+        assert_eq!(skipped, Err(String::from("INDEX 03 must follow INDEX 01")));
+        assert_eq!(before, Err(String::from("INDEX 02 must be after INDEX 01")));
+        assert_eq!(
+            out_of_range,
+            Err(String::from("INDEX 01 must be >=02 and <=99"))
+        );
+        assert!(test_track.extra_indexes().is_empty());
+        Ok(())
+    }
+
+    fn build_hidden_track(start_at: &[u8]) -> Result<Track<MockInfo>, Box<dyn std::error::Error>> {
+        let mut test_track: Track<MockInfo> = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Silent,
+                duration: Time::from_vec(&[0, 0, 0])?,
+            },
+            Time::from_vec(start_at)?,
+        );
+        test_track.set_hidden_audio(Some(HiddenAudio {
+            path: String::from("htoa.wav"),
+            file_type: super::super::FileType::Wave,
+        }))?;
+        Ok(test_track)
+    }
+
+    #[test]
+    fn test_to_split_cdtext_strings_hidden_audio() -> Result<(), Box<dyn std::error::Error>> {
+        let test_track = build_hidden_track(&[0, 0, 0])?;
+
+        let (hidden, result) = test_track.to_split_cdtext_strings(1, &TextStyle::default())?;
+
+        assert_eq!(
+            hidden,
+            vec![
+                "  TRACK 01 AUDIO",
+                "    TITLE \"title\"",
+                "    INDEX 00 00:00:00"
+            ]
+        );
+        assert_eq!(result, vec!["    INDEX 01 00:00:00"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_toc_strings_hidden_audio() -> Result<(), Box<dyn std::error::Error>> {
+        let test_track = build_hidden_track(&[0, 0, 0])?;
+
+        let result = test_track.to_toc_strings(
+            1,
+            "a.wav",
+            Some(Time::from_vec(&[3, 0, 0])?),
+            &TextStyle::default(),
+        )?;
+
+        assert_eq!(
+            result[result.len() - 3..],
+            [
+                "AUDIOFILE \"htoa.wav\" 00:00:00",
+                "START",
+                "AUDIOFILE \"a.wav\" 00:00:00 03:00:00",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_hidden_audio_err() -> Result<(), Box<dyn std::error::Error>> {
+        let hidden_audio = HiddenAudio {
+            path: String::from("htoa.wav"),
+            file_type: super::super::FileType::Wave,
+        };
+        let mut with_pregap: Track<MockInfo> = Track::new(
+            String::from("title"),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: PregapType::Included,
+                duration: Time::from_vec(&[0, 2, 0])?,
+            },
+            Time::from_vec(&[0, 0, 0])?,
+        );
+
+        let pregap = with_pregap.set_hidden_audio(Some(hidden_audio.clone()));
+        let Err(not_at_start) = build_hidden_track(&[0, 1, 0]) else {
+            return Err("hidden audio on a track not at 00:00:00 is accepted".into());
+        };
+
+        assert_eq!(
+            pregap,
+            Err(String::from(
+                "hidden audio cannot be combined with a pregap"
+            ))
+        );
+        assert_eq!(
+            not_at_start.to_string(),
+            "hidden audio requires the track to start at 00:00:00 of its file"
+        );
+        assert_eq!(with_pregap.hidden_audio(), None);
+        Ok(())
+    }
+
+    // 80分の上限(フレーム数)
+    const MAX_FRAMES: u32 = 80 * 60 * 75;
+
+    fn arb_pregap_type() -> impl Strategy<Value = PregapType> {
+        prop_oneof![
+            Just(PregapType::Silent),
+            Just(PregapType::Included),
+            Just(PregapType::Appended),
+        ]
+    }
+
+    proptest! {
+        // INDEX 01はINDEX 00より前にならず、80分を超える場合はパニックせずにエラーを返す
+        #[test]
+        fn test_index_01_after_index_00(
+            pregap_type in arb_pregap_type(),
+            duration in 0..MAX_FRAMES,
+            start_at in 0..MAX_FRAMES,
+        ) {
+            let pregap = Pregap {
+                r#type: pregap_type,
+                duration: Time::from_frame(duration).map_err(TestCaseError::fail)?,
+            };
+            let track: Track = Track::new(
+                String::from("title"),
+                None,
+                Rem::default(),
+                None,
+                pregap,
+                Time::from_frame(start_at).map_err(TestCaseError::fail)?,
+            );
+
+            let included = pregap_type == PregapType::Included && duration != 0;
+            match track.index_01() {
+                Ok(index_01) => {
+                    prop_assert!(index_01.to_frame() < MAX_FRAMES);
+                    prop_assert!(track.index_00().is_none_or(|i| i <= index_01));
+                    prop_assert_eq!(
+                        index_01.to_frame(),
+                        if included { start_at + duration } else { start_at }
+                    );
+                }
+                Err(_) => prop_assert!(included && start_at + duration >= MAX_FRAMES),
+            }
+            prop_assert_eq!(track.index_00().is_some(), included);
+        }
+    }
 }
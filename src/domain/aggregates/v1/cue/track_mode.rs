@@ -0,0 +1,54 @@
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TrackMode {
+    #[default]
+    Audio,
+    Cdg,
+    Mode1_2048,
+    Mode1_2352,
+    Mode2_2336,
+    Mode2_2352,
+    Cdi2336,
+    Cdi2352,
+}
+
+impl TrackMode {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            TrackMode::Audio => "AUDIO",
+            TrackMode::Cdg => "CDG",
+            TrackMode::Mode1_2048 => "MODE1/2048",
+            TrackMode::Mode1_2352 => "MODE1/2352",
+            TrackMode::Mode2_2336 => "MODE2/2336",
+            TrackMode::Mode2_2352 => "MODE2/2352",
+            TrackMode::Cdi2336 => "CDI/2336",
+            TrackMode::Cdi2352 => "CDI/2352",
+        }
+    }
+
+    // cdrdaoのトラックモード(CD-iはMODE2として扱う)
+    pub fn toc_keyword(&self) -> &'static str {
+        match self {
+            TrackMode::Audio => "AUDIO",
+            TrackMode::Cdg => "AUDIO",
+            TrackMode::Mode1_2048 => "MODE1",
+            TrackMode::Mode1_2352 => "MODE1_RAW",
+            TrackMode::Mode2_2336 | TrackMode::Cdi2336 => "MODE2",
+            TrackMode::Mode2_2352 | TrackMode::Cdi2352 => "MODE2_RAW",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<TrackMode> {
+        [
+            TrackMode::Audio,
+            TrackMode::Cdg,
+            TrackMode::Mode1_2048,
+            TrackMode::Mode1_2352,
+            TrackMode::Mode2_2336,
+            TrackMode::Mode2_2352,
+            TrackMode::Cdi2336,
+            TrackMode::Cdi2352,
+        ]
+        .into_iter()
+        .find(|m| m.keyword() == keyword)
+    }
+}
@@ -0,0 +1,477 @@
+use super::chapter::Chapters;
+use super::cue::Cue;
+use super::header::Header;
+use crate::share::util::{self, TextStyle};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delimiter {
+    Comma,
+    Tab,
+}
+
+// トラックリストの1行(長さが分からない場合はNone)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracklistEntry {
+    pub number: usize,
+    pub title: String,
+    pub performer: Option<String>,
+    pub composer: Option<String>,
+    pub path: String,
+    // ファイル先頭からのフレーム数
+    pub file_start: u32,
+    // 全ファイルを連結したストリーム先頭からのフレーム数
+    pub start: Option<u32>,
+    pub length: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tracklist {
+    pub title: String,
+    pub performer: Option<String>,
+    pub genre: Option<String>,
+    pub entries: Vec<TracklistEntry>,
+}
+
+impl Cue {
+    // トラックの長さは同じファイルの次のトラックのINDEX 01まで
+    // * ファイルの長さが必要なのはファイルの最後のトラックの長さと、後のファイルの開始位置のみ
+    // * file_lengthsはファイルごとのフレーム数(不明な場合はNone)
+    pub fn tracklist(&self, file_lengths: &[Option<u32>]) -> Result<Tracklist, String> {
+        let mut entries: Vec<TracklistEntry> = Vec::new();
+        let mut file_start: Option<u32> = Some(0);
+        let mut number = self.first_track_number();
+
+        for (file_index, file) in self.files().iter().enumerate() {
+            // * トラックのないファイルは長さを調べないため、開始位置の計算から除く
+            if file.tracks().is_empty() {
+                continue;
+            }
+            let file_length = file_lengths.get(file_index).copied().flatten();
+            let mut index_01s: Vec<u32> = Vec::new();
+            for (track_index, track) in file.tracks().iter().enumerate() {
+                let number = number + track_index;
+                let index_01 = match track.index_01() {
+                    Ok(res) => res.to_frame(),
+                    Err(res) => return Err(format!("error at track No.{}:\n{}", number, res)),
+                };
+                if index_01s
+                    .last()
+                    .is_some_and(|previous| index_01 < *previous)
+                {
+                    return Err(format!(
+                        "INDEX 01 of track No.{} is before the previous track",
+                        number
+                    ));
+                }
+                if file_length.is_some_and(|length| index_01 > length) {
+                    return Err(format!(
+                        "INDEX 01 of track No.{} exceeds the length of {}",
+                        number,
+                        file.path()
+                    ));
+                }
+                index_01s.push(index_01);
+            }
+
+            let ends = index_01s
+                .iter()
+                .skip(1)
+                .map(|i| Some(*i))
+                .chain([file_length]);
+            for ((track, index_01), end) in file.tracks().iter().zip(index_01s.iter()).zip(ends) {
+                entries.push(TracklistEntry {
+                    number,
+                    title: String::from(track.title()),
+                    performer: Self::non_empty(track.info().and_then(|i| i.performer())),
+                    composer: Self::non_empty(track.info().and_then(|i| i.composer())),
+                    path: String::from(file.path()),
+                    file_start: *index_01,
+                    start: file_start.map(|s| s + index_01),
+                    length: end.map(|e| e - index_01),
+                });
+                number += 1;
+            }
+            file_start = file_start.zip(file_length).map(|(s, l)| s + l);
+        }
+
+        Ok(Tracklist {
+            title: String::from(self.title()),
+            performer: self.info().and_then(|i| i.performer()).cloned(),
+            genre: self.genre().cloned(),
+            entries,
+        })
+    }
+}
+
+impl Tracklist {
+    const TRACKLIST_COLUMNS: [&str; 6] = [
+        "number",
+        "title",
+        "performer",
+        "composer",
+        "start",
+        "length",
+    ];
+    const M3U_HEADER: &str = "#EXTM3U";
+
+    // CSV(RFC 4180)またはTSV、時間はMM:SS:FF
+    pub fn to_tracklist_string(&self, delimiter: Delimiter, style: &TextStyle) -> String {
+        let separator = match delimiter {
+            Delimiter::Comma => ",",
+            Delimiter::Tab => "\t",
+        };
+        let mut lines: Vec<String> = vec![Self::TRACKLIST_COLUMNS.join(separator)];
+
+        for entry in self.entries.iter() {
+            let fields = [
+                entry.number.to_string(),
+                entry.title.clone(),
+                entry.performer.clone().unwrap_or_default(),
+                entry.composer.clone().unwrap_or_default(),
+                entry.start.map(Self::to_msf).unwrap_or_default(),
+                entry.length.map(Self::to_msf).unwrap_or_default(),
+            ];
+            let escaped: Vec<String> = fields
+                .iter()
+                .map(|f| Self::escape_field(f, delimiter))
+                .collect();
+            lines.push(escaped.join(separator));
+        }
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    pub fn to_markdown_string(&self, style: &TextStyle) -> String {
+        let mut lines: Vec<String> = vec![format!("# {}", Self::escape_markdown(&self.title))];
+
+        let mut details: Vec<String> = Vec::new();
+        if let Some(performer) = &self.performer {
+            details.push(format!("- Performer: {}", Self::escape_markdown(performer)));
+        }
+        if let Some(genre) = &self.genre {
+            details.push(format!("- Genre: {}", Self::escape_markdown(genre)));
+        }
+        if !details.is_empty() {
+            lines.push(String::new());
+            lines.extend(details);
+        }
+        lines.push(String::new());
+        lines.push(String::from(
+            "| # | Title | Performer | Composer | Length |",
+        ));
+        lines.push(String::from("|--:|:--|:--|:--|--:|"));
+        for entry in self.entries.iter() {
+            lines.push(format!(
+                "| {} | {} | {} | {} | {} |",
+                entry.number,
+                Self::escape_markdown(&entry.title),
+                Self::escape_markdown(entry.performer.as_deref().unwrap_or_default()),
+                Self::escape_markdown(entry.composer.as_deref().unwrap_or_default()),
+                entry.length.map(Self::to_minutes).unwrap_or_default()
+            ));
+        }
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    // 拡張M3U、ファイル内の範囲はVLCのオプションで指定
+    pub fn to_m3u8_string(&self, header: &Header, style: &TextStyle) -> String {
+        let mut lines: Vec<String> = vec![String::from(Self::M3U_HEADER)];
+
+        if let Some(comment) = &header.comment {
+            lines.push(format!("# {}", comment));
+        }
+        lines.push(format!("#PLAYLIST:{}", self.title));
+        for entry in self.entries.iter() {
+            // * 長さが分からない場合、#EXTINFは-1とし終了位置は指定しない
+            lines.push(format!(
+                "#EXTINF:{},{}",
+                entry
+                    .length
+                    .map_or(-1, |l| Chapters::to_seconds(l).round() as i64),
+                Self::build_display_title(entry)
+            ));
+            lines.push(format!(
+                "#EXTVLCOPT:start-time={:.3}",
+                Chapters::to_seconds(entry.file_start)
+            ));
+            if let Some(length) = entry.length {
+                lines.push(format!(
+                    "#EXTVLCOPT:stop-time={:.3}",
+                    Chapters::to_seconds(entry.file_start + length)
+                ));
+            }
+            lines.push(entry.path.clone());
+        }
+        lines.push(String::new());
+
+        util::join_strings_with_line_ending(&lines, style)
+    }
+
+    fn build_display_title(entry: &TracklistEntry) -> String {
+        let title = entry.title.replace(['\r', '\n'], " ");
+        match &entry.performer {
+            Some(performer) => format!("{} - {}", performer.replace(['\r', '\n'], " "), title),
+            None => title,
+        }
+    }
+
+    // MM:SS:FF(分は99を超えてもよい)
+    fn to_msf(frame: u32) -> String {
+        let frames_per_second = Chapters::FRAMES_PER_SECOND as u32;
+        format!(
+            "{:0>2}:{:0>2}:{:0>2}",
+            frame / frames_per_second / 60,
+            frame / frames_per_second % 60,
+            frame % frames_per_second
+        )
+    }
+
+    // M:SS(秒は四捨五入)
+    fn to_minutes(frame: u32) -> String {
+        let seconds = Chapters::to_seconds(frame).round() as u64;
+        format!("{}:{:0>2}", seconds / 60, seconds % 60)
+    }
+
+    fn escape_field(value: &str, delimiter: Delimiter) -> String {
+        match delimiter {
+            Delimiter::Comma => {
+                if value.contains([',', '"', '\r', '\n']) {
+                    return format!("\"{}\"", value.replace('"', "\"\""));
+                }
+                String::from(value)
+            }
+            Delimiter::Tab => value.replace(['\t', '\r', '\n'], " "),
+        }
+    }
+
+    fn escape_markdown(value: &str) -> String {
+        value.replace('|', "\\|").replace(['\r', '\n'], " ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{File, FileType, Pregap, PregapType, Rem, Time, Track};
+    use super::*;
+
+    fn build_tracklist() -> Tracklist {
+        let build = |number: usize, title: &str, composer: Option<&str>, start: u32, end: u32| {
+            TracklistEntry {
+                number,
+                title: String::from(title),
+                performer: Some(String::from("P")),
+                composer: composer.map(String::from),
+                path: String::from("/a.wav"),
+                file_start: start,
+                start: Some(start),
+                length: Some(end - start),
+            }
+        };
+        Tracklist {
+            title: String::from("album"),
+            performer: Some(String::from("P")),
+            genre: None,
+            entries: vec![
+                build(1, "One, \"1\"", Some("C"), 0, 75 * 60 + 40),
+                build(2, "Two|2", None, 75 * 60 + 40, 75 * 6100),
+            ],
+        }
+    }
+
+    fn build_cue() -> Result<Cue, String> {
+        let build = |title: &str, start_at: u32| -> Result<Track, String> {
+            Ok(Track::new(
+                String::from(title),
+                None,
+                Rem::default(),
+                None,
+                Pregap {
+                    r#type: PregapType::Silent,
+                    duration: Time::from_frame(0)?,
+                },
+                Time::from_frame(start_at)?,
+            ))
+        };
+        Ok(Cue::new(
+            String::from("album"),
+            None,
+            None,
+            Rem::default(),
+            vec![
+                File::new(
+                    String::from("/a.wav"),
+                    vec![build("One", 0)?, build("Two", 750)?],
+                    FileType::Wave,
+                ),
+                File::new(
+                    String::from("/b.wav"),
+                    vec![build("Three", 0)?],
+                    FileType::Wave,
+                ),
+            ],
+        ))
+    }
+
+    #[test]
+    fn test_tracklist() -> Result<(), String> {
+        let cue = build_cue()?;
+
+        let known = cue.tracklist(&[Some(1500), Some(300)])?;
+        let unknown = cue.tracklist(&[None, Some(300)])?;
+
+        let fields = |tracklist: &Tracklist| -> Vec<(u32, Option<u32>, Option<u32>)> {
+            tracklist
+                .entries
+                .iter()
+                .map(|e| (e.file_start, e.start, e.length))
+                .collect()
+        };
+        assert_eq!(
+            fields(&known),
+            vec![
+                (0, Some(0), Some(750)),
+                (750, Some(750), Some(750)),
+                (0, Some(1500), Some(300)),
+            ]
+        );
+        // * ファイルの長さが分からなくても、同じファイルの次のトラックまでの長さは分かる
+        assert_eq!(
+            fields(&unknown),
+            vec![
+                (0, Some(0), Some(750)),
+                (750, Some(750), None),
+                (0, None, Some(300))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracklist_empty_file() -> Result<(), String> {
+        let mut cue = build_cue()?;
+        cue.files_mut().insert(
+            1,
+            File::new(String::from("/empty.wav"), Vec::new(), FileType::Wave),
+        );
+
+        let result = cue.tracklist(&[Some(1500), None, Some(300)])?;
+
+        assert_eq!(result.entries[2].start, Some(1500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracklist_err() -> Result<(), String> {
+        let cue = build_cue()?;
+
+        let result = cue.tracklist(&[Some(700), None]);
+
+        assert_eq!(
+            result,
+            Err(String::from(
+                "INDEX 01 of track No.2 exceeds the length of /a.wav"
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_tracklist_string_unknown_length() -> Result<(), String> {
+        let tracklist = build_cue()?.tracklist(&[None, None])?;
+
+        let csv = tracklist.to_tracklist_string(Delimiter::Comma, &TextStyle::default());
+        let markdown = tracklist.to_markdown_string(&TextStyle::default());
+        let m3u8 = tracklist.to_m3u8_string(&Header::new(false, None), &TextStyle::default());
+
+        assert_eq!(
+            csv.lines().collect::<Vec<&str>>(),
+            vec![
+                "number,title,performer,composer,start,length",
+                "1,One,,,00:00:00,00:10:00",
+                "2,Two,,,00:10:00,",
+                "3,Three,,,,",
+            ]
+        );
+        assert_eq!(markdown.lines().nth(5), Some("| 2 | Two |  |  |  |"));
+        assert_eq!(
+            m3u8.lines().skip(6).collect::<Vec<&str>>(),
+            vec![
+                "#EXTINF:-1,Two",
+                "#EXTVLCOPT:start-time=10.000",
+                "/a.wav",
+                "#EXTINF:-1,Three",
+                "#EXTVLCOPT:start-time=0.000",
+                "/b.wav",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_tracklist_string() {
+        let tracklist = build_tracklist();
+
+        let csv = tracklist.to_tracklist_string(Delimiter::Comma, &TextStyle::default());
+        let tsv = tracklist.to_tracklist_string(Delimiter::Tab, &TextStyle::default());
+
+        assert_eq!(
+            csv,
+            [
+                "number,title,performer,composer,start,length",
+                "1,\"One, \"\"1\"\"\",P,C,00:00:00,01:00:40",
+                "2,Two|2,P,,01:00:40,100:39:35",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            tsv.lines().nth(1),
+            Some("1\tOne, \"1\"\tP\tC\t00:00:00\t01:00:40")
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_string() {
+        let tracklist = build_tracklist();
+
+        let result = tracklist.to_markdown_string(&TextStyle::default());
+
+        let expected = [
+            "# album",
+            "",
+            "- Performer: P",
+            "",
+            "| # | Title | Performer | Composer | Length |",
+            "|--:|:--|:--|:--|--:|",
+            "| 1 | One, \"1\" | P | C | 1:01 |",
+            "| 2 | Two\\|2 | P |  | 100:39 |",
+            "",
+        ];
+        assert_eq!(result, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_to_m3u8_string() {
+        let tracklist = build_tracklist();
+
+        let result = tracklist.to_m3u8_string(&Header::new(false, None), &TextStyle::default());
+
+        let expected = [
+            "#EXTM3U",
+            "#PLAYLIST:album",
+            "#EXTINF:61,P - One, \"1\"",
+            "#EXTVLCOPT:start-time=0.000",
+            "#EXTVLCOPT:stop-time=60.533",
+            "/a.wav",
+            "#EXTINF:6039,P - Two|2",
+            "#EXTVLCOPT:start-time=60.533",
+            "#EXTVLCOPT:stop-time=6100.000",
+            "/a.wav",
+            "",
+        ];
+        assert_eq!(result, expected.join("\n"));
+    }
+}
@@ -1,16 +1,47 @@
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Json {
+    #[serde(rename = "$schema")]
+    pub schema: Option<String>,
     pub version: u8,
     pub title: String,
     pub genre: Option<String>,
     pub info: Option<Info>,
+    pub rem: Option<IndexMap<String, String>>,
+    pub first_track_number: Option<u8>,
+    pub htoa: Option<Htoa>,
     pub files: Vec<File>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// 共通のアルバム情報と複数のディスク
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscSet {
+    #[serde(rename = "$schema")]
+    pub schema: Option<String>,
+    pub version: u8,
+    pub title: String,
+    pub genre: Option<String>,
+    pub info: Option<Info>,
+    pub rem: Option<IndexMap<String, String>>,
+    pub discs: Vec<Disc>,
+}
+
+// 省略した項目はディスクセットの値を使う(infoとremは項目ごとに上書き)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Disc {
+    pub title: Option<String>,
+    pub genre: Option<String>,
+    pub info: Option<Info>,
+    pub rem: Option<IndexMap<String, String>>,
+    pub first_track_number: Option<u8>,
+    pub htoa: Option<Htoa>,
+    pub files: Vec<File>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct File {
     pub path: PathBuf,
     pub tracks: Vec<Track>,
@@ -18,25 +49,81 @@ pub struct File {
     pub file_type: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Track {
+    pub number: Option<u8>,
     pub title: String,
     pub info: Option<Info>,
+    pub rem: Option<IndexMap<String, String>>,
+    pub isrc: Option<String>,
     pub pregap: Option<Pregap>,
-    pub start_at: Option<Vec<u8>>,
+    pub start_at: Option<TimeValue>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// 省略(None)はアルバムの値を引き継ぎ、null(Some(None))は消去する
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Info {
-    pub arranger: Option<String>,
-    pub composer: Option<String>,
-    pub lyricist: Option<String>,
-    pub performer: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub arranger: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub composer: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lyricist: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub performer: Option<Option<String>>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+// トラック1のプリギャップに隠した音声(HTOA)
+// * pathで別のファイル、durationで最初のファイルの先頭からの長さを指定する
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Htoa {
+    pub path: Option<PathBuf>,
+    #[serde(rename = "type")]
+    pub file_type: Option<String>,
+    pub duration: Option<TimeValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Pregap {
-    pub duration: Vec<u8>,
+    pub duration: TimeValue,
     #[serde(rename = "type")]
     pub pregap_type: String,
 }
+
+// [分, 秒, フレーム]、"mm:ss:ff"/"mm:ss.sss"、秒数、サンプル数のいずれか
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TimeValue {
+    Msf(Vec<u8>),
+    Seconds(f64),
+    Text(String),
+    Samples {
+        samples: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sample_rate: Option<u32>,
+    },
+}
@@ -0,0 +1,128 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Json {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    pub version: u8,
+    pub disc: Disc,
+    pub files: Vec<File>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Disc {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<Info>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rem: Option<IndexMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_track_number: Option<u8>,
+    // トラック1のプリギャップに隠した音声を収めた別のファイル
+    // * 最初のファイルに含まれる場合はトラック1のINDEX 00で表す
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub htoa: Option<Htoa>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Htoa {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub file_type: FileType,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct File {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub file_type: FileType,
+    pub tracks: Vec<Track>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FileType {
+    #[serde(rename = "WAVE")]
+    Wave,
+    #[serde(rename = "AIFF")]
+    Aiff,
+    #[serde(rename = "MP3")]
+    Mp3,
+    #[serde(rename = "BINARY")]
+    Binary,
+    #[serde(rename = "MOTOROLA")]
+    Motorola,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Track {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<u8>,
+    pub title: String,
+    #[serde(default)]
+    pub mode: TrackMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<Info>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rem: Option<IndexMap<String, String>>,
+    // 無音として挿入するプリギャップ(ファイルに含まれるプリギャップはINDEX 00で表す)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pregap: Option<Time>,
+    // 前のファイルの末尾に含まれるプリギャップ(INDEX 00の位置は前のファイルの長さから決める)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appended_pregap: Option<Time>,
+    pub indexes: Vec<Index>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TrackMode {
+    #[default]
+    #[serde(rename = "AUDIO")]
+    Audio,
+    #[serde(rename = "CDG")]
+    Cdg,
+    #[serde(rename = "MODE1/2048")]
+    Mode1_2048,
+    #[serde(rename = "MODE1/2352")]
+    Mode1_2352,
+    #[serde(rename = "MODE2/2336")]
+    Mode2_2336,
+    #[serde(rename = "MODE2/2352")]
+    Mode2_2352,
+    #[serde(rename = "CDI/2336")]
+    Cdi2336,
+    #[serde(rename = "CDI/2352")]
+    Cdi2352,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Index {
+    pub number: u8,
+    pub time: Time,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    pub minute: u8,
+    pub second: u8,
+    pub frame: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Info {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arranger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub composer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lyricist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub performer: Option<String>,
+}
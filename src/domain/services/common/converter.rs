@@ -0,0 +1,178 @@
+use super::FileCheck;
+use crate::domain::aggregates::common::Document;
+use crate::domain::aggregates::v1::cue::Cue;
+use crate::share::{Issue, IssueCode};
+
+// 変換結果と警告(変換はできたが確認が必要な内容)
+pub struct Converted {
+    pub cue: Cue,
+    pub warnings: Vec<Issue>,
+}
+
+pub trait CueConverter {
+    fn version(&self) -> u8;
+    fn convert(&self, document: &Document, file_check: FileCheck) -> Result<Converted, Issue>;
+    // 複数のディスクをまとめた文書はディスクごとに変換する
+    fn convert_set(
+        &self,
+        _document: &Document,
+        _file_check: FileCheck,
+    ) -> Result<Vec<Converted>, Issue> {
+        Err(Issue::new(
+            IssueCode::Version,
+            format!("disc sets are not supported in version {}", self.version()),
+        )
+        .within("/version"))
+    }
+}
+
+#[derive(Default)]
+pub struct ConverterRegistry {
+    converters: Vec<Box<dyn CueConverter>>,
+}
+
+impl ConverterRegistry {
+    pub fn new() -> ConverterRegistry {
+        ConverterRegistry::default()
+    }
+
+    // 同じバージョンが登録済みの場合は置き換える
+    pub fn register(&mut self, converter: Box<dyn CueConverter>) {
+        self.converters
+            .retain(|c| c.version() != converter.version());
+        self.converters.push(converter);
+    }
+
+    pub fn supported_versions(&self) -> Vec<u8> {
+        let mut result: Vec<u8> = self.converters.iter().map(|c| c.version()).collect();
+        result.sort();
+        result
+    }
+
+    pub fn convert(&self, document: &Document, file_check: FileCheck) -> Result<Converted, Issue> {
+        self.find(document)?.convert(document, file_check)
+    }
+
+    // ディスクセットでない場合は1枚のディスクとして返す
+    pub fn convert_all(
+        &self,
+        document: &Document,
+        file_check: FileCheck,
+    ) -> Result<Vec<Converted>, Issue> {
+        let converter = self.find(document)?;
+        if document.is_disc_set() {
+            return converter.convert_set(document, file_check);
+        }
+        Ok(vec![converter.convert(document, file_check)?])
+    }
+
+    fn find(&self, document: &Document) -> Result<&dyn CueConverter, Issue> {
+        let Some(converter) = self
+            .converters
+            .iter()
+            .find(|c| c.version() == document.version())
+        else {
+            let supported: Vec<String> = self
+                .supported_versions()
+                .iter()
+                .map(|v| v.to_string())
+                .collect();
+            return Err(Issue::new(
+                IssueCode::Version,
+                format!(
+                    "unknown version: {} (supported versions: {})",
+                    document.version(),
+                    supported.join(", ")
+                ),
+            )
+            .within("/version"));
+        };
+        Ok(converter.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::common::InputFormat;
+    use crate::domain::aggregates::v1::cue::Rem;
+
+    struct MockConverter {
+        version: u8,
+    }
+    impl CueConverter for MockConverter {
+        fn version(&self) -> u8 {
+            self.version
+        }
+
+        fn convert(&self, _: &Document, _: FileCheck) -> Result<Converted, Issue> {
+            Ok(Converted {
+                cue: Cue::new(
+                    format!("v{}", self.version),
+                    None,
+                    None,
+                    Rem::default(),
+                    Vec::new(),
+                ),
+                warnings: Vec::new(),
+            })
+        }
+    }
+
+    fn build_registry() -> ConverterRegistry {
+        let mut registry = ConverterRegistry::new();
+        registry.register(Box::new(MockConverter { version: 3 }));
+        registry.register(Box::new(MockConverter { version: 1 }));
+        registry
+    }
+
+    #[test]
+    fn test_convert() -> Result<(), String> {
+        let registry = build_registry();
+        let document = Document::parse(String::from("{\"version\": 3}"), InputFormat::Json)?;
+
+        let result = registry.convert(&document, FileCheck::Required)?;
+
+        assert_eq!(result.cue.title(), "v3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_all() -> Result<(), String> {
+        let registry = build_registry();
+        let disc = Document::parse(String::from("{\"version\": 1}"), InputFormat::Json)?;
+        let disc_set = Document::parse(
+            String::from("{\"version\": 1, \"discs\": []}"),
+            InputFormat::Json,
+        )?;
+
+        let result = registry.convert_all(&disc, FileCheck::Required)?;
+        let Err(disc_set) = registry.convert_all(&disc_set, FileCheck::Required) else {
+            return Err(String::from("unsupported disc set is accepted"));
+        };
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(disc_set.message, "disc sets are not supported in version 1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_unknown_version() -> Result<(), String> {
+        let registry = build_registry();
+        let document = Document::parse(String::from("{\"version\": 2}"), InputFormat::Json)?;
+
+        let Err(result) = registry.convert(&document, FileCheck::Required) else {
+            return Err(String::from("unknown version is accepted"));
+        };
+
+        assert_eq!(
+            result.message,
+            "unknown version: 2 (supported versions: 1, 3)"
+        );
+        assert_eq!(
+            (result.code, result.pointer.as_deref()),
+            (IssueCode::Version, Some("/version"))
+        );
+        Ok(())
+    }
+}
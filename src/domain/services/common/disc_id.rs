@@ -0,0 +1,141 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+// offsetsとlead_outはリードイン(150フレーム)を含むディスク先頭からのフレーム数
+pub struct DiscId {
+    first_track_number: usize,
+    offsets: Vec<u32>,
+    lead_out: u32,
+}
+
+impl DiscId {
+    pub const LEAD_IN_FRAMES: u32 = 150;
+    const FRAMES_PER_SECOND: u32 = 75;
+    const MAX_TRACKS: usize = 99;
+
+    pub fn new(
+        first_track_number: usize,
+        offsets: Vec<u32>,
+        lead_out: u32,
+    ) -> Result<DiscId, String> {
+        if offsets.is_empty() {
+            return Err(String::from("disc has no tracks"));
+        }
+        if first_track_number + offsets.len() - 1 > Self::MAX_TRACKS {
+            return Err(format!(
+                "disc must have at most {} tracks",
+                Self::MAX_TRACKS
+            ));
+        }
+        if offsets.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(String::from("track offsets must be in ascending order"));
+        }
+        if let Some(last) = offsets.last()
+            && *last >= lead_out
+        {
+            return Err(String::from("lead-out must be after the last track"));
+        }
+        Ok(DiscId {
+            first_track_number,
+            offsets,
+            lead_out,
+        })
+    }
+
+    pub fn freedb(&self) -> String {
+        let checksum: u32 = self
+            .offsets
+            .iter()
+            .map(|o| Self::digit_sum(o / Self::FRAMES_PER_SECOND))
+            .sum();
+        let total_seconds =
+            self.lead_out / Self::FRAMES_PER_SECOND - self.offsets[0] / Self::FRAMES_PER_SECOND;
+        let id = ((checksum % 0xFF) << 24) | (total_seconds << 8) | self.offsets.len() as u32;
+        format!("{:08X}", id)
+    }
+
+    pub fn musicbrainz(&self) -> String {
+        let last_track_number = self.first_track_number + self.offsets.len() - 1;
+        let mut toc = format!(
+            "{:02X}{:02X}{:08X}",
+            self.first_track_number, last_track_number, self.lead_out
+        );
+        // 100トラック分(リードアウト+99トラック)のうち残りは0で埋める
+        for number in 1..=Self::MAX_TRACKS {
+            let offset = if number < self.first_track_number {
+                None
+            } else {
+                self.offsets.get(number - self.first_track_number)
+            };
+            toc += &format!("{:08X}", offset.copied().unwrap_or(0));
+        }
+        let digest = sha1_smol::Sha1::from(toc.as_bytes()).digest().bytes();
+        STANDARD
+            .encode(digest)
+            .replace('+', ".")
+            .replace('/', "_")
+            .replace('=', "-")
+    }
+
+    fn digit_sum(mut value: u32) -> u32 {
+        let mut result = 0;
+        while value > 0 {
+            result += value % 10;
+            value /= 10;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFSETS: [u32; 22] = [
+        150, 9700, 25887, 39297, 53795, 63735, 77517, 94877, 107270, 123552, 135522, 148422,
+        161197, 174790, 192022, 205545, 218010, 228700, 239590, 255470, 266932, 288750,
+    ];
+    const LEAD_OUT: u32 = 303602;
+
+    #[test]
+    fn test_freedb() -> Result<(), String> {
+        let disc_id = DiscId::new(1, Vec::from(OFFSETS), LEAD_OUT)?;
+
+        assert_eq!(disc_id.freedb(), String::from("370FCE16"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_musicbrainz() -> Result<(), String> {
+        let disc_id = DiscId::new(1, Vec::from(OFFSETS), LEAD_OUT)?;
+
+        assert_eq!(
+            disc_id.musicbrainz(),
+            String::from("xUp1F2NkfP8s8jaeFn_Av3jNEI4-")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_err() {
+        let Err(empty) = DiscId::new(1, Vec::new(), LEAD_OUT) else {
+            panic!("empty disc is accepted");
+        };
+        let Err(order) = DiscId::new(1, vec![150, 100], LEAD_OUT) else {
+            panic!("unordered offsets are accepted");
+        };
+        let Err(lead_out) = DiscId::new(1, vec![150, 300], 300) else {
+            panic!("lead-out before the last track is accepted");
+        };
+
+        assert_eq!(empty, String::from("disc has no tracks"));
+        assert_eq!(
+            order,
+            String::from("track offsets must be in ascending order")
+        );
+        assert_eq!(
+            lead_out,
+            String::from("lead-out must be after the last track")
+        );
+    }
+}
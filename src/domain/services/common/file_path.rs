@@ -0,0 +1,81 @@
+use crate::share::{Issue, IssueCode};
+use std::fs;
+use std::path::{self, Path};
+
+// 音声ファイルの存在を確認するか
+// * lintなど音声ファイルを読まない処理では確認しない
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FileCheck {
+    #[default]
+    Required,
+    Skipped,
+}
+
+// 入力ファイルに書いた音声ファイルのパスを絶対パスにする
+// * v1とv2で共通
+pub struct FilePath {}
+
+impl FilePath {
+    pub fn resolve(file_path: &Path, file_check: FileCheck) -> Result<String, Issue> {
+        let Ok(abs_file_path_buf) = path::absolute(file_path) else {
+            return Err(Issue::new(
+                IssueCode::AudioFile,
+                format!("file path({:?}) is not parsable", file_path),
+            ));
+        };
+        match file_check {
+            FileCheck::Required => Self::validate_exists(file_path, &abs_file_path_buf)?,
+            FileCheck::Skipped => (),
+        };
+        let Some(abs_file_path) = abs_file_path_buf.to_str() else {
+            return Err(Issue::new(
+                IssueCode::AudioFile,
+                format!("file path({:?}) is not parsable", file_path),
+            ));
+        };
+        Ok(String::from(abs_file_path))
+    }
+
+    fn validate_exists(file_path: &Path, abs_file_path: &Path) -> Result<(), Issue> {
+        match fs::exists(abs_file_path) {
+            Err(d) => Err(Issue::new(IssueCode::AudioFile, format!("{}", d))),
+            Ok(false) => Err(Issue::new(
+                IssueCode::AudioFile,
+                format!("file ({:?}) does not exist", file_path),
+            )),
+            Ok(true) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() -> Result<(), String> {
+        let result = FilePath::resolve(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"),
+            FileCheck::Required,
+        )?;
+        let skipped = FilePath::resolve(Path::new("missing.wav"), FileCheck::Skipped)?;
+
+        assert!(result.ends_with("Cargo.toml"));
+        assert!(Path::new(&skipped).is_absolute());
+        assert!(skipped.ends_with("missing.wav"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_err() {
+        let result = FilePath::resolve(Path::new("missing.wav"), FileCheck::Required);
+
+        assert_eq!(
+            result,
+            Err(Issue::new(
+                IssueCode::AudioFile,
+                String::from("file (\"missing.wav\") does not exist")
+            ))
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::domain::aggregates::v1::cue::Cue;
+use crate::share::TextEncoding;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+// ルールに渡す設定(出力先の形式など)
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    pub encoding: TextEncoding,
+    pub max_title_length: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions {
+            encoding: TextEncoding::Utf8,
+            max_title_length: 80,
+        }
+    }
+}
+
+// ルールが見つけた問題(trackがNoneの場合はディスク全体)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub track: Option<usize>,
+    pub message: String,
+}
+
+pub trait LintRule {
+    fn id(&self) -> &'static str;
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warning
+    }
+    fn check(&self, cue: &Cue, options: &LintOptions) -> Vec<LintIssue>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub rule_id: &'static str,
+    pub level: LintLevel,
+    pub issue: LintIssue,
+}
+
+impl LintFinding {
+    pub fn location(&self) -> String {
+        match self.issue.track {
+            Some(number) => format!("track {:02}", number),
+            None => String::from("disc"),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    pub fn count(&self, level: LintLevel) -> usize {
+        self.findings.iter().filter(|f| f.level == level).count()
+    }
+
+    pub fn to_summary_string(&self) -> String {
+        match self.findings.len() {
+            0 => String::from("no problems"),
+            total => format!(
+                "{} problem(s) ({} error(s), {} warning(s))",
+                total,
+                self.count(LintLevel::Error),
+                self.count(LintLevel::Warning)
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<(Box<dyn LintRule>, LintLevel)>,
+}
+
+impl Linter {
+    pub fn new() -> Linter {
+        Linter::default()
+    }
+
+    // 同じIDが登録済みの場合は置き換える
+    pub fn register(&mut self, rule: Box<dyn LintRule>) {
+        self.rules.retain(|(r, _)| r.id() != rule.id());
+        let level = rule.default_level();
+        self.rules.push((rule, level));
+    }
+
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|(r, _)| r.id()).collect()
+    }
+
+    pub fn set_level(&mut self, id: &str, level: LintLevel) -> Result<(), String> {
+        let Some(entry) = self.rules.iter_mut().find(|(r, _)| r.id() == id) else {
+            return Err(format!(
+                "unknown lint rule: {} (available rules: {})",
+                id,
+                self.rule_ids().join(", ")
+            ));
+        };
+        entry.1 = level;
+        Ok(())
+    }
+
+    // 登録順、ルールごとの検出順に並べる
+    pub fn lint(&self, cue: &Cue, options: &LintOptions) -> LintReport {
+        let findings = self
+            .rules
+            .iter()
+            .filter(|(_, level)| *level != LintLevel::Off)
+            .flat_map(|(rule, level)| {
+                rule.check(cue, options)
+                    .into_iter()
+                    .map(|issue| LintFinding {
+                        rule_id: rule.id(),
+                        level: *level,
+                        issue,
+                    })
+            })
+            .collect();
+        LintReport { findings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::cue::Rem;
+
+    struct AlwaysRule {
+        id: &'static str,
+    }
+
+    impl LintRule for AlwaysRule {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn check(&self, _cue: &Cue, _options: &LintOptions) -> Vec<LintIssue> {
+            vec![LintIssue {
+                track: Some(1),
+                message: String::from("message"),
+            }]
+        }
+    }
+
+    fn build_linter() -> Linter {
+        let mut linter = Linter::new();
+        linter.register(Box::new(AlwaysRule { id: "first" }));
+        linter.register(Box::new(AlwaysRule { id: "second" }));
+        linter.register(Box::new(AlwaysRule { id: "first" }));
+        linter
+    }
+
+    #[test]
+    fn test_lint() -> Result<(), String> {
+        let cue = Cue::new(String::from("album"), None, None, Rem::default(), vec![]);
+        let mut linter = build_linter();
+        linter.set_level("second", LintLevel::Error)?;
+
+        let report = linter.lint(&cue, &LintOptions::default());
+
+        assert_eq!(linter.rule_ids(), vec!["second", "first"]);
+        assert_eq!(
+            report
+                .findings
+                .iter()
+                .map(|f| (f.rule_id, f.level, f.location()))
+                .collect::<Vec<(&str, LintLevel, String)>>(),
+            vec![
+                ("second", LintLevel::Error, String::from("track 01")),
+                ("first", LintLevel::Warning, String::from("track 01")),
+            ]
+        );
+        assert_eq!(
+            report.to_summary_string(),
+            "2 problem(s) (1 error(s), 1 warning(s))"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_off() -> Result<(), String> {
+        let cue = Cue::new(String::from("album"), None, None, Rem::default(), vec![]);
+        let mut linter = build_linter();
+        linter.set_level("first", LintLevel::Off)?;
+        linter.set_level("second", LintLevel::Off)?;
+
+        let report = linter.lint(&cue, &LintOptions::default());
+
+        assert_eq!(report.to_summary_string(), "no problems");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_level_err() {
+        let mut linter = build_linter();
+
+        let result = linter.set_level("third", LintLevel::Off);
+
+        assert_eq!(
+            result,
+            Err(String::from(
+                "unknown lint rule: third (available rules: second, first)"
+            ))
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use super::lint::{LintIssue, LintOptions, LintRule};
+use crate::domain::aggregates::v1::cue::{Cue, Info, PregapType, TimeFormatter, Track};
+use crate::share::TextEncoding;
+
+// トラック1のPREGAPはImgBurnなどで無視・拒否される
+pub struct PregapOnFirstTrack;
+
+impl LintRule for PregapOnFirstTrack {
+    fn id(&self) -> &'static str {
+        "pregap-on-first-track"
+    }
+
+    fn check(&self, cue: &Cue, _options: &LintOptions) -> Vec<LintIssue> {
+        let Some(track) = cue.files().first().and_then(|f| f.tracks().first()) else {
+            return Vec::new();
+        };
+        let pregap = track.pregap();
+        if pregap.r#type != PregapType::Silent || pregap.duration.to_frame() == 0 {
+            return Vec::new();
+        }
+        vec![LintIssue {
+            track: Some(cue.first_track_number()),
+            message: format!(
+                "PREGAP {} on the first track is ignored or rejected by some burning software",
+                pregap.duration.to_msf_string()
+            ),
+        }]
+    }
+}
+
+// ファイルの最初のINDEXが先頭にない場合、その手前の音声はどのトラックにも含まれない
+// * トラックごとにFILEを分けた場合、foobar2000やcdrdaoでは先頭の音声が失われる
+pub struct FirstIndexNotAtFileStart;
+
+impl LintRule for FirstIndexNotAtFileStart {
+    fn id(&self) -> &'static str {
+        "first-index-not-at-file-start"
+    }
+
+    fn check(&self, cue: &Cue, _options: &LintOptions) -> Vec<LintIssue> {
+        numbered_tracks(cue)
+            .into_iter()
+            .filter(|(_, track_index, _)| *track_index == 0)
+            .filter_map(|(number, _, track)| {
+                let (name, time) = match track.index_00() {
+                    Some(time) => ("INDEX 00", time),
+                    None => ("INDEX 01", track.start_at()),
+                };
+                (time.to_frame() != 0).then(|| LintIssue {
+                    track: Some(number),
+                    message: format!(
+                        "{} is at {}, the audio before it in the file belongs to no track",
+                        name,
+                        time.to_msf_string()
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+// CD-TEXTのTITLEは長すぎると書き込み時に切り詰められる
+pub struct TitleTooLong;
+
+impl LintRule for TitleTooLong {
+    fn id(&self) -> &'static str {
+        "title-too-long"
+    }
+
+    fn check(&self, cue: &Cue, options: &LintOptions) -> Vec<LintIssue> {
+        let album = (None, cue.title());
+        let tracks = numbered_tracks(cue)
+            .into_iter()
+            .map(|(number, _, track)| (Some(number), track.title()));
+        std::iter::once(album)
+            .chain(tracks)
+            .filter_map(|(number, title)| {
+                let length = title.chars().count();
+                (length > options.max_title_length).then(|| LintIssue {
+                    track: number,
+                    message: format!(
+                        "TITLE is {} characters long (max {})",
+                        length, options.max_title_length
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+// Latin-1で書き出す場合、Latin-1(U+0000〜U+00FF)にない文字は書き出せない
+pub struct NonLatin1Characters;
+
+impl LintRule for NonLatin1Characters {
+    fn id(&self) -> &'static str {
+        "non-latin1-characters"
+    }
+
+    fn check(&self, cue: &Cue, options: &LintOptions) -> Vec<LintIssue> {
+        if options.encoding != TextEncoding::Latin1 {
+            return Vec::new();
+        }
+        let mut album = vec![("TITLE", cue.title())];
+        album.extend(cue.genre().map(|g| ("REM GENRE", g.as_str())));
+        album.extend(info_fields(cue.info()));
+        let mut result = find_non_latin1(None, &album);
+        for (number, _, track) in numbered_tracks(cue) {
+            let mut fields = vec![("TITLE", track.title())];
+            fields.extend(info_fields(track.info()));
+            result.extend(find_non_latin1(Some(number), &fields));
+        }
+        result
+    }
+}
+
+// (トラック番号, ファイル内の位置, トラック)
+fn numbered_tracks(cue: &Cue) -> Vec<(usize, usize, &Track)> {
+    cue.files()
+        .iter()
+        .flat_map(|f| f.tracks().iter().enumerate())
+        .enumerate()
+        .map(|(index, (track_index, track))| (cue.first_track_number() + index, track_index, track))
+        .collect()
+}
+
+fn info_fields(info: Option<&Info>) -> Vec<(&'static str, &str)> {
+    let Some(info) = info else {
+        return Vec::new();
+    };
+    [
+        ("PERFORMER", info.performer()),
+        ("REM COMPOSER", info.composer()),
+        ("REM ARRANGER", info.arranger()),
+        ("REM LYRICIST", info.lyricist()),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.map(|v| (key, v.as_str())))
+    .collect()
+}
+
+fn find_non_latin1(track: Option<usize>, fields: &[(&str, &str)]) -> Vec<LintIssue> {
+    fields
+        .iter()
+        .filter_map(|(key, value)| {
+            let chars: String = value.chars().filter(|c| u32::from(*c) > 0xFF).collect();
+            (!chars.is_empty()).then(|| LintIssue {
+                track,
+                message: format!("{} contains characters outside Latin-1: {}", key, chars),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::cue::{File, FileType, Pregap, Rem, Time};
+
+    fn build_track(
+        title: &str,
+        pregap_type: PregapType,
+        pregap: u32,
+        start_at: u32,
+    ) -> Result<Track, String> {
+        Ok(Track::new(
+            String::from(title),
+            None,
+            Rem::default(),
+            None,
+            Pregap {
+                r#type: pregap_type,
+                duration: Time::from_frame(pregap)?,
+            },
+            Time::from_frame(start_at)?,
+        ))
+    }
+
+    fn build_cue(tracks: Vec<Vec<Track>>) -> Cue {
+        let files = tracks
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| File::new(format!("{}.wav", i + 1), t, FileType::Wave))
+            .collect();
+        Cue::new(
+            String::from("album"),
+            None,
+            Some(Info::new(None, None, None, Some(String::from("Beyoncé")))),
+            Rem::default(),
+            files,
+        )
+    }
+
+    fn messages(
+        rule: &dyn LintRule,
+        cue: &Cue,
+        options: &LintOptions,
+    ) -> Vec<(Option<usize>, String)> {
+        rule.check(cue, options)
+            .into_iter()
+            .map(|i| (i.track, i.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_pregap_on_first_track() -> Result<(), String> {
+        let with_pregap = build_cue(vec![vec![build_track("a", PregapType::Silent, 150, 0)?]]);
+        let without_pregap = build_cue(vec![vec![build_track("a", PregapType::Included, 150, 0)?]]);
+
+        let options = LintOptions::default();
+
+        assert_eq!(
+            messages(&PregapOnFirstTrack, &with_pregap, &options),
+            vec![(
+                Some(1),
+                String::from(
+                    "PREGAP 00:02:00 on the first track is ignored or rejected by some burning software"
+                )
+            )]
+        );
+        assert!(messages(&PregapOnFirstTrack, &without_pregap, &options).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_index_not_at_file_start() -> Result<(), String> {
+        let cue = build_cue(vec![
+            vec![
+                build_track("a", PregapType::Silent, 0, 0)?,
+                build_track("b", PregapType::Silent, 0, 1500)?,
+            ],
+            vec![build_track("c", PregapType::Included, 150, 75)?],
+            vec![build_track("d", PregapType::Silent, 150, 30)?],
+        ]);
+
+        let result = messages(&FirstIndexNotAtFileStart, &cue, &LintOptions::default());
+
+        assert_eq!(
+            result,
+            vec![
+                (
+                    Some(3),
+                    String::from(
+                        "INDEX 00 is at 00:01:00, the audio before it in the file belongs to no track"
+                    )
+                ),
+                (
+                    Some(4),
+                    String::from(
+                        "INDEX 01 is at 00:00:30, the audio before it in the file belongs to no track"
+                    )
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_title_too_long() -> Result<(), String> {
+        let cue = build_cue(vec![vec![
+            build_track("あいうえお", PregapType::Silent, 0, 0)?,
+            build_track("あいうえおか", PregapType::Silent, 0, 150)?,
+        ]]);
+        let options = LintOptions {
+            max_title_length: 5,
+            ..LintOptions::default()
+        };
+
+        let result = messages(&TitleTooLong, &cue, &options);
+
+        assert_eq!(
+            result,
+            vec![(Some(2), String::from("TITLE is 6 characters long (max 5)"))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_latin1_characters() -> Result<(), String> {
+        let mut track = build_track("Café Ñ “1”", PregapType::Silent, 0, 0)?;
+        track.set_info(Some(Info::new(
+            None,
+            None,
+            None,
+            Some(String::from("Łukasz")),
+        )));
+        let cue = build_cue(vec![vec![track]]);
+        let latin1 = LintOptions {
+            encoding: TextEncoding::Latin1,
+            ..LintOptions::default()
+        };
+
+        let result = messages(&NonLatin1Characters, &cue, &latin1);
+        let utf8 = messages(&NonLatin1Characters, &cue, &LintOptions::default());
+
+        // * アルバムのé、トラックのé/ÑはLatin-1で書き出せる
+        assert_eq!(
+            result,
+            vec![
+                (
+                    Some(1),
+                    String::from("TITLE contains characters outside Latin-1: “”")
+                ),
+                (
+                    Some(1),
+                    String::from("PERFORMER contains characters outside Latin-1: Ł")
+                ),
+            ]
+        );
+        assert!(utf8.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use std::f64::consts::PI;
+
+// ITU-R BS.1770 のK特性フィルタ(双2次フィルタ)
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        Biquad {
+            b,
+            a,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z1;
+        self.z1 = self.b[1] * x - self.a[1] * y + self.z2;
+        self.z2 = self.b[2] * x - self.a[2] * y;
+        y
+    }
+
+    fn shelf(sample_rate: u32) -> Biquad {
+        let f0 = 1681.974450955533;
+        let gain = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (PI * f0 / sample_rate as f64).tan();
+        let vh = 10f64.powf(gain / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    }
+
+    fn highpass(sample_rate: u32) -> Biquad {
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (PI * f0 / sample_rate as f64).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [1.0, -2.0, 1.0],
+            [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    }
+}
+
+pub struct LoudnessMeter {
+    channels: usize,
+    weights: Vec<f64>,
+    filters: Vec<(Biquad, Biquad)>,
+    sub_block_size: usize,
+    sub_block_count: usize,
+    sub_block_sum: f64,
+    sub_blocks: Vec<f64>,
+    peak: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Loudness {
+    blocks: Vec<f64>,
+    peak: f64,
+}
+
+impl LoudnessMeter {
+    // 100msごとのサブブロックを4つまとめて400ms(75%オーバーラップ)のゲーティングブロックとする
+    const SUB_BLOCKS_PER_SECOND: u32 = 10;
+    const SUB_BLOCKS_PER_BLOCK: usize = 4;
+    const SURROUND_CHANNELS: usize = 6;
+    const SURROUND_WEIGHTS: [f64; 6] = [1.0, 1.0, 1.0, 0.0, 1.41, 1.41];
+
+    pub fn new(sample_rate: u32, channels: u16) -> LoudnessMeter {
+        let channels = channels as usize;
+        let weights = if channels == Self::SURROUND_CHANNELS {
+            Vec::from(Self::SURROUND_WEIGHTS)
+        } else {
+            vec![1.0; channels]
+        };
+        LoudnessMeter {
+            channels,
+            weights,
+            filters: vec![(Biquad::shelf(sample_rate), Biquad::highpass(sample_rate)); channels],
+            sub_block_size: (sample_rate / Self::SUB_BLOCKS_PER_SECOND).max(1) as usize,
+            sub_block_count: 0,
+            sub_block_sum: 0.0,
+            sub_blocks: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    // samplesはチャンネル順にインターリーブされた-1.0〜1.0の値
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                let sample = *sample as f64;
+                self.peak = self.peak.max(sample.abs());
+                let (shelf, highpass) = &mut self.filters[channel];
+                let filtered = highpass.process(shelf.process(sample));
+                self.sub_block_sum += self.weights[channel] * filtered * filtered;
+            }
+            self.sub_block_count += 1;
+            if self.sub_block_count == self.sub_block_size {
+                self.sub_blocks
+                    .push(self.sub_block_sum / self.sub_block_size as f64);
+                self.sub_block_count = 0;
+                self.sub_block_sum = 0.0;
+            }
+        }
+    }
+
+    pub fn finish(self) -> Loudness {
+        let blocks = self
+            .sub_blocks
+            .windows(Self::SUB_BLOCKS_PER_BLOCK)
+            .map(|w| w.iter().sum::<f64>() / Self::SUB_BLOCKS_PER_BLOCK as f64)
+            .collect();
+        Loudness {
+            blocks,
+            peak: self.peak,
+        }
+    }
+}
+
+impl Loudness {
+    const ABSOLUTE_GATE: f64 = -70.0;
+    const RELATIVE_GATE: f64 = -10.0;
+    const OFFSET: f64 = -0.691;
+
+    // アルバム単位の計測は全トラックのブロックをまとめてゲーティングする
+    pub fn merge(items: &[Loudness]) -> Loudness {
+        let mut result = Loudness::default();
+        for item in items.iter() {
+            result.blocks.extend(item.blocks.iter());
+            result.peak = result.peak.max(item.peak);
+        }
+        result
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+
+    // 統合ラウドネス(LUFS)、ゲートを通過するブロックがない場合はNone
+    pub fn integrated(&self) -> Option<f64> {
+        let absolute_gated: Vec<f64> = self
+            .blocks
+            .iter()
+            .copied()
+            .filter(|b| Self::to_lufs(*b) > Self::ABSOLUTE_GATE)
+            .collect();
+        if absolute_gated.is_empty() {
+            return None;
+        }
+        let relative_gate = Self::to_lufs(Self::mean(&absolute_gated)) + Self::RELATIVE_GATE;
+        let relative_gated: Vec<f64> = absolute_gated
+            .into_iter()
+            .filter(|b| Self::to_lufs(*b) > relative_gate)
+            .collect();
+        if relative_gated.is_empty() {
+            return None;
+        }
+        Some(Self::to_lufs(Self::mean(&relative_gated)))
+    }
+
+    fn mean(blocks: &[f64]) -> f64 {
+        blocks.iter().sum::<f64>() / blocks.len() as f64
+    }
+
+    fn to_lufs(energy: f64) -> f64 {
+        Self::OFFSET + 10.0 * energy.log10()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_sine(sample_rate: u32, channels: u16, amplitude: f32, seconds: u32) -> Vec<f32> {
+        let mut result = Vec::new();
+        for i in 0..(sample_rate * seconds) {
+            let value = amplitude * (2.0 * PI * 997.0 * i as f64 / sample_rate as f64).sin() as f32;
+            for _ in 0..channels {
+                result.push(value);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_integrated_sine() {
+        let mut meter = LoudnessMeter::new(48000, 2);
+        meter.push(&build_sine(48000, 2, 0.1, 5));
+
+        let result = meter.finish();
+
+        let Some(loudness) = result.integrated() else {
+            panic!("loudness is not measurable");
+        };
+        assert!((loudness - -20.0).abs() < 0.1, "loudness={}", loudness);
+        assert!((result.peak() - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_integrated_sample_rate() {
+        let mut meter_44 = LoudnessMeter::new(44100, 1);
+        let mut meter_96 = LoudnessMeter::new(96000, 1);
+        meter_44.push(&build_sine(44100, 1, 0.5, 3));
+        meter_96.push(&build_sine(96000, 1, 0.5, 3));
+
+        let (Some(result_44), Some(result_96)) = (
+            meter_44.finish().integrated(),
+            meter_96.finish().integrated(),
+        ) else {
+            panic!("loudness is not measurable");
+        };
+
+        assert!((result_44 - result_96).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_integrated_silence() {
+        let mut meter = LoudnessMeter::new(44100, 2);
+        meter.push(&vec![0.0; 44100 * 2 * 2]);
+        let mut short_meter = LoudnessMeter::new(44100, 2);
+        short_meter.push(&build_sine(44100, 2, 0.5, 1)[..44100 * 2 / 4]);
+
+        assert_eq!(meter.finish().integrated(), None);
+        assert_eq!(short_meter.finish().integrated(), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut loud = LoudnessMeter::new(48000, 2);
+        loud.push(&build_sine(48000, 2, 0.5, 2));
+        let mut quiet = LoudnessMeter::new(48000, 2);
+        quiet.push(&build_sine(48000, 2, 0.05, 2));
+        let loud = loud.finish();
+        let quiet = quiet.finish();
+
+        let result = Loudness::merge(&[loud.clone(), quiet.clone()]);
+
+        let (Some(album), Some(loud_track)) = (result.integrated(), loud.integrated()) else {
+            panic!("loudness is not measurable");
+        };
+        // 相対ゲートにより小さいトラックは除外される
+        assert!((album - loud_track).abs() < 0.1);
+        assert!((result.peak() - 0.5).abs() < 0.001);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod converter;
+pub use converter::*;
+pub mod disc_id;
+pub use disc_id::*;
+pub mod file_path;
+pub use file_path::*;
+pub mod lint;
+pub use lint::*;
+pub mod lint_rules;
+pub use lint_rules::*;
+pub mod loudness;
+pub use loudness::*;
+pub mod replaygain;
+pub use replaygain::*;
+pub mod track_numbering;
+pub use track_numbering::*;
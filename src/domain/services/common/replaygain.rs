@@ -0,0 +1,80 @@
+use super::loudness::Loudness;
+
+pub struct ReplayGain {
+    pub gain: Option<f64>,
+    pub peak: f64,
+}
+
+impl ReplayGain {
+    // ReplayGain 2.0 の基準ラウドネス
+    const REFERENCE_LOUDNESS: f64 = -18.0;
+    const KEY_ALBUM_GAIN: &str = "REPLAYGAIN_ALBUM_GAIN";
+    const KEY_ALBUM_PEAK: &str = "REPLAYGAIN_ALBUM_PEAK";
+    const KEY_TRACK_GAIN: &str = "REPLAYGAIN_TRACK_GAIN";
+    const KEY_TRACK_PEAK: &str = "REPLAYGAIN_TRACK_PEAK";
+
+    pub fn from_loudness(loudness: &Loudness) -> ReplayGain {
+        ReplayGain {
+            gain: loudness.integrated().map(|l| Self::REFERENCE_LOUDNESS - l),
+            peak: loudness.peak(),
+        }
+    }
+
+    pub fn to_album_rem_entries(&self) -> Vec<(String, String)> {
+        self.to_rem_entries(Self::KEY_ALBUM_GAIN, Self::KEY_ALBUM_PEAK)
+    }
+
+    pub fn to_track_rem_entries(&self) -> Vec<(String, String)> {
+        self.to_rem_entries(Self::KEY_TRACK_GAIN, Self::KEY_TRACK_PEAK)
+    }
+
+    fn to_rem_entries(&self, key_gain: &str, key_peak: &str) -> Vec<(String, String)> {
+        let mut result = Vec::new();
+
+        // 無音などでラウドネスが求まらない場合はゲインを書き出さない
+        if let Some(gain) = self.gain {
+            result.push((String::from(key_gain), format!("{:.2} dB", gain)));
+        }
+        result.push((String::from(key_peak), format!("{:.6}", self.peak)));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_rem_entries() {
+        let replaygain = ReplayGain {
+            gain: Some(-7.891),
+            peak: 0.98855,
+        };
+        let silent = ReplayGain {
+            gain: None,
+            peak: 0.0,
+        };
+
+        assert_eq!(
+            replaygain.to_album_rem_entries(),
+            vec![
+                (
+                    String::from("REPLAYGAIN_ALBUM_GAIN"),
+                    String::from("-7.89 dB")
+                ),
+                (
+                    String::from("REPLAYGAIN_ALBUM_PEAK"),
+                    String::from("0.988550")
+                ),
+            ]
+        );
+        assert_eq!(
+            silent.to_track_rem_entries(),
+            vec![(
+                String::from("REPLAYGAIN_TRACK_PEAK"),
+                String::from("0.000000")
+            )]
+        );
+    }
+}
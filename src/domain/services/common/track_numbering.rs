@@ -0,0 +1,177 @@
+use crate::share::{Issue, IssueCode};
+
+// トラック番号の指定(ディスクの開始番号とトラックごとの番号)から開始番号を決める
+// * 番号は重複せず連続し、1から99の範囲に収まる必要がある
+pub struct TrackNumbering {}
+
+impl TrackNumbering {
+    const MIN_NUMBER: usize = 1;
+    const MAX_NUMBER: usize = 99;
+
+    // 開始番号の指定がなければ最初のトラックの番号、それもなければ1から振る
+    // * numbersはファイルごとのトラックの番号で、エラーの位置は /files/0/tracks/1/number の形式
+    // * first_number_pointerは開始番号を指定する項目の位置
+    pub fn resolve(
+        first_number: Option<usize>,
+        first_number_pointer: &str,
+        numbers: &[Vec<Option<usize>>],
+    ) -> Result<usize, Issue> {
+        let tracks: Vec<(String, Option<usize>)> = numbers
+            .iter()
+            .enumerate()
+            .flat_map(|(file_index, file)| {
+                file.iter().enumerate().map(move |(track_index, number)| {
+                    (
+                        format!("/files/{}/tracks/{}", file_index, track_index),
+                        *number,
+                    )
+                })
+            })
+            .collect();
+        let first = first_number
+            .or(tracks.first().and_then(|t| t.1))
+            .unwrap_or(Self::MIN_NUMBER);
+        if let Err(res) = Self::validate_range(first) {
+            return Err(match (first_number, tracks.first()) {
+                (None, Some((pointer, _))) => res.within(&format!("{}/number", pointer)),
+                _ => res.within(first_number_pointer),
+            });
+        }
+
+        for (index, (pointer, number)) in tracks.iter().enumerate() {
+            let expected = first + index;
+            let Some(number) = *number else {
+                continue;
+            };
+            let number_pointer = format!("{}/number", pointer);
+            Self::validate_range(number).map_err(|e| e.within(&number_pointer))?;
+            if (first..expected).contains(&number) {
+                return Err(Issue::new(
+                    IssueCode::TrackNumber,
+                    format!(
+                        "track number {} is duplicated (track in index={})",
+                        number, index
+                    ),
+                )
+                .within(&number_pointer));
+            }
+            if number != expected {
+                return Err(Issue::new(
+                    IssueCode::TrackNumber,
+                    format!(
+                        "track number {} is not contiguous, expected {} (track in index={})",
+                        number, expected, index
+                    ),
+                )
+                .within(&number_pointer));
+            }
+        }
+        if let Some(last) = tracks.len().checked_sub(1).map(|n| first + n)
+            && last > Self::MAX_NUMBER
+        {
+            // * 位置は範囲を超えた最初のトラック
+            let over = Self::MAX_NUMBER + 1 - first;
+            return Err(Issue::new(
+                IssueCode::TrackNumber,
+                format!("last track number {} exceeds {}", last, Self::MAX_NUMBER),
+            )
+            .within(&tracks[over].0));
+        }
+
+        Ok(first)
+    }
+
+    fn validate_range(number: usize) -> Result<(), Issue> {
+        if !(Self::MIN_NUMBER..=Self::MAX_NUMBER).contains(&number) {
+            return Err(Issue::new(
+                IssueCode::TrackNumber,
+                format!(
+                    "track number {} is out of range ({}-{})",
+                    number,
+                    Self::MIN_NUMBER,
+                    Self::MAX_NUMBER
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_NUMBER: &str = "/first_track_number";
+
+    #[test]
+    fn test_resolve() -> Result<(), String> {
+        assert_eq!(
+            TrackNumbering::resolve(None, FIRST_NUMBER, &[vec![None, None]])?,
+            1
+        );
+        assert_eq!(
+            TrackNumbering::resolve(Some(5), FIRST_NUMBER, &[vec![None, None]])?,
+            5
+        );
+        assert_eq!(
+            TrackNumbering::resolve(None, FIRST_NUMBER, &[vec![Some(5), None], vec![Some(7)]])?,
+            5
+        );
+        assert_eq!(
+            TrackNumbering::resolve(Some(98), FIRST_NUMBER, &[vec![None, Some(99)]])?,
+            98
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_err() {
+        let cases = [
+            (
+                Some(0),
+                vec![vec![None]],
+                "track number 0 is out of range (1-99)",
+                "/first_track_number",
+            ),
+            (
+                None,
+                vec![vec![Some(0)]],
+                "track number 0 is out of range (1-99)",
+                "/files/0/tracks/0/number",
+            ),
+            (
+                None,
+                vec![vec![Some(3)], vec![Some(3)]],
+                "track number 3 is duplicated (track in index=1)",
+                "/files/1/tracks/0/number",
+            ),
+            (
+                Some(5),
+                vec![vec![None, Some(7)]],
+                "track number 7 is not contiguous, expected 6 (track in index=1)",
+                "/files/0/tracks/1/number",
+            ),
+            (
+                Some(5),
+                vec![vec![Some(1)]],
+                "track number 1 is not contiguous, expected 5 (track in index=0)",
+                "/files/0/tracks/0/number",
+            ),
+            (
+                Some(99),
+                vec![vec![None], vec![None]],
+                "last track number 100 exceeds 99",
+                "/files/1/tracks/0",
+            ),
+        ];
+
+        for (first, numbers, expected, pointer) in cases {
+            let Err(result) = TrackNumbering::resolve(first, FIRST_NUMBER, &numbers) else {
+                panic!("invalid numbers are accepted: {:?} {:?}", first, numbers);
+            };
+            assert_eq!(result.message, expected);
+            assert_eq!(result.code, IssueCode::TrackNumber);
+            assert_eq!(result.pointer.as_deref(), Some(pointer));
+        }
+    }
+}
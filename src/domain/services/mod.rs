@@ -1 +1,3 @@
+pub mod common;
 pub mod v1;
+pub mod v2;
@@ -0,0 +1,42 @@
+use super::disc_set2cue::DiscSet2CueV1;
+use super::json2cue::Json2CueV1;
+use crate::domain::aggregates::common::Document;
+use crate::domain::aggregates::v1::json::{DiscSet, Json};
+use crate::domain::services::common::{Converted, CueConverter, FileCheck};
+use crate::share::Issue;
+
+pub struct ConverterV1 {}
+
+impl CueConverter for ConverterV1 {
+    fn version(&self) -> u8 {
+        Json2CueV1::VERSION
+    }
+
+    fn convert(&self, document: &Document, file_check: FileCheck) -> Result<Converted, Issue> {
+        let json: Json = match document.deserialize() {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res.prefixed(String::from("document is not parsable as v1\n")));
+            }
+        };
+        let mut json2cue = Json2CueV1::new(json);
+        json2cue.set_file_check(file_check);
+        json2cue.build_cue_with_warnings()
+    }
+
+    fn convert_set(
+        &self,
+        document: &Document,
+        file_check: FileCheck,
+    ) -> Result<Vec<Converted>, Issue> {
+        let disc_set: DiscSet = match document.deserialize() {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res.prefixed(String::from("document is not parsable as v1 disc set\n")));
+            }
+        };
+        let mut disc_set2cue = DiscSet2CueV1::new(disc_set);
+        disc_set2cue.set_file_check(file_check);
+        disc_set2cue.build_cues_with_warnings()
+    }
+}
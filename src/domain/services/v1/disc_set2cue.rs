@@ -0,0 +1,231 @@
+use indexmap::IndexMap;
+
+use super::json2cue::Json2CueV1;
+use crate::domain::aggregates::v1::json::{Disc, DiscSet, Info as JsonInfo, Json};
+use crate::domain::services::common::{Converted, FileCheck};
+use crate::share::{Issue, IssueCode};
+
+// ディスクセットをディスクごとのv1の文書に分けて変換する
+pub struct DiscSet2CueV1 {
+    disc_set: DiscSet,
+    file_check: FileCheck,
+}
+
+impl DiscSet2CueV1 {
+    const KEY_DISC_NUMBER: &str = "DISCNUMBER";
+    const KEY_TOTAL_DISCS: &str = "TOTALDISCS";
+
+    pub fn new(disc_set: DiscSet) -> DiscSet2CueV1 {
+        DiscSet2CueV1 {
+            disc_set,
+            file_check: FileCheck::default(),
+        }
+    }
+
+    pub fn set_file_check(&mut self, file_check: FileCheck) {
+        self.file_check = file_check;
+    }
+
+    // エラーと警告の位置は /discs/0 から始まる
+    pub fn build_cues_with_warnings(&self) -> Result<Vec<Converted>, Issue> {
+        if !Json2CueV1::is_parsable_version(self.disc_set.version) {
+            return Err(Issue::new(
+                IssueCode::Version,
+                format!("incompetible version: {}", self.disc_set.version),
+            )
+            .within("/version"));
+        }
+        if self.disc_set.discs.is_empty() {
+            return Err(Issue::from(String::from("disc set has no discs")).within("/discs"));
+        }
+
+        let total = self.disc_set.discs.len();
+        let mut result: Vec<Converted> = Vec::new();
+        for (index, disc) in self.disc_set.discs.iter().enumerate() {
+            let json = self.build_disc_json(disc);
+            let mut json2cue = Json2CueV1::new(json);
+            json2cue.set_file_check(self.file_check);
+            let mut converted = match json2cue.build_cue_with_warnings() {
+                Ok(res) => res,
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("/discs/{}", index))
+                        .prefixed(format!("disc in index={}: ", index)));
+                }
+            };
+            converted
+                .cue
+                .set_rem(String::from(Self::KEY_DISC_NUMBER), (index + 1).to_string());
+            converted
+                .cue
+                .set_rem(String::from(Self::KEY_TOTAL_DISCS), total.to_string());
+            converted.warnings = converted
+                .warnings
+                .into_iter()
+                .map(|w| {
+                    w.within(&format!("/discs/{}", index))
+                        .prefixed(format!("discs[{}].", index))
+                })
+                .collect();
+            result.push(converted);
+        }
+
+        Ok(result)
+    }
+
+    fn build_disc_json(&self, disc: &Disc) -> Json {
+        let disc_set = &self.disc_set;
+        Json {
+            schema: None,
+            version: disc_set.version,
+            title: disc.title.clone().unwrap_or(disc_set.title.clone()),
+            genre: disc.genre.clone().or(disc_set.genre.clone()),
+            info: Self::merge_info(disc_set.info.as_ref(), disc.info.as_ref()),
+            rem: Self::merge_rem(disc_set.rem.as_ref(), disc.rem.as_ref()),
+            first_track_number: disc.first_track_number,
+            htoa: disc.htoa.clone(),
+            files: disc.files.clone(),
+        }
+    }
+
+    // ディスクで省略した項目はディスクセットの値を使い、nullは消去する
+    fn merge_info(disc_set: Option<&JsonInfo>, disc: Option<&JsonInfo>) -> Option<JsonInfo> {
+        match (disc_set, disc) {
+            (None, None) => None,
+            (Some(info), None) | (None, Some(info)) => Some(info.clone()),
+            (Some(disc_set), Some(disc)) => Some(JsonInfo {
+                arranger: disc.arranger.clone().or(disc_set.arranger.clone()),
+                composer: disc.composer.clone().or(disc_set.composer.clone()),
+                lyricist: disc.lyricist.clone().or(disc_set.lyricist.clone()),
+                performer: disc.performer.clone().or(disc_set.performer.clone()),
+            }),
+        }
+    }
+
+    fn merge_rem(
+        disc_set: Option<&IndexMap<String, String>>,
+        disc: Option<&IndexMap<String, String>>,
+    ) -> Option<IndexMap<String, String>> {
+        match (disc_set, disc) {
+            (None, None) => None,
+            (disc_set, disc) => {
+                let mut result = disc_set.cloned().unwrap_or_default();
+                result.extend(disc.cloned().unwrap_or_default());
+                Some(result)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::json::{File as JsonFile, Track as JsonTrack};
+    use std::path::PathBuf;
+
+    fn build_disc(title: Option<&str>, info: Option<JsonInfo>) -> Disc {
+        Disc {
+            title: title.map(String::from),
+            genre: None,
+            info,
+            rem: Some(IndexMap::from([(
+                String::from("DATE"),
+                String::from("2025"),
+            )])),
+            first_track_number: None,
+            htoa: None,
+            files: vec![JsonFile {
+                path: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"),
+                file_type: String::from("WAVE"),
+                tracks: vec![JsonTrack {
+                    number: None,
+                    title: String::from("title"),
+                    info: None,
+                    rem: None,
+                    isrc: None,
+                    pregap: None,
+                    start_at: None,
+                }],
+            }],
+        }
+    }
+
+    fn build_disc_set(discs: Vec<Disc>) -> DiscSet {
+        DiscSet {
+            schema: None,
+            version: 1,
+            title: String::from("box"),
+            genre: Some(String::from("rock")),
+            info: Some(JsonInfo {
+                performer: Some(Some(String::from("band"))),
+                composer: Some(Some(String::from("comp"))),
+                ..JsonInfo::default()
+            }),
+            rem: Some(IndexMap::from([
+                (String::from("DATE"), String::from("2024")),
+                (String::from("LABEL"), String::from("label")),
+            ])),
+            discs,
+        }
+    }
+
+    #[test]
+    fn test_build_cues_with_warnings() -> Result<(), String> {
+        let disc_set = build_disc_set(vec![
+            build_disc(None, None),
+            build_disc(
+                Some("bonus"),
+                Some(JsonInfo {
+                    composer: Some(None),
+                    ..JsonInfo::default()
+                }),
+            ),
+        ]);
+
+        let result = DiscSet2CueV1::new(disc_set).build_cues_with_warnings()?;
+
+        assert_eq!(result.len(), 2);
+        let second = &result[1].cue;
+        assert_eq!(result[0].cue.title(), "box");
+        assert_eq!(second.title(), "bonus");
+        assert_eq!(second.genre(), Some(&String::from("rock")));
+        assert_eq!(
+            second.info().and_then(|i| i.performer()),
+            Some(&String::from("band"))
+        );
+        assert_eq!(second.info().and_then(|i| i.composer()), None);
+        assert_eq!(
+            second.rem().entries(),
+            &vec![
+                (String::from("DATE"), String::from("2025")),
+                (String::from("LABEL"), String::from("label")),
+                (String::from("DISCNUMBER"), String::from("2")),
+                (String::from("TOTALDISCS"), String::from("2")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_cues_with_warnings_err() {
+        let empty = build_disc_set(Vec::new());
+        let mut invalid_disc = build_disc(None, None);
+        invalid_disc.files[0].file_type = String::from("MP3");
+        let invalid = build_disc_set(vec![build_disc(None, None), invalid_disc]);
+
+        let (Err(empty), Err(invalid)) = (
+            DiscSet2CueV1::new(empty).build_cues_with_warnings(),
+            DiscSet2CueV1::new(invalid).build_cues_with_warnings(),
+        ) else {
+            panic!("invalid disc set is accepted");
+        };
+
+        assert_eq!(empty.message, "disc set has no discs");
+        assert!(
+            invalid.message.starts_with("disc in index=1: "),
+            "{}",
+            invalid
+        );
+        assert_eq!(invalid.pointer.as_deref(), Some("/discs/1/files/0/type"));
+    }
+}
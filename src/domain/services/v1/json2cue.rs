@@ -1,134 +1,360 @@
-use std::fs;
+use indexmap::IndexMap;
 use std::path;
 
 use crate::domain::aggregates::v1::cue::{
-    Cue, File as CueFile, FileType as CueFileType, Info as CueInfo, Pregap as CuePregap,
-    PregapType as CuePregapType, Time as CueTime, Track as CueTrack,
+    Cue, File as CueFile, FileType as CueFileType, Header, HiddenAudio, Info as CueInfo,
+    Pregap as CuePregap, PregapType as CuePregapType, Rem as CueRem, Time as CueTime,
+    Track as CueTrack,
 };
 use crate::domain::aggregates::v1::json::{
-    File as JsonFile, Info as JsonInfo, Json, Track as JsonTrack,
+    File as JsonFile, Htoa as JsonHtoa, Info as JsonInfo, Json, Pregap as JsonPregap,
+    TimeValue as JsonTimeValue, Track as JsonTrack,
 };
+use crate::domain::services::common::{Converted, FileCheck, FilePath, TrackNumbering};
+use crate::domain::services::v1::TimeNotation;
+use crate::share::util::TextStyle;
+use crate::share::{Issue, IssueCode};
 
 pub struct Json2CueV1 {
     json: Json,
+    file_check: FileCheck,
+}
+
+#[derive(Debug, Default)]
+pub struct BuildOptions {
+    pub header: Header,
+    pub style: TextStyle,
 }
 
 impl Json2CueV1 {
-    const VERSION: u8 = 1;
+    pub const VERSION: u8 = 1;
     const FILE_TYPE: &str = "WAVE";
     const PREGAP_TYPE_INCLUDED: &str = "included";
     const PREGAP_TYPE_SILENT: &str = "silent";
+    const PREGAP_TYPE_APPENDED: &str = "appended";
 
     pub fn new(json: Json) -> Json2CueV1 {
-        Json2CueV1 { json }
+        Json2CueV1 {
+            json,
+            file_check: FileCheck::default(),
+        }
+    }
+
+    pub fn set_file_check(&mut self, file_check: FileCheck) {
+        self.file_check = file_check;
     }
 
     pub fn is_parsable_version(version: u8) -> bool {
         version == Self::VERSION
     }
 
-    pub fn build(&self) -> Result<String, String> {
+    pub fn build(&self, options: &BuildOptions) -> Result<String, Issue> {
+        let cue = self.build_cue()?;
+
+        Ok(cue.to_cdtext_string(&options.header, &options.style)?)
+    }
+
+    pub fn build_cue(&self) -> Result<Cue, Issue> {
+        Ok(self.build_cue_with_warnings()?.cue)
+    }
+
+    // フレーム境界にない時間などは警告として返す
+    pub fn build_cue_with_warnings(&self) -> Result<Converted, Issue> {
         if !Self::is_parsable_version(self.json.version) {
-            return Err(format!("incompetible version: {}", self.json.version));
+            return Err(Issue::new(
+                IssueCode::Version,
+                format!("incompetible version: {}", self.json.version),
+            )
+            .within("/version"));
         };
 
         let title = self.json.title.clone();
         let genre = self.json.genre.clone();
         let info = Self::build_info(&self.json.info);
-        let files = match Self::build_files(&self.json.files) {
+        let rem = match Self::build_rem(&self.json.rem) {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res
+                    .within("/rem")
+                    .prefixed(String::from("rem is not parsable\n")));
+            }
+        };
+        let mut warnings: Vec<Issue> = Vec::new();
+        let json_files = match &self.json.htoa {
+            Some(htoa) => match Self::build_htoa_files(htoa, &self.json.files, &mut warnings) {
+                Ok(res) => res,
+                Err(res) => return Err(res.within("/htoa").prefixed(String::from("htoa: "))),
+            },
+            None => self.json.files.clone(),
+        };
+        let files = match self.build_files(&json_files, info.as_ref(), &mut warnings) {
             Ok(res) => res,
             Err(res) => {
                 return Err(res);
             }
         };
-        let cue = Cue::new(title, genre, info, files);
+        let mut cue = Cue::new(title, genre, info, rem, files);
+        let first_track_number = Self::build_first_track_number(&self.json)?;
+        if let Err(res) = cue.set_first_track_number(first_track_number) {
+            return Err(Issue::new(IssueCode::TrackNumber, res).within("/first_track_number"));
+        }
+        cue.validate_appended_pregaps("/pregap")?;
+        if let Some(htoa) = &self.json.htoa
+            && let Some(path) = &htoa.path
+        {
+            let hidden_audio = match self.build_hidden_audio(path, htoa.file_type.as_deref()) {
+                Ok(res) => res,
+                Err(res) => return Err(res.within("/htoa").prefixed(String::from("htoa: "))),
+            };
+            if let Err(res) =
+                cue.files_mut()[0].tracks_mut()[0].set_hidden_audio(Some(hidden_audio))
+            {
+                return Err(Issue::from(res).within("/htoa"));
+            }
+        }
+        cue.validate_hidden_audio()?;
+        Ok(Converted { cue, warnings })
+    }
 
-        cue.to_cdtext_string()
+    // トラック1は隠し音声をプリギャップにするため、PREGAPとstart_atは指定できない
+    // * durationの場合は最初のファイルに含まれるプリギャップとして扱う
+    // * エラーと警告の位置はhtoaの中の位置
+    pub fn build_htoa_files(
+        htoa: &JsonHtoa,
+        files: &[JsonFile],
+        warnings: &mut Vec<Issue>,
+    ) -> Result<Vec<JsonFile>, Issue> {
+        let Some(track) = files.first().and_then(|f| f.tracks.first()) else {
+            return Err(Issue::from(String::from(
+                "hidden audio requires at least one track",
+            )));
+        };
+        if track.pregap.is_some() {
+            return Err(Issue::from(String::from(
+                "hidden audio cannot be combined with the pregap of the first track",
+            )));
+        }
+        if track.start_at.is_some() {
+            return Err(Issue::from(String::from(
+                "hidden audio cannot be combined with start_at of the first track",
+            )));
+        }
+        let mut result = files.to_vec();
+        match (&htoa.path, &htoa.duration) {
+            (Some(_), None) => (),
+            (None, Some(duration)) => {
+                if htoa.file_type.is_some() {
+                    return Err(
+                        Issue::from(String::from("type is only allowed with path")).within("/type")
+                    );
+                }
+                let (time, warning) =
+                    Self::build_time(duration).map_err(|e| e.within("/duration"))?;
+                if let Some(warning) = warning {
+                    warnings.push(
+                        warning
+                            .within("/duration")
+                            .prefixed(String::from("htoa.duration: ")),
+                    );
+                }
+                if time.to_frame() == 0 {
+                    return Err(
+                        Issue::new(IssueCode::Time, String::from("duration must be >0"))
+                            .within("/duration"),
+                    );
+                }
+                result[0].tracks[0].pregap = Some(JsonPregap {
+                    duration: JsonTimeValue::Msf(time.to_vec()),
+                    pregap_type: String::from(Self::PREGAP_TYPE_INCLUDED),
+                });
+            }
+            _ => {
+                return Err(Issue::from(String::from(
+                    "either path or duration is required",
+                )));
+            }
+        }
+        Ok(result)
     }
 
-    fn build_info(info: &Option<JsonInfo>) -> Option<CueInfo> {
-        match info {
-            None => None,
-            Some(res) => Some(Self::build_confirmed_info(res)),
+    fn build_hidden_audio(
+        &self,
+        path: &path::Path,
+        file_type: Option<&str>,
+    ) -> Result<HiddenAudio, Issue> {
+        let file_type = file_type.unwrap_or(Self::FILE_TYPE);
+        if file_type != Self::FILE_TYPE {
+            return Err(Issue::new(
+                IssueCode::Schema,
+                format!("file type {} is unknown (path={:?})", file_type, path),
+            )
+            .within("/type"));
         }
+        Ok(HiddenAudio {
+            path: FilePath::resolve(path, self.file_check).map_err(|e| e.within("/path"))?,
+            file_type: CueFileType::Wave,
+        })
     }
 
-    fn build_confirmed_info(info: &JsonInfo) -> CueInfo {
+    fn build_first_track_number(json: &Json) -> Result<usize, Issue> {
+        let numbers: Vec<Vec<Option<usize>>> = json
+            .files
+            .iter()
+            .map(|f| f.tracks.iter().map(|t| t.number.map(usize::from)).collect())
+            .collect();
+        TrackNumbering::resolve(
+            json.first_track_number.map(usize::from),
+            "/first_track_number",
+            &numbers,
+        )
+    }
+
+    // アルバムではnullと空文字列は省略と同じ
+    fn build_info(info: &Option<JsonInfo>) -> Option<CueInfo> {
+        let flatten =
+            |value: &Option<Option<String>>| value.clone().flatten().filter(|v| !v.is_empty());
+        info.as_ref().map(|info| {
+            CueInfo::new(
+                flatten(&info.arranger),
+                flatten(&info.composer),
+                flatten(&info.lyricist),
+                flatten(&info.performer),
+            )
+        })
+    }
+
+    // トラックで省略した項目はアルバムの値を引き継ぎ(プレイヤーがフォールバックする)、
+    // nullまたは空文字列の項目は空文字列として書き出して消去する
+    fn build_track_info(info: &JsonInfo, album_info: Option<&CueInfo>) -> CueInfo {
+        let resolve = |value: &Option<Option<String>>, album_value: Option<&String>| match value {
+            None => None,
+            Some(Some(value)) if !value.is_empty() => Some(value.clone()),
+            // * アルバムに値がなければ消去するものもない
+            Some(_) => album_value.map(|_| String::new()),
+        };
         CueInfo::new(
-            info.arranger.clone(),
-            info.composer.clone(),
-            info.lyricist.clone(),
-            info.performer.clone(),
+            resolve(&info.arranger, album_info.and_then(|i| i.arranger())),
+            resolve(&info.composer, album_info.and_then(|i| i.composer())),
+            resolve(&info.lyricist, album_info.and_then(|i| i.lyricist())),
+            resolve(&info.performer, album_info.and_then(|i| i.performer())),
         )
     }
 
-    fn build_files(files: &Vec<JsonFile>) -> Result<Vec<CueFile>, String> {
+    // エラーの位置はキーの位置
+    fn build_rem(rem: &Option<IndexMap<String, String>>) -> Result<CueRem, Issue> {
+        let mut result = CueRem::default();
+
+        let Some(rem) = rem else {
+            return Ok(result);
+        };
+        for (key, value) in rem.iter() {
+            if let Err(res) = CueRem::validate_key(key) {
+                return Err(Issue::from(res).within(&Issue::segment(key)));
+            }
+            result.push(key.clone(), value.clone());
+        }
+
+        Ok(result)
+    }
+
+    fn build_files(
+        &self,
+        files: &[JsonFile],
+        album_info: Option<&CueInfo>,
+        warnings: &mut Vec<Issue>,
+    ) -> Result<Vec<CueFile>, Issue> {
         let mut result: Vec<CueFile> = Vec::new();
 
         for (index, file) in files.iter().enumerate() {
+            let pointer = format!("/files/{}", index);
             if file.file_type != Self::FILE_TYPE {
-                return Err(format!(
-                    "file type {} is unknown in index {}(path={:?})",
-                    file.file_type, index, file.path
-                ));
+                return Err(Issue::new(
+                    IssueCode::Schema,
+                    format!(
+                        "file type {} is unknown in index {}(path={:?})",
+                        file.file_type, index, file.path
+                    ),
+                )
+                .within(&format!("{}/type", pointer)));
             }
-            let Ok(abs_file_path_buf) = path::absolute(&file.path) else {
-                return Err(format!("file path({:?}) is not parsable", file.path));
-            };
-            match fs::exists(&abs_file_path_buf) {
-                Err(d) => return Err(format!("{}", d)),
-                Ok(false) => return Err(format!("file ({:?}) does not exist", file.path)),
-                Ok(true) => (),
-            };
-            let Some(abs_file_path) = abs_file_path_buf.to_str() else {
-                return Err(format!("file path({:?}) is not parsable", file.path));
-            };
-            let tracks = match Self::build_tracks(&file.tracks) {
+            let abs_file_path = FilePath::resolve(&file.path, self.file_check)
+                .map_err(|e| e.within(&format!("{}/path", pointer)))?;
+            let mut track_warnings: Vec<Issue> = Vec::new();
+            let tracks = match Self::build_tracks(&file.tracks, album_info, &mut track_warnings) {
                 Ok(res) => res,
                 Err(res) => {
-                    return Err(format!(
-                        "tracks are not parsable in index={}\n{}",
-                        index, res
-                    ));
+                    return Err(res
+                        .within(&format!("{}/tracks", pointer))
+                        .prefixed(format!("tracks are not parsable in index={}\n", index)));
                 }
             };
-            result.push(CueFile::new(
-                String::from(abs_file_path),
-                tracks,
-                CueFileType::Wave,
-            ));
+            warnings.extend(track_warnings.into_iter().map(|w| {
+                w.within(&format!("{}/tracks", pointer))
+                    .prefixed(format!("files[{}].", index))
+            }));
+            result.push(CueFile::new(abs_file_path, tracks, CueFileType::Wave));
         }
 
         Ok(result)
     }
 
-    fn build_tracks(tracks: &Vec<JsonTrack>) -> Result<Vec<CueTrack>, String> {
+    // エラーと警告の位置はトラックの配列の中の位置
+    fn build_tracks(
+        tracks: &[JsonTrack],
+        album_info: Option<&CueInfo>,
+        warnings: &mut Vec<Issue>,
+    ) -> Result<Vec<CueTrack>, Issue> {
         let mut result: Vec<CueTrack> = Vec::new();
 
         for (index, track) in tracks.iter().enumerate() {
+            let pointer = format!("/{}", index);
             let title = track.title.clone();
-            let info = match &track.info {
-                Some(info) => Some(Self::build_confirmed_info(info)),
-                None => None,
+            let info = track
+                .info
+                .as_ref()
+                .map(|info| Self::build_track_info(info, album_info));
+            let rem = match Self::build_rem(&track.rem) {
+                Ok(res) => res,
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("{}/rem", pointer))
+                        .prefixed(format!("rem in index={} is not parsable\n", index)));
+                }
             };
             let pregap = if let Some(pregap) = &track.pregap {
                 let pregap_type: CuePregapType = match pregap.pregap_type.as_str() {
                     Self::PREGAP_TYPE_INCLUDED => CuePregapType::Included,
                     Self::PREGAP_TYPE_SILENT => CuePregapType::Silent,
+                    Self::PREGAP_TYPE_APPENDED => CuePregapType::Appended,
                     _ => {
-                        return Err(format!(
-                            "unknown pregap_type in index={}: {}",
-                            index, pregap.pregap_type
-                        ));
+                        return Err(Issue::new(
+                            IssueCode::Schema,
+                            format!(
+                                "unknown pregap_type in index={}: {}",
+                                index, pregap.pregap_type
+                            ),
+                        )
+                        .within(&format!("{}/pregap/type", pointer)));
                     }
                 };
-                let duration = match CueTime::from_vec(&pregap.duration) {
-                    Ok(res) => res,
+                let duration_pointer = format!("{}/pregap/duration", pointer);
+                let duration = match Self::build_time(&pregap.duration) {
+                    Ok((res, warning)) => {
+                        if let Some(warning) = warning {
+                            warnings.push(
+                                warning
+                                    .within(&duration_pointer)
+                                    .prefixed(format!("tracks[{}].pregap.duration: ", index)),
+                            );
+                        }
+                        res
+                    }
                     Err(res) => {
-                        return Err(format!(
-                            "pregap in index={} is not parsable: {:?}\n{}",
-                            index, pregap.duration, res
-                        ));
+                        return Err(res.within(&duration_pointer).prefixed(format!(
+                            "pregap in index={} is not parsable: {}\n",
+                            index,
+                            TimeNotation::describe(&pregap.duration)
+                        )));
                     }
                 };
                 CuePregap {
@@ -137,10 +363,12 @@ impl Json2CueV1 {
                 }
             } else {
                 let pregap_type = CuePregapType::Silent;
-                let duration = match CueTime::from_vec(&vec![0, 0, 0]) {
+                let duration = match CueTime::from_vec(&[0, 0, 0]) {
                     Ok(res) => res,
                     Err(_) => {
-                        return Err(String::from("unknown error occured in json2cue"));
+                        return Err(Issue::from(String::from(
+                            "unknown error occured in json2cue",
+                        )));
                     }
                 };
                 CuePregap {
@@ -149,29 +377,201 @@ impl Json2CueV1 {
                 }
             };
             let start_at = if let Some(start_at) = &track.start_at {
-                match CueTime::from_vec(&start_at) {
-                    Ok(res) => res,
+                let start_at_pointer = format!("{}/start_at", pointer);
+                match Self::build_time(start_at) {
+                    Ok((res, warning)) => {
+                        if let Some(warning) = warning {
+                            warnings.push(
+                                warning
+                                    .within(&start_at_pointer)
+                                    .prefixed(format!("tracks[{}].start_at: ", index)),
+                            );
+                        }
+                        res
+                    }
                     Err(res) => {
-                        return Err(format!(
-                            "start_at in index={} is not parsable: {:?}\n{}",
-                            index, start_at, res
-                        ));
+                        return Err(res.within(&start_at_pointer).prefixed(format!(
+                            "start_at in index={} is not parsable: {}\n",
+                            index,
+                            TimeNotation::describe(start_at)
+                        )));
                     }
                 }
             } else {
-                match CueTime::from_vec(&vec![0, 0, 0]) {
+                match CueTime::from_vec(&[0, 0, 0]) {
                     Ok(res) => res,
                     Err(_) => {
-                        return Err(String::from("unknown error occured in json2cue"));
+                        return Err(Issue::from(String::from(
+                            "unknown error occured in json2cue",
+                        )));
                     }
                 }
             };
-            result.push(CueTrack::new(title, info, pregap, start_at));
+            let isrc = match &track.isrc {
+                Some(isrc) => match CueTrack::<CueInfo>::validate_isrc(isrc) {
+                    Ok(_) => Some(isrc.clone()),
+                    Err(res) => {
+                        return Err(Issue::from(format!("isrc in index={}: {}", index, res))
+                            .within(&format!("{}/isrc", pointer)));
+                    }
+                },
+                None => None,
+            };
+            result.push(CueTrack::new(title, info, rem, isrc, pregap, start_at));
         }
 
         Ok(result)
     }
+
+    // フレーム境界にない場合は丸めた上で警告を返す
+    fn build_time(value: &JsonTimeValue) -> Result<(CueTime, Option<Issue>), Issue> {
+        let parsed = TimeNotation::parse(value)?;
+        let warning = if parsed.aligned {
+            None
+        } else {
+            Some(TimeNotation::not_aligned_warning(value, &parsed))
+        };
+        Ok((parsed.time, warning))
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_track_info() {
+        let album = CueInfo::new(
+            None,
+            Some(String::from("comp")),
+            Some(String::from("lyr")),
+            Some(String::from("band")),
+        );
+        let track = JsonInfo {
+            arranger: Some(None),
+            composer: Some(None),
+            lyricist: Some(Some(String::new())),
+            performer: Some(Some(String::from("guest"))),
+        };
+
+        let result = Json2CueV1::build_track_info(&track, Some(&album));
+        let inherited = Json2CueV1::build_track_info(&JsonInfo::default(), Some(&album));
+
+        assert_eq!(
+            result,
+            CueInfo::new(
+                None,
+                Some(String::new()),
+                Some(String::new()),
+                Some(String::from("guest")),
+            )
+        );
+        assert_eq!(inherited, CueInfo::new(None, None, None, None));
+    }
+
+    #[test]
+    fn test_build_rem_keeps_order() -> Result<(), String> {
+        let rem: Option<IndexMap<String, String>> = serde_json::from_str(
+            "{\"DATE\": \"2025\", \"COMMENT\": \"a\", \"CATALOG_NOTE\": \"b\"}",
+        )
+        .map_err(|e| e.to_string())?;
+
+        let result = Json2CueV1::build_rem(&rem)?;
+
+        assert_eq!(
+            result
+                .entries()
+                .iter()
+                .map(|(k, _)| k.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["DATE", "COMMENT", "CATALOG_NOTE"]
+        );
+        Ok(())
+    }
+
+    fn build_json_files(pregap: bool) -> Vec<JsonFile> {
+        vec![JsonFile {
+            path: path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"),
+            file_type: String::from("WAVE"),
+            tracks: vec![JsonTrack {
+                number: None,
+                title: String::from("title"),
+                info: None,
+                rem: None,
+                isrc: None,
+                pregap: pregap.then(|| JsonPregap {
+                    duration: JsonTimeValue::Seconds(2.0),
+                    pregap_type: String::from("silent"),
+                }),
+                start_at: None,
+            }],
+        }]
+    }
+
+    #[test]
+    fn test_build_htoa_files() -> Result<(), String> {
+        let htoa = JsonHtoa {
+            path: None,
+            file_type: None,
+            duration: Some(JsonTimeValue::Text(String::from("01:02.005"))),
+        };
+        let mut warnings: Vec<Issue> = Vec::new();
+
+        let result = Json2CueV1::build_htoa_files(&htoa, &build_json_files(false), &mut warnings)?;
+
+        let Some(pregap) = &result[0].tracks[0].pregap else {
+            return Err(String::from("pregap is not set"));
+        };
+        assert_eq!(pregap.duration, JsonTimeValue::Msf(vec![1, 2, 0]));
+        assert_eq!(pregap.pregap_type, "included");
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| w.message.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["htoa.duration: \"01:02.005\" is not frame-aligned, rounded to 01:02:00"]
+        );
+        assert_eq!(warnings[0].pointer.as_deref(), Some("/duration"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_htoa_files_err() {
+        let both = JsonHtoa {
+            path: Some(path::PathBuf::from("Cargo.toml")),
+            file_type: None,
+            duration: Some(JsonTimeValue::Seconds(1.0)),
+        };
+        let zero = JsonHtoa {
+            path: None,
+            file_type: None,
+            duration: Some(JsonTimeValue::Seconds(0.0)),
+        };
+        let cases = [
+            (
+                &both,
+                build_json_files(false),
+                "either path or duration is required",
+            ),
+            (&zero, build_json_files(false), "duration must be >0"),
+            (
+                &zero,
+                build_json_files(true),
+                "hidden audio cannot be combined with the pregap of the first track",
+            ),
+            (
+                &zero,
+                Vec::new(),
+                "hidden audio requires at least one track",
+            ),
+        ];
+
+        for (htoa, files, expected) in cases {
+            let result = Json2CueV1::build_htoa_files(htoa, &files, &mut Vec::new());
+            assert_eq!(
+                result.err().map(|e| e.message),
+                Some(String::from(expected))
+            );
+        }
+    }
+}
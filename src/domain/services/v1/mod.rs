@@ -1,3 +1,11 @@
+pub mod converter;
+pub mod disc_set2cue;
 pub mod json2cue;
+pub mod sheet2cue;
+pub mod time_notation;
 
+pub use converter::*;
+pub use disc_set2cue::*;
 pub use json2cue::*;
+pub use sheet2cue::*;
+pub use time_notation::*;
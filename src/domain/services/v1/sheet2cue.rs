@@ -0,0 +1,587 @@
+use crate::domain::aggregates::v1::cue::{
+    Cue, File as CueFile, FileType as CueFileType, HiddenAudio, Info as CueInfo,
+    Pregap as CuePregap, PregapType as CuePregapType, Rem as CueRem, Time as CueTime,
+    Track as CueTrack, TrackMode as CueTrackMode,
+};
+use crate::domain::services::common::Converted;
+use crate::share::Issue;
+
+// 書き出したcue sheet(と一般的なcue sheet)をCueに読み込む
+// * FILEのパスは書かれたまま(存在は確認しない)
+// * 前のファイルの末尾にあるプリギャップは長さが分からないため、INDEX 00の位置のみを持つ
+pub struct CueSheet2Cue {}
+
+#[derive(Default)]
+struct PendingTrack {
+    line: usize,
+    number: usize,
+    mode: Option<CueTrackMode>,
+    title: Option<String>,
+    arranger: Option<String>,
+    composer: Option<String>,
+    lyricist: Option<String>,
+    performer: Option<String>,
+    rem: Vec<(String, String)>,
+    isrc: Option<String>,
+    pregap: Option<CueTime>,
+    // (ファイルの位置, 時間)
+    index_00: Option<(usize, CueTime)>,
+    index_01: Option<(usize, CueTime)>,
+    extra_indexes: Vec<(u8, CueTime)>,
+}
+
+#[derive(Default)]
+struct Disc {
+    title: Option<String>,
+    genre: Option<String>,
+    catalog: Option<String>,
+    arranger: Option<String>,
+    composer: Option<String>,
+    lyricist: Option<String>,
+    performer: Option<String>,
+    rem: Vec<(String, String)>,
+}
+
+impl CueSheet2Cue {
+    const KEY_REM_GENRE: &str = "GENRE";
+    const KEY_REM_COMPOSER: &str = "COMPOSER";
+    const KEY_REM_ARRANGER: &str = "ARRANGER";
+    const KEY_REM_LYRICIST: &str = "LYRICIST";
+    // 生成したcue sheetのヘッダー
+    const KEY_REM_CDRTOOLS: &str = "CDRTOOLS";
+    const IGNORED_KEYS: [&str; 3] = ["FLAGS", "POSTGAP", "CDTEXTFILE"];
+
+    pub fn parse(source: &str) -> Result<Converted, Issue> {
+        let mut disc = Disc::default();
+        let mut files: Vec<(String, CueFileType)> = Vec::new();
+        let mut tracks: Vec<PendingTrack> = Vec::new();
+        let mut warnings: Vec<Issue> = Vec::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let key = key.to_ascii_uppercase();
+            let rest = rest.trim();
+            let result = match tracks.last_mut() {
+                None => Self::parse_disc_line(&key, rest, &mut disc),
+                Some(track) => Self::parse_track_line(&key, rest, track, files.len()),
+            };
+            let parsed = match result {
+                Ok(res) => res,
+                Err(res) => return Err(Self::at_line(line_number, res)),
+            };
+            if parsed {
+                continue;
+            }
+            match key.as_str() {
+                "FILE" => match Self::parse_file(rest) {
+                    Ok(res) => files.push(res),
+                    Err(res) => return Err(Self::at_line(line_number, res)),
+                },
+                "TRACK" => {
+                    let expected = tracks.last().map(|t| t.number + 1);
+                    match Self::parse_track(rest, expected) {
+                        Ok((number, mode)) => tracks.push(PendingTrack {
+                            line: line_number,
+                            number,
+                            mode,
+                            ..PendingTrack::default()
+                        }),
+                        Err(res) => return Err(Self::at_line(line_number, res)),
+                    }
+                    if files.is_empty() {
+                        return Err(Self::at_line(
+                            line_number,
+                            String::from("TRACK before FILE"),
+                        ));
+                    }
+                }
+                _ if Self::IGNORED_KEYS.contains(&key.as_str()) => {
+                    warnings.push(Self::at_line(line_number, format!("{} is ignored", key)));
+                }
+                _ => {
+                    return Err(Self::at_line(
+                        line_number,
+                        format!("unknown command: {}", key),
+                    ));
+                }
+            }
+        }
+
+        Self::build_cue(disc, files, tracks, warnings)
+    }
+
+    // ディスクの項目として読めた場合はtrue
+    fn parse_disc_line(key: &str, rest: &str, disc: &mut Disc) -> Result<bool, String> {
+        match key {
+            "TITLE" => disc.title = Some(Self::unquote(rest)),
+            "PERFORMER" => disc.performer = Some(Self::unquote(rest)),
+            "SONGWRITER" => disc.lyricist = Some(Self::unquote(rest)),
+            "CATALOG" => {
+                Cue::<CueInfo>::validate_catalog(rest)?;
+                disc.catalog = Some(String::from(rest));
+            }
+            "REM" => {
+                let (rem_key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let value = value.trim();
+                match rem_key {
+                    Self::KEY_REM_CDRTOOLS => {}
+                    Self::KEY_REM_GENRE => disc.genre = Some(Self::unquote(value)),
+                    Self::KEY_REM_COMPOSER => disc.composer = Some(Self::unquote(value)),
+                    Self::KEY_REM_ARRANGER => disc.arranger = Some(Self::unquote(value)),
+                    Self::KEY_REM_LYRICIST => disc.lyricist = Some(Self::unquote(value)),
+                    // * それ以外のREMは書き出しと同じく値をそのまま持つ
+                    _ => disc.rem.push((String::from(rem_key), String::from(value))),
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // トラックの項目として読めた場合はtrue
+    fn parse_track_line(
+        key: &str,
+        rest: &str,
+        track: &mut PendingTrack,
+        file_count: usize,
+    ) -> Result<bool, String> {
+        match key {
+            "TITLE" => track.title = Some(Self::unquote(rest)),
+            "PERFORMER" => track.performer = Some(Self::unquote(rest)),
+            "SONGWRITER" => track.lyricist = Some(Self::unquote(rest)),
+            "ISRC" => {
+                CueTrack::<CueInfo>::validate_isrc(rest)?;
+                track.isrc = Some(String::from(rest));
+            }
+            "PREGAP" => track.pregap = Some(Self::parse_time(rest)?),
+            "INDEX" => {
+                let (number, time) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let Ok(number) = number.parse::<u8>() else {
+                    return Err(format!("INDEX number is not parsable: {}", number));
+                };
+                let time = Self::parse_time(time.trim())?;
+                // * INDEXはその時点のFILE(最後のFILE)の位置
+                let file_index = file_count - 1;
+                match number {
+                    0 => track.index_00 = Some((file_index, time)),
+                    1 => track.index_01 = Some((file_index, time)),
+                    _ => track.extra_indexes.push((number, time)),
+                }
+            }
+            "REM" => {
+                let (rem_key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let value = value.trim();
+                match rem_key {
+                    Self::KEY_REM_COMPOSER => track.composer = Some(Self::unquote(value)),
+                    Self::KEY_REM_ARRANGER => track.arranger = Some(Self::unquote(value)),
+                    Self::KEY_REM_LYRICIST => track.lyricist = Some(Self::unquote(value)),
+                    _ => track.rem.push((String::from(rem_key), String::from(value))),
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // FILE "path" TYPE(パスの引用符は行の最後の引用符まで)
+    fn parse_file(rest: &str) -> Result<(String, CueFileType), String> {
+        let (path, file_type) = match rest.strip_prefix('"').and_then(|r| r.rsplit_once('"')) {
+            Some((path, file_type)) => (path, file_type.trim()),
+            None => rest.rsplit_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
+        let Some(file_type) = CueFileType::from_keyword(&file_type.to_ascii_uppercase()) else {
+            return Err(format!("file type {} is unknown", file_type));
+        };
+        Ok((String::from(path), file_type))
+    }
+
+    fn parse_track(
+        rest: &str,
+        expected: Option<usize>,
+    ) -> Result<(usize, Option<CueTrackMode>), String> {
+        let (number, mode) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let Ok(number) = number.parse::<usize>() else {
+            return Err(format!("track number is not parsable: {}", number));
+        };
+        if number == 0 || number > Cue::<CueInfo>::MAX_TRACK_NUMBER {
+            return Err(format!(
+                "track number {} is out of range (1-{})",
+                number,
+                Cue::<CueInfo>::MAX_TRACK_NUMBER
+            ));
+        }
+        if let Some(expected) = expected
+            && number != expected
+        {
+            return Err(format!(
+                "track number {} is not contiguous, expected {}",
+                number, expected
+            ));
+        }
+        let mode = mode.trim().to_ascii_uppercase();
+        match CueTrackMode::from_keyword(&mode) {
+            Some(res) => Ok((number, Some(res))),
+            None => Err(format!("track mode {} is unknown", mode)),
+        }
+    }
+
+    fn parse_time(value: &str) -> Result<CueTime, String> {
+        let parts: Vec<Result<u8, _>> = value.split(':').map(str::parse::<u8>).collect();
+        let parts: Vec<u8> = match parts.into_iter().collect::<Result<Vec<u8>, _>>() {
+            Ok(res) if res.len() == 3 => res,
+            _ => return Err(format!("time is not parsable: {} (use mm:ss:ff)", value)),
+        };
+        CueTime::from_vec(&parts)
+    }
+
+    fn unquote(value: &str) -> String {
+        match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(res) => String::from(res),
+            None => String::from(value),
+        }
+    }
+
+    fn build_info(
+        arranger: Option<String>,
+        composer: Option<String>,
+        lyricist: Option<String>,
+        performer: Option<String>,
+    ) -> Option<CueInfo> {
+        if arranger.is_none() && composer.is_none() && lyricist.is_none() && performer.is_none() {
+            return None;
+        }
+        Some(CueInfo::new(arranger, composer, lyricist, performer))
+    }
+
+    fn build_cue(
+        disc: Disc,
+        files: Vec<(String, CueFileType)>,
+        tracks: Vec<PendingTrack>,
+        mut warnings: Vec<Issue>,
+    ) -> Result<Converted, Issue> {
+        let Some(first_track_number) = tracks.first().map(|t| t.number) else {
+            return Err(Issue::from(String::from("cue sheet has no tracks")));
+        };
+        let mut file_tracks: Vec<Vec<CueTrack>> = files.iter().map(|_| Vec::new()).collect();
+        let mut hidden_audio: Option<HiddenAudio> = None;
+
+        for (track_index, pending) in tracks.into_iter().enumerate() {
+            let line = pending.line;
+            let Some((file_index, index_01)) = pending.index_01 else {
+                return Err(Self::at_line(
+                    line,
+                    format!("track {:02} has no INDEX 01", pending.number),
+                ));
+            };
+            let zero = CueTime::from_frame(0)?;
+            let (pregap, start_at, appended_index_00) = match (pending.pregap, pending.index_00) {
+                (Some(_), Some(_)) => {
+                    return Err(Self::at_line(
+                        line,
+                        String::from("PREGAP and INDEX 00 in one track are not supported"),
+                    ));
+                }
+                (Some(duration), None) => (
+                    CuePregap {
+                        r#type: CuePregapType::Silent,
+                        duration,
+                    },
+                    index_01,
+                    None,
+                ),
+                (None, Some((index_00_file, index_00))) if index_00_file == file_index => {
+                    let Ok(duration) = index_01 - index_00 else {
+                        return Err(Self::at_line(
+                            line,
+                            format!("INDEX 00 of track {:02} is after INDEX 01", pending.number),
+                        ));
+                    };
+                    (
+                        CuePregap {
+                            r#type: CuePregapType::Included,
+                            duration,
+                        },
+                        index_00,
+                        None,
+                    )
+                }
+                // * 最初のトラックのINDEX 00がトラックのない別のファイルにある場合は隠し音声
+                (None, Some((index_00_file, index_00)))
+                    if track_index == 0
+                        && file_tracks[index_00_file].is_empty()
+                        && index_00.to_frame() == 0
+                        && index_01.to_frame() == 0 =>
+                {
+                    let (path, file_type) = &files[index_00_file];
+                    hidden_audio = Some(HiddenAudio {
+                        path: path.clone(),
+                        file_type: *file_type,
+                    });
+                    (
+                        CuePregap {
+                            r#type: CuePregapType::Silent,
+                            duration: zero,
+                        },
+                        index_01,
+                        None,
+                    )
+                }
+                (None, Some((_, index_00))) => {
+                    warnings.push(Self::at_line(
+                        line,
+                        format!(
+                            "length of the pregap of track {:02} in the previous file is unknown",
+                            pending.number
+                        ),
+                    ));
+                    (
+                        CuePregap {
+                            r#type: CuePregapType::Appended,
+                            duration: zero,
+                        },
+                        index_01,
+                        Some(index_00),
+                    )
+                }
+                (None, None) => (
+                    CuePregap {
+                        r#type: CuePregapType::Silent,
+                        duration: zero,
+                    },
+                    index_01,
+                    None,
+                ),
+            };
+            let info = Self::build_info(
+                pending.arranger,
+                pending.composer,
+                pending.lyricist,
+                pending.performer,
+            );
+            let mut track = CueTrack::new(
+                pending.title.unwrap_or_default(),
+                info,
+                CueRem::new(pending.rem),
+                pending.isrc,
+                pregap,
+                start_at,
+            );
+            if let Err(res) = track.set_extra_indexes(pending.extra_indexes) {
+                return Err(Self::at_line(line, res));
+            }
+            if track_index == 0
+                && let Err(res) = track.set_hidden_audio(hidden_audio.clone())
+            {
+                return Err(Self::at_line(line, res));
+            }
+            track.set_appended_index_00(appended_index_00);
+            if let Some(mode) = pending.mode {
+                track.set_mode(mode);
+            }
+            file_tracks[file_index].push(track);
+        }
+
+        let hidden_path = hidden_audio.map(|h| h.path);
+        let mut cue_files: Vec<CueFile> = Vec::new();
+        for ((path, file_type), tracks) in files.into_iter().zip(file_tracks) {
+            if tracks.is_empty() {
+                // * 隠し音声のファイルはトラック1に持たせる
+                if hidden_path.as_ref() != Some(&path) {
+                    warnings.push(Issue::from(format!(
+                        "FILE {:?} has no tracks, ignored",
+                        path
+                    )));
+                }
+                continue;
+            }
+            cue_files.push(CueFile::new(path, tracks, file_type));
+        }
+
+        let info = Self::build_info(disc.arranger, disc.composer, disc.lyricist, disc.performer);
+        let mut cue = Cue::new(
+            disc.title.unwrap_or_default(),
+            disc.genre,
+            info,
+            CueRem::new(disc.rem),
+            cue_files,
+        );
+        cue.set_catalog(disc.catalog);
+        cue.set_first_track_number(first_track_number)?;
+        Ok(Converted { cue, warnings })
+    }
+
+    // 行番号を前に付け、位置とする
+    fn at_line(line_number: usize, message: String) -> Issue {
+        Issue {
+            line: Some(line_number),
+            ..Issue::from(format!("line {}: {}", line_number, message))
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::cue::Header;
+    use crate::share::util::TextStyle;
+
+    fn round_trip(source: &str) -> Result<String, String> {
+        let converted = CueSheet2Cue::parse(source)?;
+        let header = Header {
+            cdrtools: false,
+            comment: None,
+        };
+        converted
+            .cue
+            .to_cdtext_string(&header, &TextStyle::default())
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let source = [
+            "REM CDRTOOLS",
+            "REM COMMENT Generated by Json2Cue",
+            "CATALOG 4988001234567",
+            "TITLE \"Album \"Live\"\"",
+            "REM GENRE \"Rock\"",
+            "REM DATE 2024",
+            "PERFORMER \"Band\"",
+            "FILE \"htoa.wav\" WAVE",
+            "  TRACK 03 AUDIO",
+            "    TITLE \"One\"",
+            "    INDEX 00 00:00:00",
+            "FILE \"dir/1 2.wav\" WAVE",
+            "    INDEX 01 00:00:00",
+            "  TRACK 04 AUDIO",
+            "    TITLE \"Two\"",
+            "    REM COMPOSER \"Someone\"",
+            "    SONGWRITER \"Writer\"",
+            "    ISRC JPAB01234567",
+            "    INDEX 00 00:40:00",
+            "    INDEX 01 00:42:00",
+            "    INDEX 02 00:50:00",
+            "  TRACK 05 AUDIO",
+            "    TITLE \"Three\"",
+            "    FLAGS DCP",
+            "    PREGAP 00:02:00",
+            "    INDEX 01 01:00:00",
+            "",
+        ]
+        .join("\n");
+
+        let converted = CueSheet2Cue::parse(&source)?;
+        let result = round_trip(&source)?;
+
+        assert_eq!(
+            converted
+                .warnings
+                .iter()
+                .map(|w| (w.message.as_str(), w.line))
+                .collect::<Vec<(&str, Option<usize>)>>(),
+            vec![("line 24: FLAGS is ignored", Some(24))]
+        );
+        assert_eq!(
+            result.lines().collect::<Vec<&str>>(),
+            vec![
+                "CATALOG 4988001234567",
+                "TITLE \"Album \"Live\"\"",
+                "REM GENRE \"Rock\"",
+                "REM COMMENT Generated by Json2Cue",
+                "REM DATE 2024",
+                "PERFORMER \"Band\"",
+                "FILE \"htoa.wav\" WAVE",
+                "  TRACK 03 AUDIO",
+                "    TITLE \"One\"",
+                "    INDEX 00 00:00:00",
+                "FILE \"dir/1 2.wav\" WAVE",
+                "    INDEX 01 00:00:00",
+                "  TRACK 04 AUDIO",
+                "    TITLE \"Two\"",
+                "    REM COMPOSER \"Someone\"",
+                "    REM LYRICIST \"Writer\"",
+                "    ISRC JPAB01234567",
+                "    INDEX 00 00:40:00",
+                "    INDEX 01 00:42:00",
+                "    INDEX 02 00:50:00",
+                "  TRACK 05 AUDIO",
+                "    TITLE \"Three\"",
+                "    PREGAP 00:02:00",
+                "    INDEX 01 01:00:00",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_appended() -> Result<(), String> {
+        let source = [
+            "TITLE \"Album\"",
+            "FILE \"1.wav\" WAVE",
+            "  TRACK 01 AUDIO",
+            "    INDEX 01 00:00:00",
+            "  TRACK 02 AUDIO",
+            "    INDEX 00 00:09:00",
+            "FILE \"2.wav\" WAVE",
+            "    INDEX 01 00:00:00",
+        ]
+        .join("\n");
+
+        let converted = CueSheet2Cue::parse(&source)?;
+        let track = &converted.cue.files()[1].tracks()[0];
+
+        assert_eq!(
+            track.appended_index_00().map(|t| t.to_frame()),
+            Some(9 * 75)
+        );
+        assert_eq!(
+            converted
+                .warnings
+                .iter()
+                .map(|w| w.message.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["line 5: length of the pregap of track 02 in the previous file is unknown"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err() {
+        let cases = [
+            ("TITLE \"a\"\nTRACK 01 AUDIO", "line 2: TRACK before FILE"),
+            (
+                "FILE \"a.wav\" WAVE\nTRACK 01 AUDIO\nINDEX 01 00:00:00\nTRACK 03 AUDIO",
+                "line 4: track number 3 is not contiguous, expected 2",
+            ),
+            (
+                "FILE \"a.wav\" WAVE\nTRACK 01 AUDIO\nTITLE \"a\"",
+                "line 2: track 01 has no INDEX 01",
+            ),
+            (
+                "FILE \"a.wav\" WAVE\nTRACK 01 AUDIO\nINDEX 01 1:2",
+                "line 3: time is not parsable: 1:2 (use mm:ss:ff)",
+            ),
+            ("FILE \"a.wav\" OGG", "line 1: file type OGG is unknown"),
+            (
+                "TITLE \"a\"\nDISCNUMBER 1",
+                "line 2: unknown command: DISCNUMBER",
+            ),
+            ("TITLE \"a\"", "cue sheet has no tracks"),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(
+                CueSheet2Cue::parse(source)
+                    .err()
+                    .map(|e| e.message)
+                    .as_deref(),
+                Some(expected),
+                "{}",
+                source
+            );
+        }
+    }
+}
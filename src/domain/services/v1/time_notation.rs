@@ -0,0 +1,297 @@
+use crate::domain::aggregates::v1::cue::{Time as CueTime, TimeFormatter};
+use crate::domain::aggregates::v1::json::TimeValue;
+use crate::share::{Issue, IssueCode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedTime {
+    pub time: CueTime,
+    // フレーム境界に一致していたか(falseの場合は丸めている)
+    pub aligned: bool,
+}
+
+// JSONの時間表記をフレーム単位の時間に変換する
+// * フレーム境界にない値は最も近いフレームに丸め、ちょうど中間の場合は後ろのフレームにする
+pub struct TimeNotation {}
+
+impl TimeNotation {
+    pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+    const FRAMES_PER_SECOND: u128 = 75;
+    const SECONDS_PER_MINUTE: u128 = 60;
+    const MAX_FRACTION_DIGITS: usize = 18;
+
+    pub fn parse(value: &TimeValue) -> Result<ParsedTime, Issue> {
+        Self::parse_value(value).map_err(|e| Issue::new(IssueCode::Time, e))
+    }
+
+    fn parse_value(value: &TimeValue) -> Result<ParsedTime, String> {
+        match value {
+            TimeValue::Msf(msf) => Ok(ParsedTime {
+                time: CueTime::from_vec(msf)?,
+                aligned: true,
+            }),
+            TimeValue::Seconds(seconds) => {
+                if !seconds.is_finite() || *seconds < 0.0 {
+                    return Err(format!("{} is not a valid number of seconds", seconds));
+                }
+                // * f64の最短表記を10進数として厳密に扱う
+                let (numerator, denominator) = Self::parse_decimal(&seconds.to_string())?;
+                Self::round(numerator * Self::FRAMES_PER_SECOND, denominator)
+            }
+            TimeValue::Text(text) => Self::parse_text(text),
+            TimeValue::Samples {
+                samples,
+                sample_rate,
+            } => {
+                let sample_rate = sample_rate.unwrap_or(Self::DEFAULT_SAMPLE_RATE);
+                if sample_rate == 0 {
+                    return Err(String::from("sample_rate must be >0"));
+                }
+                Self::round(
+                    *samples as u128 * Self::FRAMES_PER_SECOND,
+                    sample_rate as u128,
+                )
+            }
+        }
+    }
+
+    // 警告やエラーで表示する元の表記
+    pub fn describe(value: &TimeValue) -> String {
+        match value {
+            TimeValue::Msf(msf) => format!("{:?}", msf),
+            TimeValue::Seconds(seconds) => seconds.to_string(),
+            TimeValue::Text(text) => format!("\"{}\"", text),
+            TimeValue::Samples {
+                samples,
+                sample_rate,
+            } => format!(
+                "{} samples at {} Hz",
+                samples,
+                sample_rate.unwrap_or(Self::DEFAULT_SAMPLE_RATE)
+            ),
+        }
+    }
+
+    pub fn not_aligned_warning(value: &TimeValue, parsed: &ParsedTime) -> Issue {
+        Issue::new(
+            IssueCode::FrameRounding,
+            format!(
+                "{} is not frame-aligned, rounded to {}",
+                Self::describe(value),
+                parsed.time.to_msf_string()
+            ),
+        )
+    }
+
+    // "mm:ss:ff"、"mm:ss.sss"、"ss.sss"
+    fn parse_text(text: &str) -> Result<ParsedTime, String> {
+        let invalid = || {
+            format!(
+                "\"{}\" is not a valid time (use \"mm:ss:ff\", \"mm:ss.sss\" or seconds)",
+                text
+            )
+        };
+        let parts: Vec<&str> = text.trim().split(':').collect();
+        match parts.as_slice() {
+            [minute, second, frame] => {
+                let values: Vec<u8> = [minute, second, frame]
+                    .iter()
+                    .map(|p| Self::parse_integer(p).and_then(|v| u8::try_from(v).ok()))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(invalid)?;
+                Ok(ParsedTime {
+                    time: CueTime::from_vec(&values)?,
+                    aligned: true,
+                })
+            }
+            [minute, second] => {
+                let minute = Self::parse_integer(minute).ok_or_else(invalid)?;
+                let (numerator, denominator) =
+                    Self::parse_decimal(second).map_err(|_| invalid())?;
+                if numerator >= Self::SECONDS_PER_MINUTE * denominator {
+                    return Err(format!(
+                        "second must be >=0 and <{}",
+                        Self::SECONDS_PER_MINUTE
+                    ));
+                }
+                Self::round(
+                    (minute * Self::SECONDS_PER_MINUTE * denominator + numerator)
+                        * Self::FRAMES_PER_SECOND,
+                    denominator,
+                )
+            }
+            [seconds] => {
+                let (numerator, denominator) =
+                    Self::parse_decimal(seconds).map_err(|_| invalid())?;
+                Self::round(numerator * Self::FRAMES_PER_SECOND, denominator)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn parse_integer(text: &str) -> Option<u128> {
+        if text.is_empty() || text.len() > 10 || !text.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        text.parse().ok()
+    }
+
+    // 10進数の文字列を分数(分子, 分母)にする
+    // * 小数部は18桁までとし、それ以降に0以外の数字がある場合は19桁目を1とする
+    //   (フレーム境界にないことは保たれ、丸めが変わるのはフレームの中間との差が1e-18秒未満の場合のみ)
+    fn parse_decimal(text: &str) -> Result<(u128, u128), String> {
+        let invalid = || format!("\"{}\" is not a valid decimal", text);
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let integer = Self::parse_integer(integer).ok_or_else(invalid)?;
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let fraction = match fraction.split_at_checked(Self::MAX_FRACTION_DIGITS) {
+            Some((kept, rest)) if rest.bytes().any(|b| b != b'0') => format!("{}1", kept),
+            Some((kept, _)) => String::from(kept),
+            None => String::from(fraction),
+        };
+        let denominator = 10u128.pow(fraction.len() as u32);
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse().map_err(|_| invalid())?
+        };
+        Ok((integer * denominator + fraction, denominator))
+    }
+
+    fn round(frame_numerator: u128, denominator: u128) -> Result<ParsedTime, String> {
+        let quotient = frame_numerator / denominator;
+        let remainder = frame_numerator % denominator;
+        let frame = if remainder * 2 >= denominator {
+            quotient + 1
+        } else {
+            quotient
+        };
+        let Ok(frame) = u32::try_from(frame) else {
+            return Err(format!("{} frames exceeds range of Time object", frame));
+        };
+        Ok(ParsedTime {
+            time: CueTime::from_frame(frame)?,
+            aligned: remainder == 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_to_string(value: TimeValue) -> Result<(String, bool), String> {
+        let parsed = TimeNotation::parse(&value)?;
+        Ok((parsed.time.to_msf_string(), parsed.aligned))
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let cases = [
+            (TimeValue::Msf(vec![3, 12, 30]), "03:12:30", true),
+            (TimeValue::Text(String::from("03:12:30")), "03:12:30", true),
+            (TimeValue::Text(String::from("03:12.400")), "03:12:30", true),
+            (TimeValue::Text(String::from("192.4")), "03:12:30", true),
+            (TimeValue::Seconds(192.4), "03:12:30", true),
+            (TimeValue::Seconds(2.0), "00:02:00", true),
+            (
+                TimeValue::Samples {
+                    samples: 44100 * 2 + 588,
+                    sample_rate: None,
+                },
+                "00:02:01",
+                true,
+            ),
+            (
+                TimeValue::Samples {
+                    samples: 96000,
+                    sample_rate: Some(48000),
+                },
+                "00:02:00",
+                true,
+            ),
+        ];
+
+        for (value, expected, aligned) in cases {
+            assert_eq!(
+                parse_to_string(value.clone())?,
+                (String::from(expected), aligned),
+                "{:?}",
+                value
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rounding() -> Result<(), String> {
+        // 1フレーム = 588サンプル(44.1kHz)、中間(294)は後ろのフレームに丸める
+        let below = TimeValue::Samples {
+            samples: 293,
+            sample_rate: None,
+        };
+        let half = TimeValue::Samples {
+            samples: 294,
+            sample_rate: None,
+        };
+
+        assert_eq!(parse_to_string(below)?, (String::from("00:00:00"), false));
+        assert_eq!(parse_to_string(half)?, (String::from("00:00:01"), false));
+        assert_eq!(
+            parse_to_string(TimeValue::Text(String::from("00:01.005")))?,
+            (String::from("00:01:00"), false)
+        );
+        assert_eq!(
+            parse_to_string(TimeValue::Seconds(0.02))?,
+            (String::from("00:00:02"), false)
+        );
+        // * f64の表記が18桁を超える小さな値
+        assert_eq!(
+            parse_to_string(TimeValue::Seconds(1e-20))?,
+            (String::from("00:00:00"), false)
+        );
+        assert_eq!(
+            parse_to_string(TimeValue::Text(String::from(
+                "00:01.0000000000000000000001"
+            )))?,
+            (String::from("00:01:00"), false)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err() {
+        let cases = [
+            TimeValue::Msf(vec![0, 60, 0]),
+            TimeValue::Text(String::from("1:2:3:4")),
+            TimeValue::Text(String::from("00:60.000")),
+            TimeValue::Text(String::from("abc")),
+            TimeValue::Seconds(-1.0),
+            TimeValue::Seconds(80.0 * 60.0),
+            TimeValue::Samples {
+                samples: 1,
+                sample_rate: Some(0),
+            },
+        ];
+
+        for value in cases {
+            assert!(TimeNotation::parse(&value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_not_aligned_warning() -> Result<(), String> {
+        let value = TimeValue::Text(String::from("00:01.005"));
+        let parsed = TimeNotation::parse(&value)?;
+
+        let result = TimeNotation::not_aligned_warning(&value, &parsed);
+
+        assert_eq!(
+            result.message,
+            "\"00:01.005\" is not frame-aligned, rounded to 00:01:00"
+        );
+        assert_eq!(result.code, IssueCode::FrameRounding);
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use super::json2cue::Json2CueV2;
+use crate::domain::aggregates::common::Document;
+use crate::domain::aggregates::v2::json::Json;
+use crate::domain::services::common::{Converted, CueConverter, FileCheck};
+use crate::share::Issue;
+
+pub struct ConverterV2 {}
+
+impl CueConverter for ConverterV2 {
+    fn version(&self) -> u8 {
+        Json2CueV2::VERSION
+    }
+
+    fn convert(&self, document: &Document, file_check: FileCheck) -> Result<Converted, Issue> {
+        let json: Json = match document.deserialize() {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res.prefixed(String::from("document is not parsable as v2\n")));
+            }
+        };
+        let mut json2cue = Json2CueV2::new(json);
+        json2cue.set_file_check(file_check);
+        Ok(Converted {
+            cue: json2cue.build_cue()?,
+            warnings: Vec::new(),
+        })
+    }
+}
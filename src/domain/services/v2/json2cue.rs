@@ -0,0 +1,502 @@
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
+
+use crate::domain::aggregates::v1::cue::{
+    Cue, File as CueFile, FileType as CueFileType, HiddenAudio, Info as CueInfo,
+    Pregap as CuePregap, PregapType as CuePregapType, Rem as CueRem, Time as CueTime,
+    Track as CueTrack, TrackMode as CueTrackMode,
+};
+use crate::domain::aggregates::v2::json::{
+    File as JsonFile, FileType as JsonFileType, Htoa as JsonHtoa, Index as JsonIndex,
+    Info as JsonInfo, Json, Time as JsonTime, Track as JsonTrack, TrackMode as JsonTrackMode,
+};
+use crate::domain::services::common::{FileCheck, FilePath, TrackNumbering};
+use crate::share::{Issue, IssueCode};
+
+pub struct Json2CueV2 {
+    json: Json,
+    file_check: FileCheck,
+}
+
+impl Json2CueV2 {
+    pub const VERSION: u8 = 2;
+    const INDEX_00: u8 = 0;
+    const INDEX_01: u8 = 1;
+
+    pub fn new(json: Json) -> Json2CueV2 {
+        Json2CueV2 {
+            json,
+            file_check: FileCheck::default(),
+        }
+    }
+
+    pub fn set_file_check(&mut self, file_check: FileCheck) {
+        self.file_check = file_check;
+    }
+
+    pub fn is_parsable_version(version: u8) -> bool {
+        version == Self::VERSION
+    }
+
+    pub fn build_cue(&self) -> Result<Cue, Issue> {
+        if !Self::is_parsable_version(self.json.version) {
+            return Err(Issue::new(
+                IssueCode::Version,
+                format!("incompetible version: {}", self.json.version),
+            )
+            .within("/version"));
+        };
+
+        let disc = &self.json.disc;
+        let info = disc.info.as_ref().map(Self::build_info);
+        let rem = match Self::build_rem(&disc.rem) {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res
+                    .within("/disc/rem")
+                    .prefixed(String::from("rem is not parsable\n")));
+            }
+        };
+        if let Some(catalog) = &disc.catalog
+            && let Err(res) = Cue::<CueInfo>::validate_catalog(catalog)
+        {
+            return Err(Issue::from(res).within("/disc/catalog"));
+        }
+        let files = self.build_files(&self.json.files)?;
+        let numbers: Vec<Vec<Option<usize>>> = self
+            .json
+            .files
+            .iter()
+            .map(|f| f.tracks.iter().map(|t| t.number.map(usize::from)).collect())
+            .collect();
+        let first_track_number = TrackNumbering::resolve(
+            disc.first_track_number.map(usize::from),
+            "/disc/first_track_number",
+            &numbers,
+        )?;
+        let mut cue = Cue::new(disc.title.clone(), disc.genre.clone(), info, rem, files);
+        cue.set_catalog(disc.catalog.clone());
+        if let Err(res) = cue.set_first_track_number(first_track_number) {
+            return Err(Issue::new(IssueCode::TrackNumber, res).within("/disc/first_track_number"));
+        }
+        if let Some(htoa) = &disc.htoa
+            && let Err(res) = self.apply_htoa(&mut cue, htoa)
+        {
+            return Err(res.within("/disc/htoa").prefixed(String::from("htoa: ")));
+        }
+        cue.validate_appended_pregaps("/appended_pregap")?;
+        cue.validate_hidden_audio()?;
+        Ok(cue)
+    }
+
+    fn apply_htoa(&self, cue: &mut Cue, htoa: &JsonHtoa) -> Result<(), Issue> {
+        let Some(track) = cue
+            .files_mut()
+            .first_mut()
+            .and_then(|f| f.tracks_mut().first_mut())
+        else {
+            return Err(Issue::from(String::from(
+                "hidden audio requires at least one track",
+            )));
+        };
+        Ok(track.set_hidden_audio(Some(HiddenAudio {
+            path: FilePath::resolve(&htoa.path, self.file_check).map_err(|e| e.within("/path"))?,
+            file_type: Self::build_file_type(htoa.file_type),
+        }))?)
+    }
+
+    pub fn build_time(time: &JsonTime) -> Result<CueTime, String> {
+        CueTime::from_vec(&[time.minute, time.second, time.frame])
+    }
+
+    fn build_info(info: &JsonInfo) -> CueInfo {
+        CueInfo::new(
+            info.arranger.clone(),
+            info.composer.clone(),
+            info.lyricist.clone(),
+            info.performer.clone(),
+        )
+    }
+
+    // エラーの位置はキーの位置
+    fn build_rem(rem: &Option<IndexMap<String, String>>) -> Result<CueRem, Issue> {
+        let mut result = CueRem::default();
+
+        let Some(rem) = rem else {
+            return Ok(result);
+        };
+        for (key, value) in rem.iter() {
+            if let Err(res) = CueRem::validate_key(key) {
+                return Err(Issue::from(res).within(&Issue::segment(key)));
+            }
+            result.push(key.clone(), value.clone());
+        }
+
+        Ok(result)
+    }
+
+    fn build_file_type(file_type: JsonFileType) -> CueFileType {
+        match file_type {
+            JsonFileType::Wave => CueFileType::Wave,
+            JsonFileType::Aiff => CueFileType::Aiff,
+            JsonFileType::Mp3 => CueFileType::Mp3,
+            JsonFileType::Binary => CueFileType::Binary,
+            JsonFileType::Motorola => CueFileType::Motorola,
+        }
+    }
+
+    fn build_mode(mode: JsonTrackMode) -> CueTrackMode {
+        match mode {
+            JsonTrackMode::Audio => CueTrackMode::Audio,
+            JsonTrackMode::Cdg => CueTrackMode::Cdg,
+            JsonTrackMode::Mode1_2048 => CueTrackMode::Mode1_2048,
+            JsonTrackMode::Mode1_2352 => CueTrackMode::Mode1_2352,
+            JsonTrackMode::Mode2_2336 => CueTrackMode::Mode2_2336,
+            JsonTrackMode::Mode2_2352 => CueTrackMode::Mode2_2352,
+            JsonTrackMode::Cdi2336 => CueTrackMode::Cdi2336,
+            JsonTrackMode::Cdi2352 => CueTrackMode::Cdi2352,
+        }
+    }
+
+    fn build_files(&self, files: &[JsonFile]) -> Result<Vec<CueFile>, Issue> {
+        let mut result: Vec<CueFile> = Vec::new();
+
+        for (index, file) in files.iter().enumerate() {
+            let abs_file_path = FilePath::resolve(&file.path, self.file_check)
+                .map_err(|e| e.within(&format!("/files/{}/path", index)))?;
+            let tracks = match Self::build_tracks(&file.tracks) {
+                Ok(res) => res,
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("/files/{}/tracks", index))
+                        .prefixed(format!("tracks are not parsable in index={}\n", index)));
+                }
+            };
+            result.push(CueFile::new(
+                abs_file_path,
+                tracks,
+                Self::build_file_type(file.file_type),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    fn build_tracks(tracks: &[JsonTrack]) -> Result<Vec<CueTrack>, Issue> {
+        let mut result: Vec<CueTrack> = Vec::new();
+
+        for (index, track) in tracks.iter().enumerate() {
+            match Self::build_track(track) {
+                Ok(res) => result.push(res),
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("/{}", index))
+                        .prefixed(format!("track in index={}: ", index)));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // エラーの位置はトラックの中の位置
+    fn build_track(track: &JsonTrack) -> Result<CueTrack, Issue> {
+        let info = track.info.as_ref().map(Self::build_info);
+        let rem = match Self::build_rem(&track.rem) {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(res
+                    .within("/rem")
+                    .prefixed(String::from("rem is not parsable\n")));
+            }
+        };
+        if let Some(isrc) = &track.isrc
+            && let Err(res) = CueTrack::<CueInfo>::validate_isrc(isrc)
+        {
+            return Err(Issue::from(res).within("/isrc"));
+        }
+        let indexes = Self::build_indexes(&track.indexes).map_err(|e| e.within("/indexes"))?;
+        let Some(index_01) = indexes.get(&Self::INDEX_01).copied() else {
+            return Err(Issue::from(String::from("INDEX 01 is required")).within("/indexes"));
+        };
+        // * INDEX 00はファイルに含まれるプリギャップとして扱う
+        let (pregap, start_at) = match (
+            indexes.get(&Self::INDEX_00),
+            &track.pregap,
+            &track.appended_pregap,
+        ) {
+            (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+                return Err(Issue::from(String::from(
+                    "appended_pregap cannot be used together with pregap or INDEX 00",
+                ))
+                .within("/appended_pregap"));
+            }
+            (Some(_), Some(_), None) => {
+                return Err(Issue::from(String::from(
+                    "pregap and INDEX 00 cannot be used together",
+                ))
+                .within("/pregap"));
+            }
+            (Some(index_00), None, None) => (
+                CuePregap {
+                    r#type: CuePregapType::Included,
+                    duration: (index_01 - *index_00)
+                        .map_err(|e| Issue::new(IssueCode::Time, e).within("/indexes"))?,
+                },
+                *index_00,
+            ),
+            (None, None, Some(appended_pregap)) => (
+                CuePregap {
+                    r#type: CuePregapType::Appended,
+                    duration: Self::build_time(appended_pregap)
+                        .map_err(|e| Issue::new(IssueCode::Time, e).within("/appended_pregap"))?,
+                },
+                index_01,
+            ),
+            (None, pregap, None) => (
+                CuePregap {
+                    r#type: CuePregapType::Silent,
+                    duration: match pregap {
+                        Some(pregap) => Self::build_time(pregap)
+                            .map_err(|e| Issue::new(IssueCode::Time, e).within("/pregap"))?,
+                        None => CueTime::from_frame(0)?,
+                    },
+                },
+                index_01,
+            ),
+        };
+
+        let mut result = CueTrack::new(
+            track.title.clone(),
+            info,
+            rem,
+            track.isrc.clone(),
+            pregap,
+            start_at,
+        );
+        result.set_mode(Self::build_mode(track.mode));
+        if let Err(res) = result.set_extra_indexes(
+            indexes
+                .into_iter()
+                .filter(|(number, _)| *number > Self::INDEX_01)
+                .collect(),
+        ) {
+            return Err(Issue::from(res).within("/indexes"));
+        }
+        Ok(result)
+    }
+
+    // エラーの位置は配列の中の位置
+    fn build_indexes(indexes: &[JsonIndex]) -> Result<BTreeMap<u8, CueTime>, Issue> {
+        let mut result: BTreeMap<u8, CueTime> = BTreeMap::new();
+
+        for (position, index) in indexes.iter().enumerate() {
+            let time = match Self::build_time(&index.time) {
+                Ok(res) => res,
+                Err(res) => {
+                    return Err(Issue::new(
+                        IssueCode::Time,
+                        format!("INDEX {:0>2} is not parsable\n{}", index.number, res),
+                    )
+                    .within(&format!("/{}/time", position)));
+                }
+            };
+            if result.insert(index.number, time).is_some() {
+                return Err(
+                    Issue::from(format!("INDEX {:0>2} is duplicated", index.number))
+                        .within(&format!("/{}/number", position)),
+                );
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::cue::{Header, TrackFormatter};
+    use crate::domain::aggregates::v2::json::Disc;
+    use crate::share::util::TextStyle;
+    use proptest::prelude::*;
+    use std::path::PathBuf;
+
+    fn build_time(minute: u8, second: u8, frame: u8) -> JsonTime {
+        JsonTime {
+            minute,
+            second,
+            frame,
+        }
+    }
+
+    fn build_json_track(pregap: Option<JsonTime>, indexes: Vec<(u8, JsonTime)>) -> JsonTrack {
+        JsonTrack {
+            number: None,
+            title: String::from("title"),
+            mode: JsonTrackMode::Audio,
+            isrc: None,
+            info: None,
+            rem: None,
+            pregap,
+            appended_pregap: None,
+            indexes: indexes
+                .into_iter()
+                .map(|(number, time)| JsonIndex { number, time })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_track() -> Result<(), Box<dyn std::error::Error>> {
+        let track = build_json_track(
+            None,
+            vec![
+                (0, build_time(1, 0, 0)),
+                (1, build_time(1, 2, 0)),
+                (2, build_time(2, 0, 0)),
+            ],
+        );
+
+        let result = Json2CueV2::build_track(&track)?;
+
+        assert_eq!(
+            result.to_cdtext_strings(1, false, &TextStyle::default())?,
+            vec![
+                "TRACK 01 AUDIO",
+                "  TITLE \"title\"",
+                "  INDEX 00 01:00:00",
+                "  INDEX 01 01:02:00",
+                "  INDEX 02 02:00:00",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_track_err() {
+        let no_index_01 = build_json_track(None, vec![(0, build_time(0, 0, 0))]);
+        let both = build_json_track(
+            Some(build_time(0, 2, 0)),
+            vec![(0, build_time(0, 0, 0)), (1, build_time(0, 2, 0))],
+        );
+        let duplicated = build_json_track(
+            None,
+            vec![(1, build_time(0, 0, 0)), (1, build_time(0, 2, 0))],
+        );
+        let appended = JsonTrack {
+            appended_pregap: Some(build_time(0, 2, 0)),
+            ..build_json_track(Some(build_time(0, 2, 0)), vec![(1, build_time(0, 0, 0))])
+        };
+
+        let (Err(no_index_01), Err(both), Err(duplicated), Err(appended)) = (
+            Json2CueV2::build_track(&no_index_01),
+            Json2CueV2::build_track(&both),
+            Json2CueV2::build_track(&duplicated),
+            Json2CueV2::build_track(&appended),
+        ) else {
+            panic!("invalid track is accepted");
+        };
+
+        assert_eq!(no_index_01.message, "INDEX 01 is required");
+        assert_eq!(both.message, "pregap and INDEX 00 cannot be used together");
+        assert_eq!(duplicated.message, "INDEX 01 is duplicated");
+        assert_eq!(duplicated.pointer.as_deref(), Some("/indexes/1/number"));
+        assert_eq!(
+            appended.message,
+            "appended_pregap cannot be used together with pregap or INDEX 00"
+        );
+        assert_eq!(appended.pointer.as_deref(), Some("/appended_pregap"));
+    }
+
+    #[test]
+    fn test_build_cue() -> Result<(), Box<dyn std::error::Error>> {
+        let json = Json {
+            schema: None,
+            version: 2,
+            disc: Disc {
+                title: String::from("album"),
+                genre: None,
+                catalog: Some(String::from("4988001234567")),
+                info: None,
+                rem: None,
+                first_track_number: Some(5),
+                htoa: None,
+            },
+            files: vec![JsonFile {
+                path: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"),
+                file_type: JsonFileType::Binary,
+                tracks: vec![JsonTrack {
+                    mode: JsonTrackMode::Mode1_2352,
+                    ..build_json_track(Some(build_time(0, 2, 0)), vec![(1, build_time(0, 0, 0))])
+                }],
+            }],
+        };
+
+        let result = Json2CueV2::new(json)
+            .build_cue()?
+            .to_cdtext_string(&Header::new(false, None), &TextStyle::default())?;
+
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "CATALOG 4988001234567");
+        assert!(lines[2].ends_with("Cargo.toml\" BINARY"));
+        assert_eq!(lines[3], "  TRACK 05 MODE1/2352");
+        assert_eq!(lines[5], "    PREGAP 00:02:00");
+        Ok(())
+    }
+
+    // 80分の上限を超える値も含める
+    fn arb_time() -> impl Strategy<Value = JsonTime> {
+        (0u8..90, 0u8..60, 0u8..75).prop_map(|(m, s, f)| build_time(m, s, f))
+    }
+
+    fn to_frames(time: &JsonTime) -> u32 {
+        (time.minute as u32 * 60 + time.second as u32) * 75 + time.frame as u32
+    }
+
+    proptest! {
+        // 組み立てたトラックのINDEXはINDEX 00 <= INDEX 01 < INDEX 02 < 80分に収まり、
+        // 満たせない入力はエラーになる
+        #[test]
+        fn test_build_track_indexes_ordered(
+            index_00 in proptest::option::of(arb_time()),
+            index_01 in arb_time(),
+            index_02 in proptest::option::of(arb_time()),
+            pregap in proptest::option::of(arb_time()),
+        ) {
+            let mut indexes = vec![(1, index_01)];
+            indexes.extend(index_00.map(|t| (0, t)));
+            indexes.extend(index_02.map(|t| (2, t)));
+            let track = build_json_track(pregap, indexes);
+            let over_limit = [&index_00, &Some(index_01), &index_02, &pregap]
+                .into_iter()
+                .flatten()
+                .any(|t| t.minute >= 80);
+            let unordered = index_00.as_ref().is_some_and(|t| to_frames(t) > to_frames(&index_01))
+                || index_02.as_ref().is_some_and(|t| to_frames(t) <= to_frames(&index_01));
+            let conflicting = index_00.is_some() && pregap.is_some();
+
+            let result = Json2CueV2::build_track(&track);
+
+            let Ok(result) = result else {
+                prop_assert!(
+                    over_limit || unordered || conflicting,
+                    "valid track is rejected: {:?}",
+                    result.err()
+                );
+                return Ok(());
+            };
+            prop_assert!(!over_limit && !unordered && !conflicting, "invalid track is accepted");
+            let built_index_01 = result.index_01().map_err(TestCaseError::fail)?;
+            let limit = 80 * 60 * 75;
+
+            prop_assert_eq!(built_index_01.to_frame(), to_frames(&index_01));
+            prop_assert!(built_index_01.to_frame() < limit);
+            prop_assert!(result.index_00().is_none_or(|i| i <= built_index_01 && i.to_frame() < limit));
+            for (_, time) in result.extra_indexes() {
+                prop_assert!(*time > built_index_01);
+                prop_assert!(time.to_frame() < limit);
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use crate::domain::aggregates::v1::cue::{FileType as CueFileType, Time as CueTime};
+use crate::domain::aggregates::v1::json::{
+    File as V1File, Info as V1Info, Json as V1Json, TimeValue as V1TimeValue, Track as V1Track,
+};
+use crate::domain::aggregates::v2::json::{
+    Disc, File as V2File, FileType as V2FileType, Htoa as V2Htoa, Index as V2Index, Info as V2Info,
+    Json as V2Json, Time as V2Time, Track as V2Track, TrackMode as V2TrackMode,
+};
+use crate::domain::services::v1::{Json2CueV1, TimeNotation};
+use crate::share::{Issue, IssueCode};
+
+pub struct MigrateV1ToV2 {
+    json: V1Json,
+}
+
+impl MigrateV1ToV2 {
+    const VERSION: u8 = 2;
+    const SCHEMA_V1: &str = "v1.json";
+    const SCHEMA_V2: &str = "v2.json";
+    const PREGAP_TYPE_INCLUDED: &str = "included";
+    const PREGAP_TYPE_SILENT: &str = "silent";
+    const PREGAP_TYPE_APPENDED: &str = "appended";
+
+    pub fn new(json: V1Json) -> MigrateV1ToV2 {
+        MigrateV1ToV2 { json }
+    }
+
+    pub fn migrate(&self) -> Result<V2Json, Issue> {
+        Ok(self.migrate_with_warnings()?.0)
+    }
+
+    // フレーム境界にない時間は丸めた上で警告を返す
+    pub fn migrate_with_warnings(&self) -> Result<(V2Json, Vec<Issue>), Issue> {
+        let mut files: Vec<V2File> = Vec::new();
+        let mut warnings: Vec<Issue> = Vec::new();
+        // * 最初のファイルの先頭に隠した音声はトラック1のINDEX 00にする
+        let v1_files = match &self.json.htoa {
+            Some(htoa) => match Json2CueV1::build_htoa_files(htoa, &self.json.files, &mut warnings)
+            {
+                Ok(res) => res,
+                Err(res) => return Err(res.within("/htoa").prefixed(String::from("htoa: "))),
+            },
+            None => self.json.files.clone(),
+        };
+        let htoa = match self.json.htoa.as_ref().and_then(|h| h.path.as_ref()) {
+            Some(path) => Some(V2Htoa {
+                path: path.clone(),
+                file_type: Self::migrate_file_type(
+                    self.json.htoa.as_ref().and_then(|h| h.file_type.as_deref()),
+                )
+                .map_err(|e| e.within("/htoa/type"))?,
+            }),
+            None => None,
+        };
+        for (index, file) in v1_files.iter().enumerate() {
+            let mut file_warnings: Vec<Issue> = Vec::new();
+            match Self::migrate_file(file, &mut file_warnings) {
+                Ok(res) => files.push(res),
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("/files/{}", index))
+                        .prefixed(format!("file in index={}: ", index)));
+                }
+            }
+            warnings.extend(file_warnings.into_iter().map(|w| {
+                w.within(&format!("/files/{}", index))
+                    .prefixed(format!("files[{}].", index))
+            }));
+        }
+
+        let json = V2Json {
+            schema: self.json.schema.as_ref().map(|s| Self::migrate_schema(s)),
+            version: Self::VERSION,
+            disc: Disc {
+                title: self.json.title.clone(),
+                genre: self.json.genre.clone(),
+                catalog: None,
+                info: self.json.info.as_ref().map(Self::migrate_info),
+                rem: self.json.rem.clone(),
+                first_track_number: self.json.first_track_number,
+                htoa,
+            },
+            files,
+        };
+        Ok((json, warnings))
+    }
+
+    // スキーマのパスがv1.jsonを指している場合のみv2.jsonに置き換える
+    fn migrate_schema(schema: &str) -> String {
+        match schema.strip_suffix(Self::SCHEMA_V1) {
+            Some(base) => format!("{}{}", base, Self::SCHEMA_V2),
+            None => String::from(schema),
+        }
+    }
+
+    // nullによる消去はv2では空文字列で表す
+    fn migrate_info(info: &V1Info) -> V2Info {
+        let migrate =
+            |value: &Option<Option<String>>| value.as_ref().map(|v| v.clone().unwrap_or_default());
+        V2Info {
+            arranger: migrate(&info.arranger),
+            composer: migrate(&info.composer),
+            lyricist: migrate(&info.lyricist),
+            performer: migrate(&info.performer),
+        }
+    }
+
+    // v1で使えるファイル形式はWAVEのみ(省略時もWAVE)
+    fn migrate_file_type(file_type: Option<&str>) -> Result<V2FileType, Issue> {
+        let file_type = file_type.unwrap_or(CueFileType::Wave.keyword());
+        match CueFileType::from_keyword(file_type) {
+            Some(CueFileType::Wave) => Ok(V2FileType::Wave),
+            _ => Err(Issue::new(
+                IssueCode::Schema,
+                format!("file type {} is unknown", file_type),
+            )),
+        }
+    }
+
+    // エラーと警告の位置はファイルの中の位置
+    fn migrate_file(file: &V1File, warnings: &mut Vec<Issue>) -> Result<V2File, Issue> {
+        let file_type =
+            Self::migrate_file_type(Some(&file.file_type)).map_err(|e| e.within("/type"))?;
+        let mut tracks: Vec<V2Track> = Vec::new();
+        for (index, track) in file.tracks.iter().enumerate() {
+            let mut track_warnings: Vec<Issue> = Vec::new();
+            match Self::migrate_track(track, &mut track_warnings) {
+                Ok(res) => tracks.push(res),
+                Err(res) => {
+                    return Err(res
+                        .within(&format!("/tracks/{}", index))
+                        .prefixed(format!("track in index={}: ", index)));
+                }
+            }
+            warnings.extend(track_warnings.into_iter().map(|w| {
+                w.within(&format!("/tracks/{}", index))
+                    .prefixed(format!("tracks[{}].", index))
+            }));
+        }
+
+        Ok(V2File {
+            path: file.path.clone(),
+            file_type,
+            tracks,
+        })
+    }
+
+    // v1のstart_at(+ファイルに含まれるプリギャップ)をINDEX 00/01に変換する
+    // * 前のファイルの末尾に含まれるプリギャップはappended_pregapにする
+    // エラーと警告の位置はトラックの中の位置
+    fn migrate_track(track: &V1Track, warnings: &mut Vec<Issue>) -> Result<V2Track, Issue> {
+        let start_at = match &track.start_at {
+            Some(start_at) => Self::parse_time(start_at, "start_at", "/start_at", warnings)?,
+            None => CueTime::from_frame(0)?,
+        };
+        let mut pregap: Option<V2Time> = None;
+        let mut appended_pregap: Option<V2Time> = None;
+        let mut indexes: Vec<V2Index> = Vec::new();
+        match &track.pregap {
+            Some(v1_pregap) => {
+                let duration = Self::parse_time(
+                    &v1_pregap.duration,
+                    "pregap.duration",
+                    "/pregap/duration",
+                    warnings,
+                )?;
+                match v1_pregap.pregap_type.as_str() {
+                    Self::PREGAP_TYPE_SILENT => {
+                        pregap = Some(Self::migrate_time(duration));
+                        indexes.push(Self::build_index(1, start_at));
+                    }
+                    Self::PREGAP_TYPE_INCLUDED | Self::PREGAP_TYPE_APPENDED
+                        if duration.to_frame() == 0 =>
+                    {
+                        indexes.push(Self::build_index(1, start_at));
+                    }
+                    Self::PREGAP_TYPE_INCLUDED => {
+                        let index_01 = match start_at + duration {
+                            Ok(res) => res,
+                            Err(_) => {
+                                return Err(Issue::new(
+                                    IssueCode::Time,
+                                    String::from(
+                                        "(start_at + pregap) exceeds range of Time object",
+                                    ),
+                                )
+                                .within("/pregap/duration"));
+                            }
+                        };
+                        indexes.push(Self::build_index(0, start_at));
+                        indexes.push(Self::build_index(1, index_01));
+                    }
+                    Self::PREGAP_TYPE_APPENDED => {
+                        appended_pregap = Some(Self::migrate_time(duration));
+                        indexes.push(Self::build_index(1, start_at));
+                    }
+                    _ => {
+                        return Err(Issue::new(
+                            IssueCode::Schema,
+                            format!("unknown pregap_type: {}", v1_pregap.pregap_type),
+                        )
+                        .within("/pregap/type"));
+                    }
+                }
+            }
+            None => indexes.push(Self::build_index(1, start_at)),
+        }
+
+        Ok(V2Track {
+            number: track.number,
+            title: track.title.clone(),
+            mode: V2TrackMode::Audio,
+            isrc: track.isrc.clone(),
+            info: track.info.as_ref().map(Self::migrate_info),
+            rem: track.rem.clone(),
+            pregap,
+            appended_pregap,
+            indexes,
+        })
+    }
+
+    fn parse_time(
+        value: &V1TimeValue,
+        name: &str,
+        pointer: &str,
+        warnings: &mut Vec<Issue>,
+    ) -> Result<CueTime, Issue> {
+        let parsed = TimeNotation::parse(value).map_err(|e| e.within(pointer))?;
+        if !parsed.aligned {
+            warnings.push(
+                TimeNotation::not_aligned_warning(value, &parsed)
+                    .within(pointer)
+                    .prefixed(format!("{}: ", name)),
+            );
+        }
+        Ok(parsed.time)
+    }
+
+    fn build_index(number: u8, time: CueTime) -> V2Index {
+        V2Index {
+            number,
+            time: Self::migrate_time(time),
+        }
+    }
+
+    fn migrate_time(time: CueTime) -> V2Time {
+        let msf = time.to_vec();
+        V2Time {
+            minute: msf[0],
+            second: msf[1],
+            frame: msf[2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::aggregates::v1::cue::Header;
+    use crate::domain::aggregates::v1::json::Pregap as V1Pregap;
+    use crate::domain::services::common::FileCheck;
+    use crate::domain::services::v2::Json2CueV2;
+    use crate::share::TextStyle;
+    use std::path::PathBuf;
+
+    fn build_v1_track(pregap: Option<(&str, Vec<u8>)>, start_at: Option<Vec<u8>>) -> V1Track {
+        V1Track {
+            number: None,
+            title: String::from("title"),
+            info: None,
+            rem: None,
+            isrc: Some(String::from("JPABC2400001")),
+            pregap: pregap.map(|(pregap_type, duration)| V1Pregap {
+                duration: V1TimeValue::Msf(duration),
+                pregap_type: String::from(pregap_type),
+            }),
+            start_at: start_at.map(V1TimeValue::Msf),
+        }
+    }
+
+    fn build_time(minute: u8, second: u8, frame: u8) -> V2Time {
+        V2Time {
+            minute,
+            second,
+            frame,
+        }
+    }
+
+    #[test]
+    fn test_migrate_track() -> Result<(), Box<dyn std::error::Error>> {
+        let included = build_v1_track(Some(("included", vec![0, 2, 10])), Some(vec![1, 0, 0]));
+        let silent = build_v1_track(Some(("silent", vec![0, 2, 0])), None);
+
+        let included = MigrateV1ToV2::migrate_track(&included, &mut Vec::new())?;
+        let silent = MigrateV1ToV2::migrate_track(&silent, &mut Vec::new())?;
+
+        assert_eq!(
+            included.indexes,
+            vec![
+                V2Index {
+                    number: 0,
+                    time: build_time(1, 0, 0)
+                },
+                V2Index {
+                    number: 1,
+                    time: build_time(1, 2, 10)
+                },
+            ]
+        );
+        assert_eq!(included.pregap, None);
+        assert_eq!(included.isrc, Some(String::from("JPABC2400001")));
+        assert_eq!(silent.pregap, Some(build_time(0, 2, 0)));
+        assert_eq!(
+            silent.indexes,
+            vec![V2Index {
+                number: 1,
+                time: build_time(0, 0, 0)
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate() -> Result<(), Box<dyn std::error::Error>> {
+        let json = V1Json {
+            schema: Some(String::from("../schemas/v1.json")),
+            version: 1,
+            title: String::from("album"),
+            genre: Some(String::from("rock")),
+            info: None,
+            rem: None,
+            first_track_number: None,
+            htoa: None,
+            files: vec![V1File {
+                path: PathBuf::from("a.wav"),
+                tracks: vec![build_v1_track(None, None)],
+                file_type: String::from("WAVE"),
+            }],
+        };
+
+        let result = MigrateV1ToV2::new(json).migrate()?;
+
+        assert_eq!(result.schema, Some(String::from("../schemas/v2.json")));
+        assert_eq!(result.version, 2);
+        assert_eq!(result.disc.title, "album");
+        assert_eq!(result.disc.genre, Some(String::from("rock")));
+        assert_eq!(result.files[0].file_type, V2FileType::Wave);
+        Ok(())
+    }
+
+    // v1とv2への移行後の文書から同じcue sheetを組み立てる
+    #[test]
+    fn test_migrate_appended_pregap() -> Result<(), Box<dyn std::error::Error>> {
+        let json = V1Json {
+            schema: None,
+            version: 1,
+            title: String::from("album"),
+            genre: None,
+            info: None,
+            rem: None,
+            first_track_number: None,
+            htoa: None,
+            files: vec![
+                V1File {
+                    path: PathBuf::from("a.wav"),
+                    tracks: vec![build_v1_track(None, None)],
+                    file_type: String::from("WAVE"),
+                },
+                V1File {
+                    path: PathBuf::from("b.wav"),
+                    tracks: vec![
+                        build_v1_track(Some(("appended", vec![0, 2, 0])), None),
+                        build_v1_track(Some(("included", vec![0, 1, 0])), Some(vec![3, 0, 0])),
+                    ],
+                    file_type: String::from("WAVE"),
+                },
+            ],
+        };
+        let file_lengths = [Some(75 * 60 * 4), Some(75 * 60 * 5)];
+
+        let migrated = MigrateV1ToV2::new(json.clone()).migrate()?;
+        let mut v1 = Json2CueV1::new(json);
+        v1.set_file_check(FileCheck::Skipped);
+        let mut v1 = v1.build_cue()?;
+        let mut v2 = Json2CueV2::new(migrated.clone());
+        v2.set_file_check(FileCheck::Skipped);
+        let mut v2 = v2.build_cue()?;
+        v1.resolve_appended_pregaps(&file_lengths)?;
+        v2.resolve_appended_pregaps(&file_lengths)?;
+
+        assert_eq!(
+            migrated.files[1].tracks[0].appended_pregap,
+            Some(build_time(0, 2, 0))
+        );
+        assert_eq!(
+            v2.to_cdtext_string(&Header::new(false, None), &TextStyle::default())?,
+            v1.to_cdtext_string(&Header::new(false, None), &TextStyle::default())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_err() {
+        let unknown = build_v1_track(Some(("unknown", vec![0, 2, 0])), None);
+
+        let Err(result) = MigrateV1ToV2::migrate_track(&unknown, &mut Vec::new()) else {
+            panic!("unknown pregap type is accepted");
+        };
+
+        assert_eq!(result.message, "unknown pregap_type: unknown");
+        assert_eq!(result.pointer.as_deref(), Some("/pregap/type"));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod converter;
+pub mod json2cue;
+pub mod migrate;
+
+pub use converter::*;
+pub use json2cue::*;
+pub use migrate::*;
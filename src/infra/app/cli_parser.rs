@@ -1,17 +1,409 @@
-use crate::app::interfaces::{Args, ArgsGetter};
-use clap::Parser;
+use crate::app::interfaces::{
+    ArgsGetter, BuildArgs, Command, DiffArgs, InspectArgs, LintArgs, MigrateArgs, OutputFormat,
+};
+use crate::domain::aggregates::common::InputFormat;
+use crate::domain::aggregates::v1::cue::{CdTextLanguage, GapLayout, Header};
+use crate::domain::services::common::{LintLevel, LintOptions};
+use crate::share::{DiagnosticFormat, Indent, LineEnding, TextEncoding, TextStyle};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct CliArgs {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    #[command(flatten)]
+    build: BuildCliArgs,
+    /// format of the errors and warnings written to stderr
+    #[arg(long, value_enum, global = true, default_value_t = CliDiagnosticFormat::Text)]
+    format: CliDiagnosticFormat,
+}
+
+#[derive(Subcommand, Debug)]
+enum CliCommand {
+    /// build a cue sheet from a json file (default)
+    Build(BuildCliArgs),
+    /// compare two inputs (json or cue sheet) track by track
+    Diff(DiffCliArgs),
+    /// print the computed disc layout and disc ids
+    Inspect(InspectCliArgs),
+    /// check the cue sheet for constructs which players and burning software may not handle
+    Lint(LintCliArgs),
+    /// rewrite a v1 json file as v2
+    Migrate(MigrateCliArgs),
+}
+
+#[derive(Args, Debug)]
+struct BuildCliArgs {
+    #[arg(required = true)]
+    input_file_path: Option<PathBuf>,
+    #[arg(required = true)]
+    target_file_path: Option<PathBuf>,
+    /// format of the input file (default: detected from the extension, otherwise json)
+    #[arg(long, value_enum)]
+    input_format: Option<CliInputFormat>,
+    /// format of the output file
+    #[arg(long, value_enum, default_value_t = CliOutputFormat::Cue)]
+    output_format: CliOutputFormat,
+    /// CD-TEXT language of the toc output
+    #[arg(long, value_enum, default_value_t = CliCdTextLanguage::En)]
+    toc_language: CliCdTextLanguage,
+    /// text encoding of the output cue sheet (ffmetadata, mkv-chapters and m3u8 are always utf-8)
+    #[arg(long, value_enum, default_value_t = CliEncoding::Utf8)]
+    encoding: CliEncoding,
+    /// transliterate characters which cannot be encoded instead of failing
+    #[arg(long)]
+    transliterate: bool,
+    /// line ending of the output cue sheet
+    #[arg(long, value_enum, default_value_t = CliLineEnding::Lf)]
+    line_ending: CliLineEnding,
+    /// indent character of the output cue sheet
+    #[arg(long, value_enum, default_value_t = CliIndent::Spaces)]
+    indent: CliIndent,
+    /// number of indent characters per level (default: 2 for spaces, 1 for tabs)
+    #[arg(long)]
+    indent_width: Option<usize>,
+    /// omit the generated REM CDRTOOLS/REM COMMENT header
+    #[arg(long)]
+    no_header: bool,
+    /// text of the generated REM COMMENT header (cannot be used with --no-header)
+    #[arg(long, conflicts_with = "no_header")]
+    comment: Option<String>,
+    /// analyze the audio and write REM REPLAYGAIN_* tags
+    #[arg(long)]
+    replaygain: bool,
+    /// compute FreeDB/MusicBrainz disc ids and write REM DISCID/REM MUSICBRAINZ_DISCID
+    #[arg(long)]
+    disc_id: bool,
+    /// write the album-level info inherited by each track explicitly on every track
+    #[arg(long)]
+    write_inherited_info: bool,
+    /// move pregaps at file switches to the head of the track's file (compliant) or the end of the previous file (noncompliant); only the cue sheet is rewritten, so the audio has to be split to match (cue output only)
+    #[arg(long, value_enum)]
+    gap_layout: Option<CliGapLayout>,
+}
+
+#[derive(Args, Debug)]
+struct DiffCliArgs {
+    old_file_path: PathBuf,
+    new_file_path: PathBuf,
+    /// format of the non-cue inputs (default: detected from the extension, otherwise json)
+    #[arg(long, value_enum)]
+    input_format: Option<CliInputFormat>,
+    /// text encoding of the input cue sheets
+    #[arg(long, value_enum, default_value_t = CliEncoding::Utf8)]
+    cue_encoding: CliEncoding,
+}
+
+#[derive(Args, Debug)]
+struct InspectCliArgs {
+    input_file_path: PathBuf,
+    /// format of the input file (default: detected from the extension, otherwise json)
+    #[arg(long, value_enum)]
+    input_format: Option<CliInputFormat>,
+}
+
+#[derive(Args, Debug)]
+struct LintCliArgs {
+    input_file_path: PathBuf,
+    /// format of the input file (default: detected from the extension, otherwise json)
+    #[arg(long, value_enum)]
+    input_format: Option<CliInputFormat>,
+    /// text encoding the cue sheet will be written in
+    #[arg(long, value_enum, default_value_t = CliEncoding::Utf8)]
+    encoding: CliEncoding,
+    /// maximum number of characters of TITLE (title-too-long)
+    #[arg(long, default_value_t = LintOptions::default().max_title_length)]
+    max_title_length: usize,
+    /// severity of a rule as <RULE>=<off|warning|error> (can be repeated)
+    #[arg(long, value_name = "RULE=LEVEL")]
+    rule: Vec<String>,
+}
+
+impl LintCliArgs {
+    fn to_lint_args(&self) -> Result<LintArgs, String> {
+        let levels = self
+            .rule
+            .iter()
+            .map(|r| parse_rule_level(r))
+            .collect::<Result<Vec<(String, LintLevel)>, String>>()?;
+        Ok(LintArgs {
+            input_file_path: self.input_file_path.clone(),
+            input_format: self.input_format.map(InputFormat::from),
+            options: LintOptions {
+                encoding: TextEncoding::from(self.encoding),
+                max_title_length: self.max_title_length,
+            },
+            levels,
+        })
+    }
+}
+
+fn parse_rule_level(value: &str) -> Result<(String, LintLevel), String> {
+    let level = match value.split_once('=') {
+        Some((id, "off")) => Some((id, LintLevel::Off)),
+        Some((id, "warning")) => Some((id, LintLevel::Warning)),
+        Some((id, "error")) => Some((id, LintLevel::Error)),
+        _ => None,
+    };
+    match level {
+        Some((id, level)) if !id.is_empty() => Ok((String::from(id), level)),
+        _ => Err(format!(
+            "invalid --rule: {} (expected <RULE>=<off|warning|error>)",
+            value
+        )),
+    }
+}
+
+#[derive(Args, Debug)]
+struct MigrateCliArgs {
     input_file_path: PathBuf,
     target_file_path: PathBuf,
+    /// format of the input file (default: detected from the extension, otherwise json)
+    #[arg(long, value_enum)]
+    input_format: Option<CliInputFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliInputFormat {
+    Json,
+    #[value(alias = "yml")]
+    Yaml,
+    Toml,
+}
+
+impl From<CliInputFormat> for InputFormat {
+    fn from(value: CliInputFormat) -> Self {
+        match value {
+            CliInputFormat::Json => InputFormat::Json,
+            CliInputFormat::Yaml => InputFormat::Yaml,
+            CliInputFormat::Toml => InputFormat::Toml,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliOutputFormat {
+    /// cue sheet
+    Cue,
+    /// cdrdao toc file
+    Toc,
+    /// audacity label track
+    AudacityLabels,
+    /// ffmpeg metadata file (FFMETADATA1)
+    Ffmetadata,
+    /// matroska chapter xml
+    MkvChapters,
+    /// comma separated track list
+    Csv,
+    /// tab separated track list
+    Tsv,
+    /// markdown track list
+    Markdown,
+    /// extended m3u8 playlist
+    M3u8,
+    /// per-track vorbis comment/id3v2 tags as json
+    Tags,
+}
+
+impl CliOutputFormat {
+    // XMLの宣言(encoding="UTF-8")、FFMETADATA1、M3U8、JSON(RFC 8259)はUTF-8で書き出す
+    fn requires_utf8(&self) -> bool {
+        matches!(
+            self,
+            CliOutputFormat::Ffmetadata
+                | CliOutputFormat::MkvChapters
+                | CliOutputFormat::M3u8
+                | CliOutputFormat::Tags
+        )
+    }
+}
+
+fn value_name<T: ValueEnum>(value: T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| String::from(v.get_name()))
+        .unwrap_or_default()
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliDiagnosticFormat {
+    /// one line per diagnostic
+    Text,
+    /// json document with code, severity, json pointer and line/column
+    Json,
+    /// SARIF 2.1.0 log
+    Sarif,
+}
+
+impl From<CliDiagnosticFormat> for DiagnosticFormat {
+    fn from(value: CliDiagnosticFormat) -> Self {
+        match value {
+            CliDiagnosticFormat::Text => DiagnosticFormat::Text,
+            CliDiagnosticFormat::Json => DiagnosticFormat::Json,
+            CliDiagnosticFormat::Sarif => DiagnosticFormat::Sarif,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliGapLayout {
+    Compliant,
+    Noncompliant,
+}
+
+impl From<CliGapLayout> for GapLayout {
+    fn from(value: CliGapLayout) -> Self {
+        match value {
+            CliGapLayout::Compliant => GapLayout::Compliant,
+            CliGapLayout::Noncompliant => GapLayout::Noncompliant,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliCdTextLanguage {
+    En,
+    Ja,
+    De,
+    Fr,
+    It,
+    Nl,
+    Es,
+}
+
+impl From<CliCdTextLanguage> for CdTextLanguage {
+    fn from(value: CliCdTextLanguage) -> Self {
+        match value {
+            CliCdTextLanguage::En => CdTextLanguage::English,
+            CliCdTextLanguage::Ja => CdTextLanguage::Japanese,
+            CliCdTextLanguage::De => CdTextLanguage::German,
+            CliCdTextLanguage::Fr => CdTextLanguage::French,
+            CliCdTextLanguage::It => CdTextLanguage::Italian,
+            CliCdTextLanguage::Nl => CdTextLanguage::Dutch,
+            CliCdTextLanguage::Es => CdTextLanguage::Spanish,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliEncoding {
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-8-bom")]
+    Utf8Bom,
+    #[value(name = "shift-jis", alias = "sjis")]
+    ShiftJis,
+    #[value(name = "latin-1", alias = "iso-8859-1")]
+    Latin1,
+}
+
+impl From<CliEncoding> for TextEncoding {
+    fn from(value: CliEncoding) -> Self {
+        match value {
+            CliEncoding::Utf8 => TextEncoding::Utf8,
+            CliEncoding::Utf8Bom => TextEncoding::Utf8Bom,
+            CliEncoding::ShiftJis => TextEncoding::ShiftJis,
+            CliEncoding::Latin1 => TextEncoding::Latin1,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliLineEnding {
+    Lf,
+    Crlf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CliIndent {
+    Spaces,
+    Tabs,
 }
 
 pub struct CliParser {
     parser: CliArgs,
 }
 
+impl BuildCliArgs {
+    const DEFAULT_SPACES_WIDTH: usize = 2;
+    const DEFAULT_TABS_WIDTH: usize = 1;
+
+    fn build_style(&self) -> TextStyle {
+        let line_ending = match self.line_ending {
+            CliLineEnding::Lf => LineEnding::Lf,
+            CliLineEnding::Crlf => LineEnding::Crlf,
+        };
+        let indent = match self.indent {
+            CliIndent::Spaces => {
+                Indent::Spaces(self.indent_width.unwrap_or(Self::DEFAULT_SPACES_WIDTH))
+            }
+            CliIndent::Tabs => Indent::Tabs(self.indent_width.unwrap_or(Self::DEFAULT_TABS_WIDTH)),
+        };
+        TextStyle::new(line_ending, indent)
+    }
+
+    fn build_header(&self) -> Header {
+        let default = Header::default();
+        // * --commentと--no-headerはclapで併用できない
+        if self.no_header {
+            return Header::new(false, None);
+        }
+        match &self.comment {
+            Some(comment) => Header::new(default.cdrtools, Some(comment.clone())),
+            None => default,
+        }
+    }
+
+    fn to_build_args(&self) -> Result<BuildArgs, String> {
+        let (Some(input_file_path), Some(target_file_path)) =
+            (&self.input_file_path, &self.target_file_path)
+        else {
+            return Err(String::from("input and target file paths are required"));
+        };
+        // * cue以外の出力ではギャップの置き方を書き換えないため、黙って無視せずエラーにする
+        if self.gap_layout.is_some() && !matches!(self.output_format, CliOutputFormat::Cue) {
+            return Err(String::from(
+                "--gap-layout can only be used with --output-format cue",
+            ));
+        }
+        // * UTF-8と決まっている形式は、他の文字コードに変換すると宣言や仕様と一致しなくなる
+        if self.output_format.requires_utf8() && !matches!(self.encoding, CliEncoding::Utf8) {
+            return Err(format!(
+                "--output-format {} is always written in utf-8 (--encoding {} cannot be used)",
+                value_name(self.output_format),
+                value_name(self.encoding)
+            ));
+        }
+        Ok(BuildArgs {
+            input_file_path: input_file_path.clone(),
+            input_format: self.input_format.map(InputFormat::from),
+            target_file_path: target_file_path.clone(),
+            output_format: match self.output_format {
+                CliOutputFormat::Cue => OutputFormat::Cue,
+                CliOutputFormat::Toc => OutputFormat::Toc(self.toc_language.into()),
+                CliOutputFormat::AudacityLabels => OutputFormat::AudacityLabels,
+                CliOutputFormat::Ffmetadata => OutputFormat::FfMetadata,
+                CliOutputFormat::MkvChapters => OutputFormat::MatroskaChapters,
+                CliOutputFormat::Csv => OutputFormat::Csv,
+                CliOutputFormat::Tsv => OutputFormat::Tsv,
+                CliOutputFormat::Markdown => OutputFormat::Markdown,
+                CliOutputFormat::M3u8 => OutputFormat::M3u8,
+                CliOutputFormat::Tags => OutputFormat::Tags,
+            },
+            encoding: self.encoding.into(),
+            transliterate: self.transliterate,
+            style: self.build_style(),
+            header: self.build_header(),
+            replaygain: self.replaygain,
+            disc_id: self.disc_id,
+            write_inherited_info: self.write_inherited_info,
+            gap_layout: self.gap_layout.map(GapLayout::from),
+        })
+    }
+}
+
 impl CliParser {
     pub fn new() -> CliParser {
         CliParser {
@@ -20,11 +412,37 @@ impl CliParser {
     }
 }
 
+impl Default for CliParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ArgsGetter for CliParser {
-    fn load(&self) -> Result<Args, String> {
-        Ok(Args {
-            input_file_path: self.parser.input_file_path.clone(),
-            target_file_path: self.parser.target_file_path.clone(),
-        })
+    fn load(&self) -> Result<Command, String> {
+        match &self.parser.command {
+            None => Ok(Command::Build(self.parser.build.to_build_args()?)),
+            Some(CliCommand::Build(build)) => Ok(Command::Build(build.to_build_args()?)),
+            Some(CliCommand::Diff(diff)) => Ok(Command::Diff(DiffArgs {
+                old_file_path: diff.old_file_path.clone(),
+                new_file_path: diff.new_file_path.clone(),
+                input_format: diff.input_format.map(InputFormat::from),
+                cue_encoding: TextEncoding::from(diff.cue_encoding),
+            })),
+            Some(CliCommand::Inspect(inspect)) => Ok(Command::Inspect(InspectArgs {
+                input_file_path: inspect.input_file_path.clone(),
+                input_format: inspect.input_format.map(InputFormat::from),
+            })),
+            Some(CliCommand::Lint(lint)) => Ok(Command::Lint(lint.to_lint_args()?)),
+            Some(CliCommand::Migrate(migrate)) => Ok(Command::Migrate(MigrateArgs {
+                input_file_path: migrate.input_file_path.clone(),
+                input_format: migrate.input_format.map(InputFormat::from),
+                target_file_path: migrate.target_file_path.clone(),
+            })),
+        }
+    }
+
+    fn diagnostic_format(&self) -> DiagnosticFormat {
+        DiagnosticFormat::from(self.parser.format)
     }
 }
@@ -0,0 +1,2 @@
+pub mod wave_decoder;
+pub use wave_decoder::*;
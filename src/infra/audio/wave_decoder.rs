@@ -0,0 +1,94 @@
+use crate::app::interfaces::AudioDecoder;
+use crate::domain::aggregates::common::AudioFormat;
+use crate::share::{Issue, IssueCode};
+use hound::{SampleFormat, WavReader};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+pub struct WaveDecoder {}
+
+impl WaveDecoder {
+    const CHUNK_FRAMES: usize = 4096;
+
+    pub fn new() -> WaveDecoder {
+        WaveDecoder {}
+    }
+
+    fn open(path: &Path) -> Result<WavReader<BufReader<File>>, Issue> {
+        match WavReader::open(path) {
+            Ok(res) => Ok(res),
+            Err(res) => Err(Issue::new(
+                IssueCode::AudioFile,
+                format!("failed to open wave file: {:?}\n{}", path, res),
+            )),
+        }
+    }
+
+    fn decode_samples<S, I>(
+        path: &Path,
+        samples: I,
+        channels: usize,
+        scale: f32,
+        on_samples: &mut dyn FnMut(&[f32]),
+    ) -> Result<(), Issue>
+    where
+        S: Into<f64>,
+        I: Iterator<Item = hound::Result<S>>,
+    {
+        let chunk_size = Self::CHUNK_FRAMES * channels;
+        let mut chunk: Vec<f32> = Vec::with_capacity(chunk_size);
+        for sample in samples {
+            match sample {
+                Ok(res) => chunk.push((res.into() / scale as f64) as f32),
+                Err(res) => {
+                    return Err(Issue::new(
+                        IssueCode::AudioFile,
+                        format!("failed to decode wave file: {:?}\n{}", path, res),
+                    ));
+                }
+            }
+            if chunk.len() == chunk_size {
+                on_samples(&chunk);
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            on_samples(&chunk);
+        }
+        Ok(())
+    }
+}
+
+impl Default for WaveDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDecoder for WaveDecoder {
+    fn probe(&self, path: &Path) -> Result<AudioFormat, Issue> {
+        let reader = Self::open(path)?;
+        let spec = reader.spec();
+        Ok(AudioFormat {
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+            length: reader.duration() as u64,
+        })
+    }
+
+    fn decode(&self, path: &Path, on_samples: &mut dyn FnMut(&[f32])) -> Result<(), Issue> {
+        let mut reader = Self::open(path)?;
+        let spec = reader.spec();
+        let channels = spec.channels as usize;
+        match spec.sample_format {
+            SampleFormat::Float => {
+                Self::decode_samples(path, reader.samples::<f32>(), channels, 1.0, on_samples)
+            }
+            SampleFormat::Int => {
+                let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+                Self::decode_samples(path, reader.samples::<i32>(), channels, scale, on_samples)
+            }
+        }
+    }
+}
@@ -1,15 +1,28 @@
 use std::process::ExitCode;
 
-use json2cuesheet::app::usecases::build_and_save_file;
+use json2cuesheet::app::interfaces::ArgsGetter;
+use json2cuesheet::app::usecases::{RunOutput, build_diagnostic, run};
 use json2cuesheet::infra::app::CliParser;
+use json2cuesheet::infra::audio::WaveDecoder;
+use json2cuesheet::share::{Severity, format_diagnostics};
 
 fn main() -> ExitCode {
     let args_getter = CliParser::new();
-    match build_and_save_file(args_getter) {
-        Ok(_) => ExitCode::SUCCESS,
-        Err(e) => {
-            println!("{}", e);
-            return ExitCode::FAILURE;
-        }
+    let format = args_getter.diagnostic_format();
+    let decoder = WaveDecoder::new();
+    // * エラーも診断として標準エラー出力に表示する
+    let output = run(args_getter, decoder).unwrap_or_else(|e| RunOutput {
+        stdout: None,
+        diagnostics: vec![build_diagnostic(Severity::Error, &e)],
+    });
+    if let Some(stdout) = &output.stdout {
+        println!("{}", stdout);
+    }
+    if let Some(diagnostics) = format_diagnostics(&output.diagnostics, format) {
+        eprintln!("{}", diagnostics);
+    }
+    match output.has_errors() {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
     }
 }
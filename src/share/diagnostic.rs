@@ -0,0 +1,246 @@
+use super::Issue;
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// 診断(エラーと警告)の出力形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    // 入力ファイル内の位置を表せない場合の位置(track 03など)
+    pub location: Option<String>,
+    pub file: Option<String>,
+    pub pointer: Option<String>,
+    // 1始まりの行と列
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: String, message: String) -> Diagnostic {
+        Diagnostic {
+            code,
+            severity,
+            message,
+            location: None,
+            file: None,
+            pointer: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    // エラーや警告から作る(メッセージは入力ファイルを前に付けたもの)
+    pub fn from_issue(severity: Severity, issue: &Issue) -> Diagnostic {
+        Diagnostic {
+            file: issue
+                .file
+                .as_ref()
+                .map(|f| f.to_string_lossy().into_owned()),
+            pointer: issue.pointer.clone(),
+            line: issue.line,
+            column: issue.column,
+            ..Diagnostic::new(
+                severity,
+                String::from(issue.code.name(severity)),
+                issue.to_string(),
+            )
+        }
+    }
+
+    pub fn to_text_string(&self) -> String {
+        let location = match &self.location {
+            Some(location) => format!(" {}", location),
+            None => String::new(),
+        };
+        format!(
+            "{}[{}]{}: {}",
+            self.severity.name(),
+            self.code,
+            location,
+            self.message
+        )
+    }
+
+    fn to_json_value(&self) -> Value {
+        json!({
+            "code": self.code,
+            "severity": self.severity.name(),
+            "message": self.message,
+            "location": self.location,
+            "file": self.file,
+            "pointer": self.pointer,
+            "line": self.line,
+            "column": self.column,
+        })
+    }
+
+    fn to_sarif_value(&self) -> Value {
+        let mut result = json!({
+            "ruleId": self.code,
+            "level": self.severity.name(),
+            "message": { "text": self.message },
+        });
+        let mut location = serde_json::Map::new();
+        if let Some(file) = &self.file {
+            let mut physical = json!({ "artifactLocation": { "uri": file } });
+            if let Some(line) = self.line {
+                physical["region"] = json!({ "startLine": line });
+                if let Some(column) = self.column {
+                    physical["region"]["startColumn"] = json!(column);
+                }
+            }
+            location.insert(String::from("physicalLocation"), physical);
+        }
+        if let Some(name) = self.pointer.as_ref().or(self.location.as_ref()) {
+            location.insert(
+                String::from("logicalLocations"),
+                json!([{ "fullyQualifiedName": name }]),
+            );
+        }
+        if !location.is_empty() {
+            result["locations"] = json!([location]);
+        }
+        result
+    }
+}
+
+// テキストは1件ずつ1行(空の場合はNone)、JSONとSARIFは常に1つの文書にする
+pub fn format_diagnostics(diagnostics: &[Diagnostic], format: DiagnosticFormat) -> Option<String> {
+    let value = match format {
+        DiagnosticFormat::Text => {
+            return (!diagnostics.is_empty()).then(|| {
+                diagnostics
+                    .iter()
+                    .map(Diagnostic::to_text_string)
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            });
+        }
+        DiagnosticFormat::Json => json!({
+            "diagnostics": diagnostics.iter().map(Diagnostic::to_json_value).collect::<Vec<Value>>(),
+        }),
+        DiagnosticFormat::Sarif => {
+            let mut rules: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+            rules.sort();
+            rules.dedup();
+            json!({
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": env!("CARGO_PKG_NAME"),
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "rules": rules.iter().map(|r| json!({ "id": r })).collect::<Vec<Value>>(),
+                        }
+                    },
+                    "results": diagnostics.iter().map(Diagnostic::to_sarif_value).collect::<Vec<Value>>(),
+                }],
+            })
+        }
+    };
+    serde_json::to_string_pretty(&value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::share::IssueCode;
+
+    #[test]
+    fn test_from_issue() {
+        let issue = Issue {
+            pointer: Some(String::from("/files/0/tracks/1/start_at")),
+            ..Issue::new(
+                IssueCode::FrameRounding,
+                String::from("\"00:01.005\" is not frame-aligned, rounded to 00:01:00"),
+            )
+        }
+        .in_file(std::path::Path::new("dir/a.json"));
+
+        let result = Diagnostic::from_issue(Severity::Warning, &issue);
+
+        assert_eq!(
+            (result.code.as_str(), result.file.as_deref()),
+            ("W001", Some("dir/a.json"))
+        );
+        assert_eq!(
+            result.pointer.as_deref(),
+            Some("/files/0/tracks/1/start_at")
+        );
+        assert_eq!(
+            result.message,
+            "\"dir/a.json\": \"00:01.005\" is not frame-aligned, rounded to 00:01:00"
+        );
+        assert_eq!((result.line, result.column), (None, None));
+    }
+
+    #[test]
+    fn test_format_diagnostics() -> Result<(), String> {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Warning,
+            String::from("title-too-long"),
+            String::from("TITLE is 95 characters long (max 80)"),
+        );
+        diagnostic.location = Some(String::from("track 03"));
+        diagnostic.file = Some(String::from("a.json"));
+        diagnostic.line = Some(4);
+        diagnostic.column = Some(7);
+        let diagnostics = vec![diagnostic];
+
+        let text = format_diagnostics(&diagnostics, DiagnosticFormat::Text);
+        let Some(json) = format_diagnostics(&diagnostics, DiagnosticFormat::Json) else {
+            return Err(String::from("json is not written"));
+        };
+        let Some(sarif) = format_diagnostics(&diagnostics, DiagnosticFormat::Sarif) else {
+            return Err(String::from("sarif is not written"));
+        };
+        let json: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let sarif: Value = serde_json::from_str(&sarif).map_err(|e| e.to_string())?;
+
+        assert_eq!(
+            text.as_deref(),
+            Some("warning[title-too-long] track 03: TITLE is 95 characters long (max 80)")
+        );
+        assert_eq!(format_diagnostics(&[], DiagnosticFormat::Text), None);
+        assert_eq!(json["diagnostics"][0]["code"], "title-too-long");
+        assert_eq!(json["diagnostics"][0]["pointer"], Value::Null);
+        assert_eq!(json["diagnostics"][0]["line"], 4);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(result["ruleId"], "title-too-long");
+        assert_eq!(result["level"], "warning");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            7
+        );
+        assert_eq!(
+            result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+            "track 03"
+        );
+        Ok(())
+    }
+}
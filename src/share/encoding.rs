@@ -0,0 +1,217 @@
+use encoding_rs::SHIFT_JIS;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    ShiftJis,
+    Latin1,
+}
+
+impl TextEncoding {
+    const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+    const LATIN1_MAX: u32 = 0xFF;
+    const FALLBACK_CHAR: &str = "?";
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 (BOM)",
+            TextEncoding::ShiftJis => "Shift_JIS",
+            TextEncoding::Latin1 => "ISO-8859-1",
+        }
+    }
+
+    // transliterate=trueの場合、変換できない文字をASCIIに音訳して書き出す
+    pub fn encode(&self, text: &str, transliterate: bool) -> Result<Vec<u8>, String> {
+        match self {
+            TextEncoding::Utf8 => Ok(Vec::from(text.as_bytes())),
+            TextEncoding::Utf8Bom => {
+                let mut result = Vec::from(Self::UTF8_BOM);
+                result.extend_from_slice(text.as_bytes());
+                Ok(result)
+            }
+            TextEncoding::ShiftJis | TextEncoding::Latin1 => {
+                self.encode_per_char(text, transliterate)
+            }
+        }
+    }
+
+    // UTF-8は先頭のBOMを取り除く(BOMの有無は区別しない)
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+                let bytes = bytes.strip_prefix(&Self::UTF8_BOM).unwrap_or(bytes);
+                match String::from_utf8(Vec::from(bytes)) {
+                    Ok(res) => Ok(res),
+                    Err(res) => Err(format!("text is not valid {}\n{}", self.name(), res)),
+                }
+            }
+            TextEncoding::ShiftJis => {
+                match SHIFT_JIS.decode_without_bom_handling_and_without_replacement(bytes) {
+                    Some(res) => Ok(res.into_owned()),
+                    None => Err(format!("text is not valid {}", self.name())),
+                }
+            }
+            TextEncoding::Latin1 => Ok(bytes.iter().map(|b| char::from(*b)).collect()),
+        }
+    }
+
+    fn encode_per_char(&self, text: &str, transliterate: bool) -> Result<Vec<u8>, String> {
+        let mut result: Vec<u8> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+
+        for (line_index, line) in text.split('\n').enumerate() {
+            if line_index > 0 {
+                result.push(b'\n');
+            }
+            for (column_index, c) in line.chars().enumerate() {
+                if let Some(bytes) = self.encode_char(c) {
+                    result.extend(bytes);
+                    continue;
+                }
+                if transliterate {
+                    result.extend(self.transliterate_char(c));
+                    continue;
+                }
+                errors.push(format!(
+                    "line {}, column {}: '{}' (U+{:04X})",
+                    line_index + 1,
+                    column_index + 1,
+                    c,
+                    c as u32
+                ));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(format!(
+                "{} character(s) cannot be encoded in {}:\n{}",
+                errors.len(),
+                self.name(),
+                errors.join("\n")
+            ));
+        }
+        Ok(result)
+    }
+
+    fn encode_char(&self, c: char) -> Option<Vec<u8>> {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => Some(Vec::from(c.to_string().as_bytes())),
+            TextEncoding::ShiftJis => {
+                let char_string = c.to_string();
+                let (bytes, _, had_errors) = SHIFT_JIS.encode(&char_string);
+                if had_errors {
+                    return None;
+                }
+                Some(bytes.into_owned())
+            }
+            TextEncoding::Latin1 => {
+                if c as u32 > Self::LATIN1_MAX {
+                    return None;
+                }
+                Some(vec![c as u8])
+            }
+        }
+    }
+
+    fn transliterate_char(&self, c: char) -> Vec<u8> {
+        let mut result: Vec<u8> = Vec::new();
+        let transliterated = deunicode::deunicode_char(c).unwrap_or(Self::FALLBACK_CHAR);
+        // 音訳結果が空の場合も文字が消えないように代替文字を使う
+        let transliterated = if transliterated.is_empty() {
+            Self::FALLBACK_CHAR
+        } else {
+            transliterated
+        };
+        for t in transliterated.chars() {
+            match self.encode_char(t) {
+                Some(bytes) => result.extend(bytes),
+                None => result.extend(Self::FALLBACK_CHAR.as_bytes()),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_utf8() -> Result<(), String> {
+        let result = TextEncoding::Utf8.encode("TITLE \"あ\"", false)?;
+
+        assert_eq!(result, "TITLE \"あ\"".as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_utf8_bom() -> Result<(), String> {
+        let result = TextEncoding::Utf8Bom.encode("TITLE", false)?;
+
+        assert_eq!(result, [0xEF, 0xBB, 0xBF, b'T', b'I', b'T', b'L', b'E']);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_shift_jis() -> Result<(), String> {
+        let result = TextEncoding::ShiftJis.encode("A\nわたかん", false)?;
+
+        assert_eq!(
+            result,
+            [b'A', b'\n', 0x82, 0xED, 0x82, 0xBD, 0x82, 0xA9, 0x82, 0xF1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_latin1() -> Result<(), String> {
+        let result = TextEncoding::Latin1.encode("Café", false)?;
+
+        assert_eq!(result, [b'C', b'a', b'f', 0xE9]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_err() -> Result<(), String> {
+        let Err(result) = TextEncoding::Latin1.encode("REM\nTITLE \"わ\"", false) else {
+            return Err(String::from("unexpectedly encoded"));
+        };
+
+        assert_eq!(
+            result,
+            String::from(
+                "1 character(s) cannot be encoded in ISO-8859-1:\nline 2, column 8: 'わ' (U+308F)"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_transliterate() -> Result<(), String> {
+        let latin1 = TextEncoding::Latin1.encode("わたかん é", true)?;
+        let shift_jis = TextEncoding::ShiftJis.encode("Café ♫", true)?;
+
+        assert_eq!(
+            latin1,
+            [b'w', b'a', b't', b'a', b'k', b'a', b'n', b' ', 0xE9]
+        );
+        assert_eq!(shift_jis, "Cafe #".as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode() -> Result<(), String> {
+        let utf8 = TextEncoding::Utf8.decode(&[0xEF, 0xBB, 0xBF, 0xE3, 0x81, 0x82])?;
+        let shift_jis = TextEncoding::ShiftJis.decode(&[0x82, 0xA0, b'A'])?;
+        let latin1 = TextEncoding::Latin1.decode(&[b'C', b'a', b'f', 0xE9])?;
+        let invalid = TextEncoding::Utf8.decode(&[0x82, 0xA0]);
+
+        assert_eq!(utf8, "あ");
+        assert_eq!(shift_jis, "あA");
+        assert_eq!(latin1, "Café");
+        assert!(invalid.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use super::Severity;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// エラーと警告の種類(診断のコード)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueCode {
+    // その他(エラーはE000、警告はW000)
+    Other,
+    InputNotReadable,
+    Syntax,
+    Version,
+    Schema,
+    Time,
+    TrackNumber,
+    AudioFile,
+    DiscLimit,
+    Encoding,
+    OutputNotWritable,
+    FrameRounding,
+}
+
+impl IssueCode {
+    pub fn name(&self, severity: Severity) -> &'static str {
+        match (self, severity) {
+            (IssueCode::Other, Severity::Error) => "E000",
+            (IssueCode::Other, Severity::Warning) => "W000",
+            (IssueCode::InputNotReadable, _) => "E001",
+            (IssueCode::Syntax, _) => "E002",
+            (IssueCode::Version, _) => "E003",
+            (IssueCode::Schema, _) => "E004",
+            (IssueCode::Time, _) => "E005",
+            (IssueCode::TrackNumber, _) => "E006",
+            (IssueCode::AudioFile, _) => "E007",
+            (IssueCode::DiscLimit, _) => "E008",
+            (IssueCode::Encoding, _) => "E009",
+            (IssueCode::OutputNotWritable, _) => "E010",
+            (IssueCode::FrameRounding, _) => "W001",
+        }
+    }
+}
+
+// コードと入力ファイル内の位置を持つエラーや警告
+// * 位置はエラーを返す箇所で決め、呼び出し元が外側の位置とメッセージの文脈を前に加える
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub code: IssueCode,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    // JSON Pointer(位置を表せない場合はNone)
+    pub pointer: Option<String>,
+    // 読み込みエラーの1始まりの行と列
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl Issue {
+    pub fn new(code: IssueCode, message: String) -> Issue {
+        Issue {
+            code,
+            message,
+            file: None,
+            pointer: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    // 外側の位置(/files/0/tracks など)を前に加える
+    pub fn within(mut self, pointer: &str) -> Issue {
+        self.pointer = Some(format!("{}{}", pointer, self.pointer.unwrap_or_default()));
+        self
+    }
+
+    // 外側の文脈をメッセージの前に加える
+    pub fn prefixed(mut self, prefix: String) -> Issue {
+        self.message = format!("{}{}", prefix, self.message);
+        self
+    }
+
+    pub fn in_file(mut self, path: &Path) -> Issue {
+        self.file = Some(path.to_path_buf());
+        self
+    }
+
+    // JSON Pointerの1つの要素(オブジェクトのキーなど)として書く
+    pub fn segment(key: &str) -> String {
+        format!("/{}", key.replace('~', "~0").replace('/', "~1"))
+    }
+}
+
+// 入力ファイルがある場合は "path": を前に付ける
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{:?}: {}", file, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for Issue {}
+
+impl From<String> for Issue {
+    fn from(message: String) -> Issue {
+        Issue::new(IssueCode::Other, message)
+    }
+}
+
+impl From<Issue> for String {
+    fn from(issue: Issue) -> String {
+        issue.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within() {
+        let issue = Issue::new(IssueCode::Time, String::from("\"zz\" is not a valid time"))
+            .within("/start_at")
+            .prefixed(String::from("start_at in index=1 is not parsable\n"))
+            .within("/1")
+            .within(&format!("/files/{}/tracks", 0))
+            .in_file(Path::new("a \"b\".json"));
+        let unlocated = Issue::from(String::from("unknown"));
+
+        assert_eq!(issue.pointer.as_deref(), Some("/files/0/tracks/1/start_at"));
+        assert_eq!(
+            issue.to_string(),
+            "\"a \\\"b\\\".json\": start_at in index=1 is not parsable\n\"zz\" is not a valid time"
+        );
+        assert_eq!(issue.code.name(Severity::Error), "E005");
+        assert_eq!(unlocated.pointer, None);
+        assert_eq!(unlocated.code.name(Severity::Warning), "W000");
+    }
+
+    #[test]
+    fn test_segment() {
+        assert_eq!(Issue::segment("a/b~c"), "/a~1b~0c");
+    }
+}
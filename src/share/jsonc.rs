@@ -0,0 +1,284 @@
+use super::line_and_column;
+
+// JSONCのコメント(// と /* */)と末尾のカンマを取り除く
+// * エラー位置がずれないよう、取り除いた部分は空白に置き換えて改行は残す
+pub fn strip_jsonc(source: &str) -> String {
+    let stripped = strip_comments(source.as_bytes());
+    let stripped = strip_trailing_commas(stripped);
+    // 置き換えるのはASCIIとコメント内の文字全体のみなのでUTF-8として正しい
+    String::from_utf8(stripped).unwrap_or_else(|_| String::from(source))
+}
+
+fn blank(byte: u8) -> u8 {
+    match byte {
+        b'\n' | b'\r' => byte,
+        _ => b' ',
+    }
+}
+
+fn strip_comments(source: &[u8]) -> Vec<u8> {
+    let mut result: Vec<u8> = Vec::with_capacity(source.len());
+    let mut index = 0;
+
+    while index < source.len() {
+        match (source[index], source.get(index + 1)) {
+            (b'"', _) => {
+                let end = find_string_end(source, index);
+                result.extend_from_slice(&source[index..end]);
+                index = end;
+            }
+            (b'/', Some(b'/')) => {
+                while index < source.len() && source[index] != b'\n' {
+                    result.push(blank(source[index]));
+                    index += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                let end = match source[index + 2..].windows(2).position(|w| w == b"*/") {
+                    Some(position) => index + 2 + position + 2,
+                    None => source.len(),
+                };
+                result.extend(source[index..end].iter().map(|b| blank(*b)));
+                index = end;
+            }
+            (byte, _) => {
+                result.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    result
+}
+
+fn strip_trailing_commas(mut source: Vec<u8>) -> Vec<u8> {
+    let mut index = 0;
+    // 直前の空白以外の文字
+    let mut previous: Option<u8> = None;
+
+    while index < source.len() {
+        match source[index] {
+            b'"' => {
+                index = find_string_end(&source, index);
+                previous = Some(b'"');
+            }
+            b',' => {
+                let next = source[index + 1..]
+                    .iter()
+                    .find(|b| !b.is_ascii_whitespace());
+                // * 値のないカンマ(`[,]` や `[1,,]`)は構文エラーとなるよう残す
+                let follows_value = !matches!(previous, None | Some(b'[' | b'{' | b','));
+                if follows_value && matches!(next, Some(b'}') | Some(b']')) {
+                    source[index] = b' ';
+                } else {
+                    previous = Some(b',');
+                }
+                index += 1;
+            }
+            byte => {
+                if !byte.is_ascii_whitespace() {
+                    previous = Some(byte);
+                }
+                index += 1;
+            }
+        }
+    }
+
+    source
+}
+
+// 文字列の終わりの次の位置(閉じていない場合は末尾)
+fn find_string_end(source: &[u8], start: usize) -> usize {
+    let mut index = start + 1;
+
+    while index < source.len() {
+        match source[index] {
+            b'\\' => index += 2,
+            b'"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+
+    source.len()
+}
+
+// JSON Pointerが指す位置の行と列(1始まり)を返す(オブジェクトのメンバーはキーの位置)
+// * 見つからない場合はNone
+pub fn locate_pointer(source: &str, pointer: &str) -> Option<(usize, usize)> {
+    let stripped = strip_jsonc(source);
+    let bytes = stripped.as_bytes();
+    let mut index = skip_whitespace(bytes, 0);
+    let mut target = index;
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match bytes.get(index)? {
+            b'{' => {
+                index = skip_whitespace(bytes, index + 1);
+                loop {
+                    if bytes.get(index)? != &b'"' {
+                        return None;
+                    }
+                    let key_end = find_string_end(bytes, index);
+                    let key: String = serde_json::from_str(&stripped[index..key_end]).ok()?;
+                    let key_start = index;
+                    index = skip_whitespace(bytes, key_end);
+                    if bytes.get(index)? != &b':' {
+                        return None;
+                    }
+                    index = skip_whitespace(bytes, index + 1);
+                    if key == segment {
+                        target = key_start;
+                        break;
+                    }
+                    index = skip_whitespace(bytes, skip_value(bytes, index));
+                    if bytes.get(index)? != &b',' {
+                        return None;
+                    }
+                    index = skip_whitespace(bytes, index + 1);
+                }
+            }
+            b'[' => {
+                let position = segment.parse::<usize>().ok()?;
+                index = skip_whitespace(bytes, index + 1);
+                for _ in 0..position {
+                    index = skip_whitespace(bytes, skip_value(bytes, index));
+                    if bytes.get(index)? != &b',' {
+                        return None;
+                    }
+                    index = skip_whitespace(bytes, index + 1);
+                }
+                if matches!(bytes.get(index)?, b']') {
+                    return None;
+                }
+                target = index;
+            }
+            _ => return None,
+        }
+    }
+
+    // 取り除いた部分はバイト数を変えずに置き換えているので元の文字列で数える
+    Some(line_and_column(source, target))
+}
+
+fn skip_whitespace(source: &[u8], start: usize) -> usize {
+    let mut index = start;
+    while index < source.len() && source[index].is_ascii_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+// 値の終わりの次の位置
+fn skip_value(source: &[u8], start: usize) -> usize {
+    let mut index = start;
+    let mut depth = 0;
+
+    while index < source.len() {
+        match source[index] {
+            b'"' => {
+                index = find_string_end(source, index);
+                if depth == 0 {
+                    return index;
+                }
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            b',' | b'}' | b']' if depth == 0 => return index,
+            _ => {}
+        }
+        index += 1;
+    }
+
+    source.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let source = [
+            "{",
+            "  // 編集メモ",
+            "  \"title\": \"a // b /* c */\", /* 旧タイトル: \"x\" */",
+            "  \"list\": [1, 2,],",
+            "}",
+        ]
+        .join("\n");
+
+        let result = strip_jsonc(&source);
+
+        assert_eq!(
+            result.lines().map(str::trim_end).collect::<Vec<&str>>(),
+            vec![
+                "{",
+                "",
+                "  \"title\": \"a // b /* c */\",",
+                "  \"list\": [1, 2 ]",
+                "}",
+            ]
+        );
+        assert_eq!(result.len(), source.len());
+    }
+
+    #[test]
+    fn test_strip_jsonc_escaped_quote() {
+        let source = "{\"a\": \"\\\",]\", \"b\": 1,}";
+
+        let result = strip_jsonc(source);
+
+        assert_eq!(result, "{\"a\": \"\\\",]\", \"b\": 1 }");
+    }
+
+    #[test]
+    fn test_strip_jsonc_bare_comma() {
+        let sources = ["[,]", "{,}", "[1,,]", "[1, /* a */ ,]"];
+
+        let results = sources.map(|s| serde_json::from_str::<serde_json::Value>(&strip_jsonc(s)));
+
+        assert!(results.iter().all(|r| r.is_err()), "{:?}", results);
+    }
+
+    #[test]
+    fn test_locate_pointer() {
+        let source = [
+            "{",
+            "  // \"files\": []",
+            "  \"title\": \"ア,[\", \"files\": [",
+            "    {\"tracks\": [{\"title\": \"a\"}, /* , */ {\"title\": \"b\"},]},",
+            "    {\"tracks\": [1, {\"start_at\": [0, 0, 0]}]}",
+            "  ]",
+            "}",
+        ]
+        .join("\n");
+
+        let results = [
+            locate_pointer(&source, ""),
+            locate_pointer(&source, "/files"),
+            locate_pointer(&source, "/files/0/tracks/1"),
+            locate_pointer(&source, "/files/1/tracks/1/start_at"),
+            locate_pointer(&source, "/files/1/tracks/2"),
+            locate_pointer(&source, "/missing"),
+        ];
+
+        assert_eq!(
+            results,
+            [
+                Some((1, 1)),
+                Some((3, 19)),
+                Some((4, 41)),
+                Some((5, 21)),
+                None,
+                None,
+            ]
+        );
+    }
+}
@@ -1,2 +1,10 @@
+pub mod diagnostic;
+pub use diagnostic::*;
+pub mod encoding;
+pub use encoding::*;
+pub mod issue;
+pub use issue::*;
+pub mod jsonc;
+pub use jsonc::*;
 pub mod util;
 pub use util::*;
@@ -1,13 +1,67 @@
-const INDENT: &str = "  ";
-const LF: &str = "\n";
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub line_ending: LineEnding,
+    pub indent: Indent,
+}
+
+impl TextStyle {
+    const DEFAULT_INDENT_WIDTH: usize = 2;
 
-pub fn build_indent_string(base_string: &String) -> String {
-    let result = String::from(INDENT);
+    pub fn new(line_ending: LineEnding, indent: Indent) -> TextStyle {
+        TextStyle {
+            line_ending,
+            indent,
+        }
+    }
+
+    fn indent_string(&self) -> String {
+        match self.indent {
+            Indent::Spaces(width) => " ".repeat(width),
+            Indent::Tabs(width) => "\t".repeat(width),
+        }
+    }
+
+    fn line_ending_string(&self) -> &'static str {
+        match self.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle::new(LineEnding::Lf, Indent::Spaces(Self::DEFAULT_INDENT_WIDTH))
+    }
+}
+
+pub fn build_indent_string(base_string: &str, style: &TextStyle) -> String {
+    let result = style.indent_string();
     result + base_string
 }
 
-pub fn join_strings_with_lf(strings: &Vec<String>) -> String {
-    strings.join(LF)
+pub fn join_strings_with_line_ending(strings: &[String], style: &TextStyle) -> String {
+    strings.join(style.line_ending_string())
+}
+
+// 文字列中の位置(バイト)の1始まりの行と列(列は文字数で数える)
+pub fn line_and_column(source: &str, index: usize) -> (usize, usize) {
+    let before = &source[..index];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or(before).chars().count() + 1;
+    (line, column)
 }
 
 #[cfg(test)]
@@ -18,17 +72,60 @@ mod tests {
     fn test_build_indent_string() {
         let test_string = String::from("hogege");
 
-        let result = build_indent_string(&test_string);
+        let result = build_indent_string(&test_string, &TextStyle::default());
 
         assert_eq!(result, String::from("  hogege"));
     }
 
     #[test]
-    fn test_join_strings_with_lf() {
+    fn test_build_indent_string_spaces() {
+        let test_string = String::from("hogege");
+        let style = TextStyle::new(LineEnding::Lf, Indent::Spaces(4));
+
+        let result = build_indent_string(&test_string, &style);
+
+        assert_eq!(result, String::from("    hogege"));
+    }
+
+    #[test]
+    fn test_build_indent_string_tabs() {
+        let test_string = String::from("hogege");
+        let style_1 = TextStyle::new(LineEnding::Crlf, Indent::Tabs(1));
+        let style_2 = TextStyle::new(LineEnding::Crlf, Indent::Tabs(2));
+
+        let result_1 = build_indent_string(&test_string, &style_1);
+        let result_2 = build_indent_string(&test_string, &style_2);
+
+        assert_eq!(result_1, String::from("\thogege"));
+        assert_eq!(result_2, String::from("\t\thogege"));
+    }
+
+    #[test]
+    fn test_build_indent_string_zero_width() {
+        let test_string = String::from("hogege");
+        let style = TextStyle::new(LineEnding::Lf, Indent::Spaces(0));
+
+        let result = build_indent_string(&test_string, &style);
+
+        assert_eq!(result, String::from("hogege"));
+    }
+
+    #[test]
+    fn test_join_strings_with_line_ending_lf() {
         let test_strings = vec![String::from("hoge"), String::from("fg")];
 
-        let result = join_strings_with_lf(&test_strings);
+        let result = join_strings_with_line_ending(&test_strings, &TextStyle::default());
 
         assert_eq!(result, String::from("hoge\nfg"));
     }
+
+    #[test]
+    fn test_join_strings_with_line_ending_crlf() {
+        let test_strings = vec![String::from("hoge"), String::from("fg")];
+        let style = TextStyle::new(LineEnding::Crlf, Indent::Spaces(2));
+
+        let result = join_strings_with_line_ending(&test_strings, &style);
+
+        assert_eq!(result, String::from("hoge\r\nfg"));
+    }
 }
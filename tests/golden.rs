@@ -0,0 +1,222 @@
+// 入力ファイルからcue sheetを書き出し、tests/golden以下のスナップショットと比べる
+// * <ケース>.json/.yaml/.toml を入力とし、<ケース>.cue(書き出したcue sheet)と
+//   <ケース>.stderr(エラー・警告)を期待する出力とする
+// * <ケース>.args がある場合は1行1つのオプションとして渡す
+// * UPDATE_GOLDEN=1 cargo test --test golden でスナップショットを書き直す
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+const INPUT_EXTENSIONS: [&str; 4] = ["json", "jsonc", "yaml", "toml"];
+// 入力から参照する音声ファイル(ファイル名, 秒数)
+const AUDIO_FILES: [(&str, u32); 4] =
+    [("1.wav", 10), ("2.wav", 10), ("3.wav", 10), ("htoa.wav", 3)];
+const SAMPLE_RATE: u32 = 44100;
+// 作業ディレクトリの絶対パスを置き換える文字列
+const DIR_PLACEHOLDER: &str = "$DIR";
+
+struct Snapshot {
+    cue: Option<String>,
+    stderr: Option<String>,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+fn prepare_work_dir() -> Result<PathBuf, String> {
+    let work_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    if work_dir.exists() {
+        fs::remove_dir_all(&work_dir).map_err(|e| format!("{:?}: {}", work_dir, e))?;
+    }
+    fs::create_dir_all(&work_dir).map_err(|e| format!("{:?}: {}", work_dir, e))?;
+    // * 書き出したFILEのパスを比べられるよう、作業ディレクトリは絶対パスにする
+    let work_dir = fs::canonicalize(&work_dir).map_err(|e| format!("{:?}: {}", work_dir, e))?;
+    for (name, seconds) in AUDIO_FILES {
+        write_silence(&work_dir.join(name), seconds)?;
+    }
+    Ok(work_dir)
+}
+
+fn write_silence(path: &Path, seconds: u32) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(|e| e.to_string())?;
+    for _ in 0..SAMPLE_RATE * seconds * u32::from(spec.channels) {
+        writer.write_sample(0i16).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+fn list_cases() -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(golden_dir()).map_err(|e| e.to_string())?;
+    let mut cases: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| INPUT_EXTENSIONS.contains(&e))
+        })
+        .collect();
+    cases.sort();
+    Ok(cases)
+}
+
+fn read_args(input: &Path) -> Result<Vec<String>, String> {
+    let args_path = input.with_extension("args");
+    if !args_path.exists() {
+        return Ok(Vec::new());
+    }
+    let source = fs::read_to_string(&args_path).map_err(|e| format!("{:?}: {}", args_path, e))?;
+    Ok(source
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn normalize(text: &str, work_dir: &Path) -> String {
+    let text = text.replace("\r\n", "\n");
+    text.replace(&work_dir.to_string_lossy().into_owned(), DIR_PLACEHOLDER)
+}
+
+fn run_case(input: &Path, work_dir: &Path) -> Result<Snapshot, String> {
+    let Some(file_name) = input.file_name() else {
+        return Err(format!("{:?}: file name is not readable", input));
+    };
+    fs::copy(input, work_dir.join(file_name)).map_err(|e| format!("{:?}: {}", input, e))?;
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    let output_name = format!("{}.out.cue", stem);
+    let args = read_args(input)?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_json2cuesheet"))
+        .current_dir(work_dir)
+        .arg(file_name)
+        .arg(&output_name)
+        .args(&args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    let output_path = work_dir.join(&output_name);
+    let cue = match output_path.exists() {
+        true => Some(fs::read_to_string(&output_path).map_err(|e| e.to_string())?),
+        false => None,
+    };
+    if output.status.success() != cue.is_some() {
+        return Err(format!(
+            "{}: exit status ({}) does not match the output",
+            stem, output.status
+        ));
+    }
+
+    // * 書き出したcue sheetを読み直し、入力との違いがないことを確かめる
+    if cue.is_some() && args.is_empty() {
+        let diff = Command::new(env!("CARGO_BIN_EXE_json2cuesheet"))
+            .current_dir(work_dir)
+            .arg("diff")
+            .arg(file_name)
+            .arg(&output_name)
+            .output()
+            .map_err(|e| e.to_string())?;
+        let stdout = String::from_utf8_lossy(&diff.stdout);
+        if !diff.status.success() || stdout.trim_end() != "no changes" {
+            return Err(format!(
+                "{}: the written cue sheet differs from the input:\n{}{}",
+                stem,
+                stdout,
+                String::from_utf8_lossy(&diff.stderr)
+            ));
+        }
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(Snapshot {
+        cue: cue.map(|c| normalize(&c, work_dir)),
+        stderr: (!stderr.is_empty()).then(|| normalize(&stderr, work_dir)),
+    })
+}
+
+// 期待する出力と比べ、違う場合は理由を返す(更新する場合は書き直す)
+fn check_snapshot(path: &Path, actual: Option<&str>, update: bool) -> Result<(), String> {
+    let expected = match path.exists() {
+        true => Some(fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?),
+        false => None,
+    };
+    if expected
+        .as_deref()
+        .map(|e| e.replace("\r\n", "\n"))
+        .as_deref()
+        == actual
+    {
+        return Ok(());
+    }
+    if update {
+        let result = match actual {
+            Some(actual) => fs::write(path, actual),
+            None => fs::remove_file(path),
+        };
+        return result.map_err(|e| format!("{:?}: {}", path, e));
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    Err(match (expected, actual) {
+        (None, Some(actual)) => format!("{} is missing, actual:\n{}", file_name, actual),
+        (Some(_), None) => format!("{} is expected but nothing was written", file_name),
+        (expected, actual) => format!(
+            "{} does not match\n--- expected\n{}\n--- actual\n{}",
+            file_name,
+            expected.unwrap_or_default(),
+            actual.unwrap_or_default()
+        ),
+    })
+}
+
+#[test]
+fn test_golden() -> Result<(), String> {
+    let update = env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+    let work_dir = prepare_work_dir()?;
+    let cases = list_cases()?;
+    if cases.is_empty() {
+        return Err(format!("no cases in {:?}", golden_dir()));
+    }
+
+    let mut failures: Vec<String> = Vec::new();
+    for input in cases.iter() {
+        let result = run_case(input, &work_dir).and_then(|snapshot| {
+            check_snapshot(
+                &input.with_extension("cue"),
+                snapshot.cue.as_deref(),
+                update,
+            )?;
+            check_snapshot(
+                &input.with_extension("stderr"),
+                snapshot.stderr.as_deref(),
+                update,
+            )
+        });
+        if let Err(res) = result {
+            failures.push(format!(
+                "{:?}: {}",
+                input.file_name().unwrap_or_default(),
+                res
+            ));
+        }
+    }
+    // * 差分を読めるよう、エラーの文字列ではなくそのまま表示する
+    assert!(
+        failures.is_empty(),
+        "{} of {} case(s) failed (set {}=1 to update the snapshots)\n\n{}",
+        failures.len(),
+        cases.len(),
+        UPDATE_ENV,
+        failures.join("\n\n")
+    );
+    Ok(())
+}
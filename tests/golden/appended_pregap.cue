@@ -0,0 +1,15 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Appended Pregap"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 00 00:08:00
+FILE "$DIR/2.wav" WAVE
+    INDEX 01 00:00:00
+  TRACK 03 AUDIO
+    TITLE "Three"
+    INDEX 01 00:05:00
\ No newline at end of file
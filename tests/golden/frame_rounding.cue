@@ -0,0 +1,10 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Frame Rounding"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 01 00:01:00
\ No newline at end of file
@@ -0,0 +1,12 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Gap Layout"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 01 00:00:00
+FILE "$DIR/2.wav" WAVE
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 00 00:00:00
+    INDEX 01 00:02:00
\ No newline at end of file
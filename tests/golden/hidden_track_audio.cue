@@ -0,0 +1,12 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Hidden Track"
+FILE "$DIR/htoa.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 00 00:00:00
+FILE "$DIR/1.wav" WAVE
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 01 00:05:00
\ No newline at end of file
@@ -0,0 +1,11 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Included Pregap"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 00 00:04:00
+    INDEX 01 00:05:30
\ No newline at end of file
@@ -0,0 +1,20 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Info Inheritance"
+REM GENRE "Pop"
+REM DATE 2024
+REM COMPOSER "Writer"
+PERFORMER "Band"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "Inherited"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Overridden"
+    REM ARRANGER "Arranger"
+    PERFORMER "Guest"
+    INDEX 01 00:03:00
+  TRACK 03 AUDIO
+    TITLE "Cleared"
+    REM COMPOSER ""
+    INDEX 01 00:06:00
\ No newline at end of file
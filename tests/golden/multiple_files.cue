@@ -0,0 +1,19 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Multiple Files"
+FILE "$DIR/1.wav" WAVE
+  TRACK 04 AUDIO
+    TITLE "Four"
+    INDEX 01 00:00:00
+  TRACK 05 AUDIO
+    TITLE "Five"
+    INDEX 01 00:06:00
+FILE "$DIR/2.wav" WAVE
+  TRACK 06 AUDIO
+    TITLE "Six"
+    INDEX 01 00:00:00
+FILE "$DIR/3.wav" WAVE
+  TRACK 07 AUDIO
+    TITLE "Seven"
+    ISRC JPAB01234567
+    INDEX 01 00:00:00
\ No newline at end of file
@@ -0,0 +1,11 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+TITLE "Silent Pregap"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    PREGAP 00:02:00
+    INDEX 01 00:04:00
\ No newline at end of file
@@ -0,0 +1,18 @@
+REM CDRTOOLS
+REM COMMENT Generated by Json2Cue
+CATALOG 4988001234567
+TITLE "Version 2"
+FILE "$DIR/1.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One"
+    ISRC JPAB01234567
+    INDEX 01 00:00:00
+    INDEX 02 00:02:00
+  TRACK 02 AUDIO
+    TITLE "Two"
+    INDEX 00 00:05:00
+    INDEX 01 00:06:00
+  TRACK 03 AUDIO
+    TITLE "Three"
+    PREGAP 00:01:00
+    INDEX 01 00:08:00
\ No newline at end of file